@@ -15,9 +15,13 @@
 use std::sync::Arc;
 
 use arrow_arith::arity::binary;
-use arrow_array::{Int32Array, RecordBatch, StringArray};
+use arrow_array::cast::AsArray;
+use arrow_array::{
+    BooleanArray, Decimal128Array, Float64Array, Int32Array, RecordBatch, StringArray,
+};
 use arrow_schema::{DataType, Field, Schema};
 use arrow_udf::function;
+use arrow_udf::types::Decimal;
 use arrow_udf_js::Runtime as JsRuntime;
 use arrow_udf_js_deno::Runtime as DenoRuntime;
 use arrow_udf_python::Runtime as PythonRuntime;
@@ -128,6 +132,160 @@ def gcd(a: int, b: int) -> int:
     });
 }
 
+fn bench_eval_checked_div(c: &mut Criterion) {
+    #[function("checked_div(int, int) -> int")]
+    fn checked_div(a: i32, b: i32) -> Result<i32, &'static str> {
+        a.checked_div(b).ok_or("division by zero")
+    }
+
+    // no error occurs, so the error column is never materialized
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ])),
+        vec![
+            Arc::new(Int32Array::from_iter(0..1024)),
+            Arc::new(Int32Array::from_iter(1..1025)),
+        ],
+    )
+    .unwrap();
+
+    c.bench_function("checked_div/rust", |bencher| {
+        bencher.iter(|| checked_div_int32_int32_int32_eval(&input).unwrap())
+    });
+}
+
+fn bench_eval_split(c: &mut Criterion) {
+    #[function("split(string) -> setof string")]
+    fn split(s: &str) -> impl Iterator<Item = &str> {
+        s.splitn(4, ',')
+    }
+
+    #[function("split_bounded(string) -> setof string", max_rows_per_input = 4)]
+    fn split_bounded(s: &str) -> impl Iterator<Item = &str> {
+        s.splitn(4, ',')
+    }
+
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("s", DataType::Utf8, true)])),
+        vec![Arc::new(StringArray::from_iter_values(
+            (0..1024).map(|i| format!("{i},{i}")),
+        ))],
+    )
+    .unwrap();
+
+    // the bounded version builds the output without a generator
+    let mut group = c.benchmark_group("split");
+    group.bench_function("rust", |bencher| {
+        bencher.iter(|| split_string_string_eval(&input).unwrap().count())
+    });
+    group.bench_function("rust/bounded", |bencher| {
+        bencher.iter(|| split_bounded_string_string_eval(&input).unwrap().count())
+    });
+    group.finish();
+}
+
+fn bench_eval_add3(c: &mut Criterion) {
+    let js_code = r#"
+    export function add3(a, b, c) {
+        return a + b + c;
+    }
+    "#;
+
+    const N: i32 = 1_000_000;
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+            Field::new("c", DataType::Int32, true),
+        ])),
+        vec![
+            Arc::new(Int32Array::from_iter(0..N)),
+            Arc::new(Int32Array::from_iter(0..N)),
+            Arc::new(Int32Array::from_iter(0..N)),
+        ],
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("add3");
+    group.sample_size(10);
+    group.bench_function("js", |bencher| {
+        let mut rt = JsRuntime::new().unwrap();
+        rt.add_function(
+            "add3",
+            DataType::Int32,
+            arrow_udf_js::CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+        bencher.iter(|| rt.call("add3", &input).unwrap())
+    });
+    // the columns are passed as typed arrays without converting each value
+    group.bench_function("js/typed_array", |bencher| {
+        let mut rt = JsRuntime::new().unwrap();
+        rt.add_function_with_options(
+            "add3",
+            DataType::Int32,
+            arrow_udf_js::CallMode::ReturnNullOnNullInput,
+            r#"
+            export function add3({ a, b, c }, n) {
+                const result = new Int32Array(n);
+                for (let i = 0; i < n; i++) {
+                    result[i] = a[i] + b[i] + c[i];
+                }
+                return result;
+            }
+            "#,
+            arrow_udf_js::FunctionOptions::default().typed_array_mode(true),
+        )
+        .unwrap();
+        bencher.iter(|| rt.call("add3", &input).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_eval_mixed_args(c: &mut Criterion) {
+    let js_code = r#"
+    export function mixed(a, b, c, d) {
+        return d ? a + b + c.length : 0;
+    }
+    "#;
+
+    const N: i32 = 1_000_000;
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Float64, true),
+            Field::new("c", DataType::Utf8, true),
+            Field::new("d", DataType::Boolean, true),
+        ])),
+        vec![
+            Arc::new(Int32Array::from_iter(0..N)),
+            Arc::new(Float64Array::from_iter((0..N).map(|i| i as f64))),
+            Arc::new(StringArray::from_iter_values((0..N).map(|i| i.to_string()))),
+            Arc::new(BooleanArray::from_iter((0..N).map(|i| Some(i % 2 == 0)))),
+        ],
+    )
+    .unwrap();
+
+    // the arguments are converted column by column, dispatching on the data type once per column
+    let mut group = c.benchmark_group("mixed_args");
+    group.sample_size(10);
+    group.bench_function("js", |bencher| {
+        let mut rt = JsRuntime::new().unwrap();
+        rt.add_function(
+            "mixed",
+            DataType::Float64,
+            arrow_udf_js::CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+        bencher.iter(|| rt.call("mixed", &input).unwrap())
+    });
+    group.finish();
+}
+
 fn bench_eval_range(c: &mut Criterion) {
     let js_code = r#"
     export function* range(n) {
@@ -299,6 +457,85 @@ def decimal_(a):
     });
 }
 
+fn bench_eval_decimal_add(c: &mut Criterion) {
+    #[function("decimal_add(decimal, decimal) -> decimal")]
+    #[function("decimal_add(decimal128(18,2), decimal128(18,2)) -> decimal128(18,2)")]
+    fn decimal_add(a: Decimal, b: Decimal) -> Decimal {
+        a + b
+    }
+
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![decimal_field("a"), decimal_field("b")])),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                (0..1024).map(|i| format!("{i}.25")),
+            )),
+            Arc::new(StringArray::from(vec!["0.50"; 1024])),
+        ],
+    )
+    .unwrap();
+    c.bench_function("decimal_add/string", |bencher| {
+        bencher.iter(|| decimal_add_decimal_decimal_decimal_eval(&input).unwrap())
+    });
+
+    let decimal128 = |values: Vec<i128>| {
+        Arc::new(
+            Decimal128Array::from(values)
+                .with_precision_and_scale(18, 2)
+                .unwrap(),
+        )
+    };
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Decimal128(18, 2), true),
+            Field::new("b", DataType::Decimal128(18, 2), true),
+        ])),
+        vec![
+            decimal128((0..1024).map(|i| i * 100 + 25).collect()),
+            decimal128(vec![50; 1024]),
+        ],
+    )
+    .unwrap();
+    c.bench_function("decimal_add/decimal128", |bencher| {
+        bencher.iter(|| {
+            decimal_add_decimal128_18_2_decimal128_18_2_decimal128_18_2_eval(&input).unwrap()
+        })
+    });
+}
+
+fn bench_eval_and(c: &mut Criterion) {
+    #[function("and(boolean, boolean) -> boolean")]
+    fn and(a: bool, b: bool) -> bool {
+        a && b
+    }
+
+    let input = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Boolean, true),
+            Field::new("b", DataType::Boolean, true),
+        ])),
+        vec![
+            Arc::new(BooleanArray::from_iter(
+                (0..1_000_000).map(|i| Some(i % 2 == 0)),
+            )),
+            Arc::new(BooleanArray::from_iter(
+                (0..1_000_000).map(|i| Some(i % 3 == 0)),
+            )),
+        ],
+    )
+    .unwrap();
+
+    c.bench_function("and/native", |bencher| {
+        let a = input.column(0).as_boolean();
+        let b = input.column(1).as_boolean();
+        bencher.iter(|| arrow_arith::boolean::and(a, b).unwrap())
+    });
+
+    c.bench_function("and/rust", |bencher| {
+        bencher.iter(|| and_boolean_boolean_boolean_eval(&input).unwrap())
+    });
+}
+
 fn bench_eval_sum(c: &mut Criterion) {
     let input = RecordBatch::try_new(
         Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, true)])),
@@ -357,8 +594,14 @@ def retract(state, value):
 criterion_group!(
     benches,
     bench_eval_gcd,
+    bench_eval_checked_div,
+    bench_eval_split,
+    bench_eval_add3,
+    bench_eval_mixed_args,
     bench_eval_range,
     bench_eval_decimal,
+    bench_eval_decimal_add,
+    bench_eval_and,
     bench_eval_sum
 );
 criterion_main!(benches);
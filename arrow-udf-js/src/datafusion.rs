@@ -0,0 +1,193 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Integration with [DataFusion](https://datafusion.apache.org).
+//!
+//! A scalar function added to a [`RuntimePool`] can be registered as a DataFusion [`ScalarUDF`],
+//! which may be called by multiple threads at the same time:
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use arrow_udf_js::{Runtime, RuntimePool, CallMode, FunctionOptions};
+//! # use arrow_udf_js::datafusion::JsScalarUDF;
+//! # use arrow_schema::DataType;
+//! # use datafusion_expr::{ScalarUDF, Volatility};
+//! let mut pool = RuntimePool::new(4, Runtime::builder()).unwrap();
+//! pool
+//!     .add_function_with_options(
+//!         "gcd",
+//!         DataType::Int32,
+//!         CallMode::ReturnNullOnNullInput,
+//!         "export function gcd(a, b) { while (b) [a, b] = [b, a % b]; return a; }",
+//!         FunctionOptions::default().arg_types(vec![DataType::Int32, DataType::Int32]),
+//!     )
+//!     .unwrap();
+//!
+//! let udf = JsScalarUDF::new(Arc::new(pool), "gcd", Volatility::Immutable).unwrap();
+//! let udf = ScalarUDF::from(udf);
+//! // register it with `SessionContext::register_udf`
+//! ```
+//!
+//! Arguments and results are passed to the runtime as arrays, and scalar values are converted to
+//! arrays of one row.
+//!
+//! # Arrow versions
+//!
+//! The arrays of DataFusion are passed to the runtime as is, so `arrow-array` and `arrow-schema`
+//! must resolve to the version used by DataFusion, i.e. arrow 52 for DataFusion 40. The arrow
+//! dependencies of this crate are bounded to arrow 52 for this reason.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion_common::{DataFusionError, ScalarValue};
+use datafusion_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+
+use crate::RuntimePool;
+
+/// A DataFusion scalar function backed by a function of a [`RuntimePool`].
+///
+/// Each call borrows a runtime from the pool. The arguments are passed to [`Runtime::call`] as the
+/// columns of a record batch, named by [`FunctionOptions::arg_names`] if specified, otherwise
+/// `arg0`, `arg1`, etc. Scalar arguments are converted to arrays of one row, and the result is
+/// converted back to a scalar if all arguments are scalars.
+///
+/// Table functions and multi-column functions are not supported.
+///
+/// [`Runtime::call`]: crate::Runtime::call
+/// [`FunctionOptions::arg_names`]: crate::FunctionOptions::arg_names
+#[derive(Debug)]
+pub struct JsScalarUDF {
+    pool: Arc<RuntimePool>,
+    name: String,
+    signature: Signature,
+    return_type: DataType,
+    arg_names: Option<Vec<String>>,
+}
+
+impl JsScalarUDF {
+    /// Create a DataFusion scalar function from the function `name` of the pool.
+    ///
+    /// The signature is exactly the argument types specified by [`FunctionOptions::arg_types`],
+    /// or any arguments if they are not specified.
+    ///
+    /// [`FunctionOptions::arg_types`]: crate::FunctionOptions::arg_types
+    pub fn new(pool: Arc<RuntimePool>, name: &str, volatility: Volatility) -> Result<Self> {
+        let info = pool
+            .get()
+            .function_info(name)
+            .with_context(|| format!("function \"{name}\" not found"))?;
+        let signature = match info.arg_types {
+            Some(arg_types) if !info.variadic => Signature::exact(arg_types, volatility),
+            // the trailing arguments are checked by the runtime
+            _ => Signature::variadic_any(volatility),
+        };
+        Ok(Self {
+            pool,
+            name: name.to_string(),
+            signature,
+            return_type: info.return_field.data_type().clone(),
+            arg_names: info.arg_names,
+        })
+    }
+
+    /// Call the function on `input` and return the only output column.
+    fn call(&self, input: &RecordBatch) -> Result<ArrayRef> {
+        let output = self.pool.get().call(&self.name, input)?;
+        if output.num_columns() != 1 {
+            anyhow::bail!("multi-column functions are not supported");
+        }
+        Ok(output.column(0).clone())
+    }
+
+    /// Build the input batch from the argument arrays with `num_rows` rows.
+    fn input(&self, arrays: Vec<ArrayRef>, num_rows: usize) -> Result<RecordBatch> {
+        let fields = arrays
+            .iter()
+            .enumerate()
+            .map(|(i, array)| {
+                let name = match self.arg_names.as_ref().and_then(|names| names.get(i)) {
+                    Some(name) => name.clone(),
+                    None => format!("arg{i}"),
+                };
+                Field::new(name, array.data_type().clone(), true)
+            })
+            .collect::<Vec<_>>();
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+        Ok(RecordBatch::try_new_with_options(
+            Arc::new(Schema::new(fields)),
+            arrays,
+            &options,
+        )?)
+    }
+}
+
+impl From<JsScalarUDF> for ScalarUDF {
+    fn from(udf: JsScalarUDF) -> Self {
+        ScalarUDF::new_from_impl(udf)
+    }
+}
+
+impl ScalarUDFImpl for JsScalarUDF {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> datafusion_common::Result<DataType> {
+        Ok(self.return_type.clone())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> datafusion_common::Result<ColumnarValue> {
+        let all_scalars = args
+            .iter()
+            .all(|arg| matches!(arg, ColumnarValue::Scalar(_)));
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let num_rows = arrays.first().map_or(1, |array| array.len());
+        let array = self
+            .input(arrays, num_rows)
+            .and_then(|input| self.call(&input))
+            .map_err(external_error)?;
+        if all_scalars {
+            Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+                &array, 0,
+            )?))
+        } else {
+            Ok(ColumnarValue::Array(array))
+        }
+    }
+
+    fn invoke_no_args(&self, number_rows: usize) -> datafusion_common::Result<ColumnarValue> {
+        let array = self
+            .input(vec![], number_rows)
+            .and_then(|input| self.call(&input))
+            .map_err(external_error)?;
+        Ok(ColumnarValue::Array(array))
+    }
+}
+
+/// Convert an error of the runtime into a DataFusion error.
+fn external_error(err: anyhow::Error) -> DataFusionError {
+    DataFusionError::External(err.into())
+}
@@ -16,14 +16,16 @@
 //! Convert arrow array from/to js objects.
 
 use anyhow::{Context, Result};
-use arrow_array::{array::*, builder::*, ArrowNativeTypeOp};
-use arrow_buffer::{i256, OffsetBuffer};
-use arrow_schema::{DataType, Field};
+use arrow_array::{array::*, builder::*, types::IntervalMonthDayNanoType, ArrowNativeTypeOp};
+use arrow_buffer::{i256, NullBuffer, OffsetBuffer};
+use arrow_schema::{DataType, Field, Fields, IntervalUnit};
 use rquickjs::{
-    function::Args, function::Constructor, Ctx, Error, FromJs, Function, IntoJs, Object,
-    TypedArray, Value,
+    function::Args, function::Constructor, ArrayBuffer, BigInt, Ctx, Error, Exception, FromJs,
+    Function, IntoJs, Object, Type, TypedArray, Value,
 };
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+use crate::{NonFiniteFloat, NumberCoercion};
 
 macro_rules! get_jsvalue {
     ($array_type: ty, $ctx:expr, $array:expr, $i:expr) => {{
@@ -32,6 +34,22 @@ macro_rules! get_jsvalue {
     }};
 }
 
+/// Convert all values of an array to JS values with a single downcast.
+macro_rules! get_jsvalues {
+    ($array_type: ty, $ctx:expr, $array:expr, $rows:expr, $skip:expr) => {{
+        let array = $array.as_any().downcast_ref::<$array_type>().unwrap();
+        $rows
+            .map(|i| {
+                if array.is_null(i) || $skip.is_some_and(|skip| skip.is_null(i)) {
+                    Ok(Value::new_null($ctx.clone()))
+                } else {
+                    array.value(i).into_js($ctx)
+                }
+            })
+            .collect()
+    }};
+}
+
 macro_rules! get_date_ms_js_value {
     ($array_type: ty, $ctx:expr, $array:expr, $i:expr) => {{
         let array = $array.as_any().downcast_ref::<$array_type>().unwrap();
@@ -53,13 +71,21 @@ macro_rules! build_timestamp_array {
 
         let mut builder = <$builder_type>::with_capacity($values.len());
 
-        for val in $values {
+        for (i, val) in $values.into_iter().enumerate() {
             if val.is_null() || val.is_undefined() {
                 builder.append_null();
             } else {
-                let date: i64 = date_to_ms_epoch.call((val,))?;
-                let date = date $op $coeff;
-                builder.append_value(date as $date_primitive_type);
+                let type_name = val.type_of().as_str();
+                let date: f64 = date_to_ms_epoch
+                    .call((val,))
+                    .map_err(|_| expected_error($ctx, "Date", i, type_name))?;
+                if !date.is_finite() {
+                    anyhow::bail!("invalid Date at row {i}");
+                }
+                let date = <$date_primitive_type>::try_from(date as i64 as i128 $op $coeff)
+                    .ok()
+                    .with_context(|| format!("Date at row {i} is out of range"))?;
+                builder.append_value(date);
             }
         }
         Ok(Arc::new(builder.finish()))
@@ -73,38 +99,246 @@ macro_rules! get_typed_array {
     }};
 }
 
+macro_rules! build_from_typed_array {
+    ($native_type: ty, $array_type: ty, $ctx:expr, $val:expr) => {{
+        match TypedArray::<$native_type>::from_js($ctx, $val.clone()) {
+            Ok(array) => {
+                let values: &[$native_type] = array.as_ref();
+                Some(Arc::new(<$array_type>::new(values.to_vec().into(), None)) as ArrayRef)
+            }
+            Err(_) => None,
+        }
+    }};
+}
+
+macro_rules! get_uint8_array {
+    ($array_type: ty, $ctx:expr, $array:expr, $i:expr) => {{
+        let array = $array.as_any().downcast_ref::<$array_type>().unwrap();
+        TypedArray::<u8>::new($ctx.clone(), array.value($i)).map(|a| a.into_value())
+    }};
+}
+
 macro_rules! build_array {
-    (NullBuilder, $ctx:expr, $values:expr) => {{
-        let mut builder = NullBuilder::new();
-        for val in $values {
+    // string and bytea
+    ($builder_type: ty, $values:expr) => {{
+        let mut builder = <$builder_type>::with_capacity($values.len(), 1024);
+        for (i, val) in $values.into_iter().enumerate() {
             if val.is_null() || val.is_undefined() {
                 builder.append_null();
+            } else if let Some(s) = val.as_string() {
+                builder.append_value(s.to_string()?);
             } else {
-                builder.append_empty_value();
+                anyhow::bail!("expected string at row {i}, got {}", val.type_of().as_str());
             }
         }
         Ok(Arc::new(builder.finish()))
     }};
-    // primitive types
-    ($builder_type: ty, $ctx:expr, $values:expr) => {{
-        let mut builder = <$builder_type>::with_capacity($values.len());
-        for val in $values {
-            if val.is_null() || val.is_undefined() {
-                builder.append_null();
+}
+
+/// Append a JS boolean at row `i` to a `BooleanBuilder`.
+macro_rules! append_bool {
+    ($builder:expr, $val:expr, $i:expr) => {{
+        let (val, i) = ($val, $i);
+        if val.is_null() || val.is_undefined() {
+            $builder.append_null();
+        } else if let Some(b) = val.as_bool() {
+            $builder.append_value(b);
+        } else {
+            anyhow::bail!(
+                "expected boolean at row {i}, got {}",
+                val.type_of().as_str()
+            );
+        }
+    }};
+}
+
+/// Append a JS number at row `i` to an integer builder of at most 32 bits.
+macro_rules! append_int {
+    ($builder:expr, $native_type: ty, $coercion:expr, $val:expr, $i:expr) => {{
+        let (val, i) = ($val, $i);
+        if val.is_null() || val.is_undefined() {
+            $builder.append_null();
+        } else {
+            let type_name = stringify!($native_type);
+            let number = number_from_js(&val, i)?;
+            let integer = $coercion.to_integer(number, i, type_name)?;
+            if integer < <$native_type>::MIN as f64 || integer > <$native_type>::MAX as f64 {
+                anyhow::bail!("number {number} at row {i} is out of range for {type_name}");
+            }
+            $builder.append_value(integer as $native_type);
+        }
+    }};
+}
+
+/// Append a JS number at row `i` to a float builder.
+macro_rules! append_float {
+    ($builder:expr, $native_type: ty, $non_finite:expr, $val:expr, $i:expr) => {{
+        let (val, i) = ($val, $i);
+        if val.is_null() || val.is_undefined() {
+            $builder.append_null();
+        } else {
+            // check after the conversion, since larger numbers may overflow `f32`
+            let number = number_from_js(&val, i)? as $native_type;
+            if number.is_finite() || $non_finite.keep(number as f64, i, stringify!($native_type))? {
+                $builder.append_value(number);
             } else {
-                builder.append_value(FromJs::from_js($ctx, val)?);
+                $builder.append_null();
             }
         }
+    }};
+}
+
+/// Append a JS number or `BigInt` at row `i` to an `Int64` or `UInt64` builder.
+macro_rules! append_int64 {
+    ($builder:expr, $native_type: ty, $to_string:expr, $coercion:expr, $val:expr, $i:expr) => {{
+        let (val, i) = ($val, $i);
+        if val.is_null() || val.is_undefined() {
+            $builder.append_null();
+        } else {
+            $builder.append_value(int64_from_js::<$native_type>(
+                $to_string,
+                val,
+                i,
+                stringify!($native_type),
+                $coercion,
+            )?);
+        }
+    }};
+}
+
+/// Build an integer array of at most 32 bits from JS numbers.
+macro_rules! build_int_array {
+    ($builder_type: ty, $native_type: ty, $coercion:expr, $values:expr) => {{
+        let mut builder = <$builder_type>::with_capacity($values.len());
+        for (i, val) in $values.into_iter().enumerate() {
+            append_int!(builder, $native_type, $coercion, val, i);
+        }
         Ok(Arc::new(builder.finish()))
     }};
-    // string and bytea
-    ($builder_type: ty, $elem_type: ty, $ctx:expr, $values:expr) => {{
+}
+
+/// Build a float array from JS numbers.
+macro_rules! build_float_array {
+    ($builder_type: ty, $native_type: ty, $non_finite:expr, $values:expr) => {{
+        let mut builder = <$builder_type>::with_capacity($values.len());
+        for (i, val) in $values.into_iter().enumerate() {
+            append_float!(builder, $native_type, $non_finite, val, i);
+        }
+        Ok(Arc::new(builder.finish()))
+    }};
+}
+
+/// Build an `Int64` or `UInt64` array from JS numbers or `BigInt`s.
+macro_rules! build_int64_array {
+    ($builder_type: ty, $native_type: ty, $ctx:expr, $coercion:expr, $values:expr) => {{
+        let mut builder = <$builder_type>::with_capacity($values.len());
+        let to_string: Function = $ctx.globals().get("String")?;
+        for (i, val) in $values.into_iter().enumerate() {
+            append_int64!(builder, $native_type, &to_string, $coercion, val, i);
+        }
+        Ok(Arc::new(builder.finish()))
+    }};
+}
+
+/// Returns the error for a value at row `i` that is not of the `expected` type.
+///
+/// The exception thrown while converting the value is cleared.
+fn expected_error(ctx: &Ctx<'_>, expected: &str, i: usize, type_name: &str) -> anyhow::Error {
+    let _ = ctx.catch();
+    anyhow::anyhow!("expected {expected} at row {i}, got {type_name}")
+}
+
+/// Get the number at row `i`, or report an error if the value is not a number.
+fn number_from_js(val: &Value, i: usize) -> Result<f64> {
+    val.as_number()
+        .with_context(|| format!("expected number at row {i}, got {}", val.type_of().as_str()))
+}
+
+/// The maximum safe integer in JS (`Number.MAX_SAFE_INTEGER`).
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Convert a JS number or `BigInt` at row `i` to a 64-bit integer.
+///
+/// `BigInt`s are converted through their decimal representation,
+/// so that values out of the range of the target type are rejected instead of wrapped around.
+/// Non-integer numbers are only accepted if they are safe integers (`Number.isSafeInteger`)
+/// after the `coercion`, since larger values may have lost precision.
+fn int64_from_js<'a, T>(
+    to_string: &Function<'a>,
+    val: Value<'a>,
+    i: usize,
+    type_name: &str,
+    coercion: NumberCoercion,
+) -> Result<T>
+where
+    T: TryFrom<i64> + std::str::FromStr,
+{
+    if val.type_of() == Type::BigInt {
+        let string: String = to_string.call((val,))?;
+        return string.parse().map_err(|_| {
+            anyhow::anyhow!("BigInt {string} at row {i} is out of range for {type_name}")
+        });
+    }
+    if let Some(number) = val.as_number() {
+        let number = match coercion {
+            // non-integers are reported below
+            NumberCoercion::Error => number,
+            _ => coercion.to_integer(number, i, type_name)?,
+        };
+        let value = if number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER {
+            T::try_from(number as i64).ok()
+        } else {
+            None
+        };
+        return value.with_context(|| {
+            format!(
+                "number {number} at row {i} is not a safe integer for {type_name}. HINT: return a BigInt instead"
+            )
+        });
+    }
+    anyhow::bail!(
+        "expected number or BigInt at row {i}, got {}",
+        val.type_of().as_str()
+    )
+}
+
+/// Call `f` with the bytes of a JS `Uint8Array`, `ArrayBuffer` or array of numbers at row `i`.
+fn with_bytes<'a, R>(
+    ctx: &Ctx<'a>,
+    val: Value<'a>,
+    i: usize,
+    f: impl FnOnce(&[u8]) -> Result<R>,
+) -> Result<R> {
+    if let Ok(array) = TypedArray::<u8>::from_js(ctx, val.clone()) {
+        f(array.as_bytes().context("Uint8Array is detached")?)
+    } else if let Ok(buffer) = ArrayBuffer::from_js(ctx, val.clone()) {
+        f(buffer.as_bytes().context("ArrayBuffer is detached")?)
+    } else {
+        let type_name = val.type_of().as_str();
+        let bytes = Vec::<u8>::from_js(ctx, val).map_err(|_| {
+            expected_error(
+                ctx,
+                "Uint8Array, ArrayBuffer or array of bytes",
+                i,
+                type_name,
+            )
+        })?;
+        f(&bytes)
+    }
+}
+
+/// Build a binary array from JS `Uint8Array`s, `ArrayBuffer`s or arrays of numbers.
+macro_rules! build_binary_array {
+    ($builder_type: ty, $ctx:expr, $values:expr) => {{
         let mut builder = <$builder_type>::with_capacity($values.len(), 1024);
-        for val in $values {
+        for (i, val) in $values.into_iter().enumerate() {
             if val.is_null() || val.is_undefined() {
                 builder.append_null();
             } else {
-                builder.append_value(<$elem_type>::from_js($ctx, val)?);
+                with_bytes($ctx, val, i, |bytes| {
+                    builder.append_value(bytes);
+                    Ok(())
+                })?;
             }
         }
         Ok(Arc::new(builder.finish()))
@@ -114,13 +348,18 @@ macro_rules! build_array {
 macro_rules! build_json_array {
     ($array_type: ty, $ctx:expr, $values:expr) => {{
         let mut builder = <$array_type>::with_capacity($values.len(), 1024);
-        for val in $values {
+        for (i, val) in $values.into_iter().enumerate() {
             if val.is_null() || val.is_undefined() {
                 builder.append_null();
-            } else if let Some(s) = $ctx.json_stringify(val)? {
-                builder.append_value(s.to_string()?);
-            } else {
-                builder.append_null();
+                continue;
+            }
+            let type_name = val.type_of().as_str();
+            let json = $ctx
+                .json_stringify(val)
+                .map_err(|_| expected_error($ctx, "JSON serializable value", i, type_name))?;
+            match json {
+                Some(s) => builder.append_value(s.to_string()?),
+                None => builder.append_null(),
             }
         }
         Ok(Arc::new(builder.finish()))
@@ -132,6 +371,73 @@ pub struct Converter {
     arrow_extension_key: Cow<'static, str>,
     json_extension_name: Cow<'static, str>,
     decimal_extension_name: Cow<'static, str>,
+    uuid_extension_name: Cow<'static, str>,
+    number_coercion: NumberCoercion,
+    non_finite_float: NonFiniteFloat,
+    decimal_objects: bool,
+}
+
+/// A builder of a primitive array which appends the JS values one by one.
+///
+/// It converts the values in the same way as [`Converter::build_array`], but doesn't need to
+/// collect all values first.
+pub(super) struct PrimitiveBuilder<'a> {
+    builder: TypedBuilder,
+    coercion: NumberCoercion,
+    non_finite: NonFiniteFloat,
+    /// The `String` function to convert `BigInt`s.
+    to_string: Function<'a>,
+}
+
+enum TypedBuilder {
+    Boolean(BooleanBuilder),
+    Int8(Int8Builder),
+    Int16(Int16Builder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    UInt8(UInt8Builder),
+    UInt16(UInt16Builder),
+    UInt32(UInt32Builder),
+    UInt64(UInt64Builder),
+    Float32(Float32Builder),
+    Float64(Float64Builder),
+}
+
+impl<'a> PrimitiveBuilder<'a> {
+    /// Append the value of row `i`.
+    pub(super) fn append(&mut self, val: Value<'a>, i: usize) -> Result<()> {
+        let coercion = self.coercion;
+        match &mut self.builder {
+            TypedBuilder::Boolean(b) => append_bool!(b, val, i),
+            TypedBuilder::Int8(b) => append_int!(b, i8, coercion, val, i),
+            TypedBuilder::Int16(b) => append_int!(b, i16, coercion, val, i),
+            TypedBuilder::Int32(b) => append_int!(b, i32, coercion, val, i),
+            TypedBuilder::Int64(b) => append_int64!(b, i64, &self.to_string, coercion, val, i),
+            TypedBuilder::UInt8(b) => append_int!(b, u8, coercion, val, i),
+            TypedBuilder::UInt16(b) => append_int!(b, u16, coercion, val, i),
+            TypedBuilder::UInt32(b) => append_int!(b, u32, coercion, val, i),
+            TypedBuilder::UInt64(b) => append_int64!(b, u64, &self.to_string, coercion, val, i),
+            TypedBuilder::Float32(b) => append_float!(b, f32, self.non_finite, val, i),
+            TypedBuilder::Float64(b) => append_float!(b, f64, self.non_finite, val, i),
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(&mut self) -> ArrayRef {
+        match &mut self.builder {
+            TypedBuilder::Boolean(b) => Arc::new(b.finish()),
+            TypedBuilder::Int8(b) => Arc::new(b.finish()),
+            TypedBuilder::Int16(b) => Arc::new(b.finish()),
+            TypedBuilder::Int32(b) => Arc::new(b.finish()),
+            TypedBuilder::Int64(b) => Arc::new(b.finish()),
+            TypedBuilder::UInt8(b) => Arc::new(b.finish()),
+            TypedBuilder::UInt16(b) => Arc::new(b.finish()),
+            TypedBuilder::UInt32(b) => Arc::new(b.finish()),
+            TypedBuilder::UInt64(b) => Arc::new(b.finish()),
+            TypedBuilder::Float32(b) => Arc::new(b.finish()),
+            TypedBuilder::Float64(b) => Arc::new(b.finish()),
+        }
+    }
 }
 
 impl Converter {
@@ -140,9 +446,23 @@ impl Converter {
             arrow_extension_key: "ARROW:extension:name".into(),
             json_extension_name: "arrowudf.json".into(),
             decimal_extension_name: "arrowudf.decimal".into(),
+            uuid_extension_name: "arrow.uuid".into(),
+            number_coercion: NumberCoercion::default(),
+            non_finite_float: NonFiniteFloat::default(),
+            decimal_objects: false,
         }
     }
 
+    /// Set how non-integer numbers are converted to integer types.
+    pub(crate) fn set_number_coercion(&mut self, coercion: NumberCoercion) {
+        self.number_coercion = coercion;
+    }
+
+    /// Set how non-finite numbers are converted to float types.
+    pub(crate) fn set_non_finite_float(&mut self, non_finite: NonFiniteFloat) {
+        self.non_finite_float = non_finite;
+    }
+
     /// Set the key for the arrow extension.
     ///
     /// The default value is `ARROW:extension:name`.
@@ -164,6 +484,148 @@ impl Converter {
         self.decimal_extension_name = name.to_string().into();
     }
 
+    /// Set the name for the uuid extension.
+    ///
+    /// The default value is `arrow.uuid`.
+    pub fn set_uuid_extension_name(&mut self, name: &str) {
+        self.uuid_extension_name = name.to_string().into();
+    }
+
+    /// Set whether `Decimal128` and `Decimal256` values are passed as objects
+    /// `{ value, precision, scale }` instead of `BigDecimal`s.
+    ///
+    /// `value` is the decimal string with exactly `scale` fractional digits, e.g. `"123.40"`.
+    /// The default value is `false`.
+    pub fn set_decimal_objects(&mut self, decimal_objects: bool) {
+        self.decimal_objects = decimal_objects;
+    }
+
+    /// Get a JS object that reads the values of an array on demand.
+    ///
+    /// The object has a `length` property, and `get(i)` and `isNull(i)` methods.
+    pub(super) fn get_jsreader<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        field: &Field,
+        array: &ArrayRef,
+    ) -> Result<Value<'a>, Error> {
+        let len = array.len();
+        // the index must be an integer in range
+        let check_index = move |ctx: &Ctx<'a>, i: f64| {
+            if i.fract() != 0.0 || i < 0.0 || i >= len as f64 {
+                return Err(Exception::throw_range(
+                    ctx,
+                    &format!("index {i} out of bounds for length {len}"),
+                ));
+            }
+            Ok(i as usize)
+        };
+        let object = Object::new(ctx.clone())?;
+        object.set("length", len)?;
+        let (converter, field, values) = (self.clone(), field.clone(), array.clone());
+        let get = Function::new(ctx.clone(), move |ctx: Ctx<'a>, i: f64| {
+            let i = check_index(&ctx, i)?;
+            converter.get_jsvalue(&ctx, &field, values.as_ref(), i)
+        })?;
+        object.set("get", get)?;
+        // every value of a `NullArray` is null, although it has no null buffer
+        let nulls = array.logical_nulls();
+        let is_null = Function::new(ctx.clone(), move |ctx: Ctx<'a>, i: f64| {
+            let i = check_index(&ctx, i)?;
+            Ok::<_, Error>(nulls.as_ref().is_some_and(|nulls| nulls.is_null(i)))
+        })?;
+        object.set("isNull", is_null)?;
+        Ok(object.into_value())
+    }
+
+    /// Get a numeric array as a JS typed array, e.g. `Int32Array` for `Int32`.
+    ///
+    /// The values are copied, and the values of null elements are unspecified.
+    /// Returns `None` if the array is not numeric.
+    pub(super) fn get_typed_array<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        array: &dyn Array,
+    ) -> Result<Option<Value<'a>>, Error> {
+        let value = match array.data_type() {
+            DataType::Int8 => get_typed_array!(Int8Array, ctx, array),
+            DataType::Int16 => get_typed_array!(Int16Array, ctx, array),
+            DataType::Int32 => get_typed_array!(Int32Array, ctx, array),
+            DataType::Int64 => get_typed_array!(Int64Array, ctx, array),
+            DataType::UInt8 => get_typed_array!(UInt8Array, ctx, array),
+            DataType::UInt16 => get_typed_array!(UInt16Array, ctx, array),
+            DataType::UInt32 => get_typed_array!(UInt32Array, ctx, array),
+            DataType::UInt64 => get_typed_array!(UInt64Array, ctx, array),
+            DataType::Float32 => get_typed_array!(Float32Array, ctx, array),
+            DataType::Float64 => get_typed_array!(Float64Array, ctx, array),
+            _ => return Ok(None),
+        };
+        value.map(Some)
+    }
+
+    /// Build a numeric array from a JS typed array of the same type, e.g. `Int32Array` for `Int32`.
+    ///
+    /// Returns `None` if the value is not a typed array of the type.
+    pub(super) fn build_from_typed_array<'a>(
+        &self,
+        field: &Field,
+        ctx: &Ctx<'a>,
+        value: &Value<'a>,
+    ) -> Option<ArrayRef> {
+        match field.data_type() {
+            DataType::Int8 => build_from_typed_array!(i8, Int8Array, ctx, value),
+            DataType::Int16 => build_from_typed_array!(i16, Int16Array, ctx, value),
+            DataType::Int32 => build_from_typed_array!(i32, Int32Array, ctx, value),
+            DataType::Int64 => build_from_typed_array!(i64, Int64Array, ctx, value),
+            DataType::UInt8 => build_from_typed_array!(u8, UInt8Array, ctx, value),
+            DataType::UInt16 => build_from_typed_array!(u16, UInt16Array, ctx, value),
+            DataType::UInt32 => build_from_typed_array!(u32, UInt32Array, ctx, value),
+            DataType::UInt64 => build_from_typed_array!(u64, UInt64Array, ctx, value),
+            DataType::Float32 => build_from_typed_array!(f32, Float32Array, ctx, value),
+            DataType::Float64 => build_from_typed_array!(f64, Float64Array, ctx, value),
+            _ => None,
+        }
+    }
+
+    /// Get the elements in `rows` of an array as JS values.
+    ///
+    /// Unlike calling [`get_jsvalue`](Self::get_jsvalue) for each element, the data type is only
+    /// dispatched once for common types. Elements that are null in `skip` are not converted and
+    /// returned as null.
+    pub(super) fn column_to_js<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        field: &Field,
+        array: &dyn Array,
+        rows: Range<usize>,
+        skip: Option<&NullBuffer>,
+    ) -> Result<Vec<Value<'a>>, Error> {
+        let extension = field.metadata().get(self.arrow_extension_key.as_ref());
+        match array.data_type() {
+            DataType::Boolean => get_jsvalues!(BooleanArray, ctx, array, rows, skip),
+            DataType::Int8 => get_jsvalues!(Int8Array, ctx, array, rows, skip),
+            DataType::Int16 => get_jsvalues!(Int16Array, ctx, array, rows, skip),
+            DataType::Int32 => get_jsvalues!(Int32Array, ctx, array, rows, skip),
+            DataType::Int64 => get_jsvalues!(Int64Array, ctx, array, rows, skip),
+            DataType::UInt8 => get_jsvalues!(UInt8Array, ctx, array, rows, skip),
+            DataType::UInt16 => get_jsvalues!(UInt16Array, ctx, array, rows, skip),
+            DataType::UInt32 => get_jsvalues!(UInt32Array, ctx, array, rows, skip),
+            DataType::UInt64 => get_jsvalues!(UInt64Array, ctx, array, rows, skip),
+            DataType::Float32 => get_jsvalues!(Float32Array, ctx, array, rows, skip),
+            DataType::Float64 => get_jsvalues!(Float64Array, ctx, array, rows, skip),
+            DataType::Utf8 if extension.is_none() => {
+                get_jsvalues!(StringArray, ctx, array, rows, skip)
+            }
+            DataType::LargeUtf8 => get_jsvalues!(LargeStringArray, ctx, array, rows, skip),
+            _ => rows
+                .map(|i| match skip {
+                    Some(skip) if skip.is_null(i) => Ok(Value::new_null(ctx.clone())),
+                    _ => self.get_jsvalue(ctx, field, array, i),
+                })
+                .collect(),
+        }
+    }
+
     /// Get array element as a JS Value.
     pub(super) fn get_jsvalue<'a>(
         &self,
@@ -206,7 +668,7 @@ impl Converter {
                     let array = array.as_any().downcast_ref::<BinaryArray>().unwrap();
                     ctx.json_parse(array.value(i))
                 }
-                _ => get_jsvalue!(BinaryArray, ctx, array, i),
+                _ => get_uint8_array!(BinaryArray, ctx, array, i),
             },
             DataType::LargeUtf8 => get_jsvalue!(LargeStringArray, ctx, array, i),
             DataType::LargeBinary => {
@@ -215,20 +677,30 @@ impl Converter {
                         let array = array.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
                         ctx.json_parse(array.value(i))
                     }
-                    _ => get_jsvalue!(LargeBinaryArray, ctx, array, i),
+                    _ => get_uint8_array!(LargeBinaryArray, ctx, array, i),
                 }
             }
-            DataType::Decimal128(_, _) => {
+            DataType::FixedSizeBinary(16) if self.is_uuid(field) => {
+                let array = array
+                    .as_any()
+                    .downcast_ref::<FixedSizeBinaryArray>()
+                    .unwrap();
+                format_uuid(array.value(i)).into_js(ctx)
+            }
+            DataType::FixedSizeBinary(_) => {
+                get_uint8_array!(FixedSizeBinaryArray, ctx, array, i)
+            }
+            DataType::Decimal128(precision, scale) => {
                 let array = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
                 let decimal_str = array.value_as_string(i);
 
-                self.call_bigdecimal(ctx, &decimal_str)
+                self.get_decimal(ctx, &decimal_str, *precision, *scale)
             }
-            DataType::Decimal256(_, _) => {
+            DataType::Decimal256(precision, scale) => {
                 let array = array.as_any().downcast_ref::<Decimal256Array>().unwrap();
                 let decimal_str = array.value_as_string(i);
 
-                self.call_bigdecimal(ctx, &decimal_str)
+                self.get_decimal(ctx, &decimal_str, *precision, *scale)
             }
             // TODO: handle tz correctly. requires probably converting tz str into a Chrono Tz
             DataType::Timestamp(unit, _tz) => {
@@ -251,6 +723,23 @@ impl Converter {
             DataType::Date32 => {
                 get_date_ms_js_value!(Date32Array, ctx, array, i)
             }
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                let array = array
+                    .as_any()
+                    .downcast_ref::<IntervalMonthDayNanoArray>()
+                    .unwrap();
+                let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(array.value(i));
+                let object = Object::new(ctx.clone())?;
+                object.set("months", months)?;
+                object.set("days", days)?;
+                // nanoseconds that are not safe integers are passed as `BigInt`
+                if (nanos.unsigned_abs() as f64) <= MAX_SAFE_INTEGER {
+                    object.set("nanos", nanos as f64)?;
+                } else {
+                    object.set("nanos", BigInt::from_i64(ctx.clone(), nanos)?)?;
+                }
+                Ok(object.into_value())
+            }
             // list
             DataType::List(inner) => {
                 let array = array.as_any().downcast_ref::<ListArray>().unwrap();
@@ -301,17 +790,58 @@ impl Converter {
             }
             DataType::Struct(fields) => {
                 let array = array.as_any().downcast_ref::<StructArray>().unwrap();
-                let object = Object::new(ctx.clone())?;
-                for (j, field) in fields.iter().enumerate() {
-                    let value = self.get_jsvalue(ctx, field, array.column(j).as_ref(), i)?;
-                    object.set(field.name(), value)?;
-                }
-                Ok(object.into_value())
+                self.get_jsobject(ctx, fields, array.columns(), i)
             }
             _other => Err(Error::Unknown),
         }
     }
 
+    /// Get the `i`-th row of columns as a JS object keyed by field names.
+    pub(super) fn get_jsobject<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        fields: &Fields,
+        columns: &[ArrayRef],
+        i: usize,
+    ) -> Result<Value<'a>, Error> {
+        let object = Object::new(ctx.clone())?;
+        for (field, column) in fields.iter().zip(columns) {
+            let value = self.get_jsvalue(ctx, field, column.as_ref(), i)?;
+            object.set(field.name(), value)?;
+        }
+        Ok(object.into_value())
+    }
+
+    /// Returns a builder that appends the values of a primitive type one by one, or `None` if the
+    /// type is not primitive.
+    pub(super) fn primitive_builder<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        data_type: &DataType,
+        capacity: usize,
+    ) -> Result<Option<PrimitiveBuilder<'a>>> {
+        let builder = match data_type {
+            DataType::Boolean => TypedBuilder::Boolean(BooleanBuilder::with_capacity(capacity)),
+            DataType::Int8 => TypedBuilder::Int8(Int8Builder::with_capacity(capacity)),
+            DataType::Int16 => TypedBuilder::Int16(Int16Builder::with_capacity(capacity)),
+            DataType::Int32 => TypedBuilder::Int32(Int32Builder::with_capacity(capacity)),
+            DataType::Int64 => TypedBuilder::Int64(Int64Builder::with_capacity(capacity)),
+            DataType::UInt8 => TypedBuilder::UInt8(UInt8Builder::with_capacity(capacity)),
+            DataType::UInt16 => TypedBuilder::UInt16(UInt16Builder::with_capacity(capacity)),
+            DataType::UInt32 => TypedBuilder::UInt32(UInt32Builder::with_capacity(capacity)),
+            DataType::UInt64 => TypedBuilder::UInt64(UInt64Builder::with_capacity(capacity)),
+            DataType::Float32 => TypedBuilder::Float32(Float32Builder::with_capacity(capacity)),
+            DataType::Float64 => TypedBuilder::Float64(Float64Builder::with_capacity(capacity)),
+            _ => return Ok(None),
+        };
+        Ok(Some(PrimitiveBuilder {
+            builder,
+            coercion: self.number_coercion,
+            non_finite: self.non_finite_float,
+            to_string: ctx.globals().get("String")?,
+        }))
+    }
+
     pub(super) fn build_array<'a>(
         &self,
         field: &Field,
@@ -319,18 +849,33 @@ impl Converter {
         values: Vec<Value<'a>>,
     ) -> Result<ArrayRef> {
         match field.data_type() {
-            DataType::Null => build_array!(NullBuilder, ctx, values),
-            DataType::Boolean => build_array!(BooleanBuilder, ctx, values),
-            DataType::Int8 => build_array!(Int8Builder, ctx, values),
-            DataType::Int16 => build_array!(Int16Builder, ctx, values),
-            DataType::Int32 => build_array!(Int32Builder, ctx, values),
-            DataType::Int64 => build_array!(Int64Builder, ctx, values),
-            DataType::UInt8 => build_array!(UInt8Builder, ctx, values),
-            DataType::UInt16 => build_array!(UInt16Builder, ctx, values),
-            DataType::UInt32 => build_array!(UInt32Builder, ctx, values),
-            DataType::UInt64 => build_array!(UInt64Builder, ctx, values),
-            DataType::Float32 => build_array!(Float32Builder, ctx, values),
-            DataType::Float64 => build_array!(Float64Builder, ctx, values),
+            // the return values are ignored, e.g. for functions called for side effects
+            DataType::Null => Ok(Arc::new(NullArray::new(values.len()))),
+            DataType::Boolean => {
+                let mut builder = BooleanBuilder::with_capacity(values.len());
+                for (i, val) in values.into_iter().enumerate() {
+                    append_bool!(builder, val, i);
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            DataType::Int8 => build_int_array!(Int8Builder, i8, self.number_coercion, values),
+            DataType::Int16 => build_int_array!(Int16Builder, i16, self.number_coercion, values),
+            DataType::Int32 => build_int_array!(Int32Builder, i32, self.number_coercion, values),
+            DataType::Int64 => {
+                build_int64_array!(Int64Builder, i64, ctx, self.number_coercion, values)
+            }
+            DataType::UInt8 => build_int_array!(UInt8Builder, u8, self.number_coercion, values),
+            DataType::UInt16 => build_int_array!(UInt16Builder, u16, self.number_coercion, values),
+            DataType::UInt32 => build_int_array!(UInt32Builder, u32, self.number_coercion, values),
+            DataType::UInt64 => {
+                build_int64_array!(UInt64Builder, u64, ctx, self.number_coercion, values)
+            }
+            DataType::Float32 => {
+                build_float_array!(Float32Builder, f32, self.non_finite_float, values)
+            }
+            DataType::Float64 => {
+                build_float_array!(Float64Builder, f64, self.non_finite_float, values)
+            }
             DataType::Utf8 => match field.metadata().get(self.arrow_extension_key.as_ref()) {
                 Some(x) if x == self.json_extension_name.as_ref() => {
                     build_json_array!(StringBuilder, ctx, values)
@@ -341,58 +886,96 @@ impl Converter {
                         .eval("BigDecimal.prototype.toString")
                         .context("failed to get BigDecimal.prototype.string")?;
 
-                    for val in values {
+                    for (i, val) in values.into_iter().enumerate() {
                         if val.is_null() || val.is_undefined() {
                             builder.append_null();
                         } else {
+                            let type_name = val.type_of().as_str();
                             let mut args = Args::new(ctx.clone(), 0);
                             args.this(val)?;
 
-                            let string: String = bigdecimal_to_string.call_arg(args).context(
-                                "failed to convert BigDecimal to string. make sure you return a BigDecimal value",
-                            )?;
+                            let string: String = bigdecimal_to_string
+                                .call_arg(args)
+                                .map_err(|_| expected_error(ctx, "BigDecimal", i, type_name))?;
 
                             builder.append_value(string);
                         }
                     }
                     Ok(Arc::new(builder.finish()))
                 }
-                _ => build_array!(StringBuilder, String, ctx, values),
+                _ => build_array!(StringBuilder, values),
             },
-            DataType::LargeUtf8 => build_array!(LargeStringBuilder, String, ctx, values),
+            DataType::LargeUtf8 => build_array!(LargeStringBuilder, values),
             DataType::Binary => match field.metadata().get(self.arrow_extension_key.as_ref()) {
                 Some(x) if x == self.json_extension_name.as_ref() => {
                     build_json_array!(BinaryBuilder, ctx, values)
                 }
-                _ => build_array!(BinaryBuilder, Vec::<u8>, ctx, values),
+                _ => build_binary_array!(BinaryBuilder, ctx, values),
             },
             DataType::LargeBinary => {
                 match field.metadata().get(self.arrow_extension_key.as_ref()) {
                     Some(x) if x == self.json_extension_name.as_ref() => {
                         build_json_array!(LargeBinaryBuilder, ctx, values)
                     }
-                    _ => build_array!(LargeBinaryBuilder, Vec::<u8>, ctx, values),
+                    _ => build_binary_array!(LargeBinaryBuilder, ctx, values),
                 }
             }
+            DataType::FixedSizeBinary(16) if self.is_uuid(field) => {
+                let mut builder = FixedSizeBinaryBuilder::with_capacity(values.len(), 16);
+                for (i, val) in values.into_iter().enumerate() {
+                    if val.is_null() || val.is_undefined() {
+                        builder.append_null();
+                        continue;
+                    }
+                    let Some(string) = val.as_string() else {
+                        let type_name = val.type_of().as_str();
+                        anyhow::bail!("expected UUID string at row {i}, got {type_name}");
+                    };
+                    let string = string.to_string()?;
+                    let bytes = parse_uuid(&string)
+                        .with_context(|| format!("invalid UUID \"{string}\" at row {i}"))?;
+                    builder.append_value(bytes)?;
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            DataType::FixedSizeBinary(size) => {
+                let mut builder = FixedSizeBinaryBuilder::with_capacity(values.len(), *size);
+                for (i, val) in values.into_iter().enumerate() {
+                    if val.is_null() || val.is_undefined() {
+                        builder.append_null();
+                    } else {
+                        with_bytes(ctx, val, i, |bytes| {
+                            if bytes.len() != *size as usize {
+                                anyhow::bail!(
+                                    "expected {size} bytes at row {i}, got {}",
+                                    bytes.len()
+                                );
+                            }
+                            Ok(builder.append_value(bytes)?)
+                        })?;
+                    }
+                }
+                Ok(Arc::new(builder.finish()))
+            }
             DataType::Decimal128(precision, scale) => {
                 let mut builder = Decimal128Builder::with_capacity(values.len())
                     .with_precision_and_scale(*precision, *scale)?;
 
-                let bigdecimal_to_precision: Function =
-                    self.get_bigdecimal_to_precision_function(ctx)?;
+                let bigdecimal_to_string = self.get_bigdecimal_to_string_function(ctx)?;
 
-                for val in values {
+                for (i, val) in values.into_iter().enumerate() {
                     if val.is_null() || val.is_undefined() {
                         builder.append_null();
                     } else {
+                        let type_name = val.type_of().as_str();
                         let mut args = Args::new(ctx.clone(), 0);
                         args.this(val)?;
-                        args.push_arg(*precision)?;
-                        let string: String = bigdecimal_to_precision.call_arg(args).context(
-                            "failed to convert BigDecimal to string. make sure you return a BigDecimal value",
-                        )?;
+                        let string: String = bigdecimal_to_string
+                            .call_arg(args)
+                            .map_err(|_| expected_error(ctx, "BigDecimal", i, type_name))?;
 
-                        let decimal_integer = self.decimal_string_to_i128(&string, *scale)?;
+                        let decimal_integer =
+                            self.decimal_string_to_i128(&string, *precision, *scale, i)?;
                         builder.append_value(decimal_integer);
                     }
                 }
@@ -402,19 +985,20 @@ impl Converter {
                 let mut builder = Decimal256Builder::with_capacity(values.len())
                     .with_precision_and_scale(*precision, *scale)?;
 
-                let bigdecimal_to_precision = self.get_bigdecimal_to_precision_function(ctx)?;
+                let bigdecimal_to_string = self.get_bigdecimal_to_string_function(ctx)?;
 
-                for val in values {
+                for (i, val) in values.into_iter().enumerate() {
                     if val.is_null() || val.is_undefined() {
                         builder.append_null();
                     } else {
+                        let type_name = val.type_of().as_str();
                         let mut args = Args::new(ctx.clone(), 0);
                         args.this(val)?;
-                        args.push_arg(*precision)?;
-                        let string: String = bigdecimal_to_precision.call_arg(args).context(
-                            "failed to convert BigDecimal to string. make sure you return a BigDecimal value",
-                        )?;
-                        let decimal_integer = self.decimal_string_to_i256(&string, *scale)?;
+                        let string: String = bigdecimal_to_string
+                            .call_arg(args)
+                            .map_err(|_| expected_error(ctx, "BigDecimal", i, type_name))?;
+                        let decimal_integer =
+                            self.decimal_string_to_i256(&string, *precision, *scale, i)?;
                         builder.append_value(decimal_integer);
                     }
                 }
@@ -441,15 +1025,53 @@ impl Converter {
             DataType::Date32 => {
                 build_timestamp_array!(Date32Builder, i32, ctx, values, /, 1000 * 60 * 60 * 24)
             }
+            // an interval is an object `{ months, days, nanos }`, where missing fields are 0
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                let mut builder = IntervalMonthDayNanoBuilder::with_capacity(values.len());
+                let to_string: Function = ctx.globals().get("String")?;
+                for (i, val) in values.into_iter().enumerate() {
+                    if val.is_null() || val.is_undefined() {
+                        builder.append_null();
+                        continue;
+                    }
+                    let object = val.as_object().with_context(|| {
+                        format!(
+                            "expected interval object at row {i}, got {}",
+                            val.type_of().as_str()
+                        )
+                    })?;
+                    let get_i32 = |key: &str| {
+                        object.get::<_, Option<i32>>(key).map_err(|_| {
+                            anyhow::anyhow!("expected integer {key} of interval at row {i}")
+                        })
+                    };
+                    let months = get_i32("months")?;
+                    let days = get_i32("days")?;
+                    let nanos: Value = object.get("nanos")?;
+                    let nanos = if nanos.is_null() || nanos.is_undefined() {
+                        0
+                    } else {
+                        int64_from_js::<i64>(&to_string, nanos, i, "i64", self.number_coercion)?
+                    };
+                    builder.append_value(IntervalMonthDayNanoType::make_value(
+                        months.unwrap_or(0),
+                        days.unwrap_or(0),
+                        nanos,
+                    ));
+                }
+                Ok(Arc::new(builder.finish()))
+            }
             // list
             DataType::List(inner) => {
                 // flatten lists
                 let mut flatten_values = vec![];
                 let mut offsets = Vec::<i32>::with_capacity(values.len() + 1);
                 offsets.push(0);
-                for val in &values {
+                for (i, val) in values.iter().enumerate() {
                     if !val.is_null() && !val.is_undefined() {
-                        let array = val.as_array().context("failed to convert to array")?;
+                        let array = val.as_array().with_context(|| {
+                            format!("expected array at row {i}, got {}", val.type_of().as_str())
+                        })?;
                         flatten_values.reserve(array.len());
                         for elem in array.iter() {
                             flatten_values.push(elem?);
@@ -469,15 +1091,23 @@ impl Converter {
                     Some(nulls),
                 )))
             }
+            // A property missing from the object reads as `undefined` and is treated as null.
+            // Each field is built recursively from the property values of all rows, where a
+            // null or undefined parent yields null for every field beneath it.
             DataType::Struct(fields) => {
                 let mut arrays = Vec::with_capacity(fields.len());
                 for field in fields {
                     let mut field_values = Vec::with_capacity(values.len());
-                    for val in &values {
+                    for (i, val) in values.iter().enumerate() {
                         let v = if val.is_null() || val.is_undefined() {
                             Value::new_null(ctx.clone())
                         } else {
-                            let object = val.as_object().context("expect object")?;
+                            let object = val.as_object().with_context(|| {
+                                format!(
+                                    "expected object at row {i}, got {}",
+                                    val.type_of().as_str()
+                                )
+                            })?;
                             object.get(field.name())?
                         };
                         field_values.push(v);
@@ -488,16 +1118,42 @@ impl Converter {
                     .iter()
                     .map(|v| !v.is_null() && !v.is_undefined())
                     .collect();
-                Ok(Arc::new(StructArray::new(
+                Ok(Arc::new(StructArray::try_new(
                     fields.clone(),
                     arrays,
                     Some(nulls),
-                )))
+                )?))
             }
             other => Err(anyhow::anyhow!("Unimplemented datatype {}", other)),
         }
     }
 
+    /// Returns true if the field is a `FixedSizeBinary(16)` with the uuid extension.
+    fn is_uuid(&self, field: &Field) -> bool {
+        field
+            .metadata()
+            .get(self.arrow_extension_key.as_ref())
+            .is_some_and(|x| x == self.uuid_extension_name.as_ref())
+    }
+
+    /// Get a decimal value as a `BigDecimal`, or an object with the precision and scale.
+    fn get_decimal<'a>(
+        &self,
+        ctx: &Ctx<'a>,
+        value: &str,
+        precision: u8,
+        scale: i8,
+    ) -> Result<Value<'a>, Error> {
+        if !self.decimal_objects {
+            return self.call_bigdecimal(ctx, value);
+        }
+        let object = Object::new(ctx.clone())?;
+        object.set("value", value)?;
+        object.set("precision", precision as i32)?;
+        object.set("scale", scale as i32)?;
+        Ok(object.into_value())
+    }
+
     fn call_bigdecimal<'a>(
         &self,
         ctx: &Ctx<'a>,
@@ -507,54 +1163,130 @@ impl Converter {
         bigdecimal.call((value,))
     }
 
-    fn get_bigdecimal_to_precision_function<'a>(&self, ctx: &Ctx<'a>) -> Result<Function<'a>> {
-        ctx.eval("BigDecimal.prototype.toPrecision")
-            .context("failed to get BigDecimal.prototype.toPrecision")
+    fn get_bigdecimal_to_string_function<'a>(&self, ctx: &Ctx<'a>) -> Result<Function<'a>> {
+        ctx.eval("BigDecimal.prototype.toString")
+            .context("failed to get BigDecimal.prototype.toString")
     }
 
-    fn decimal_string_to_i128(&self, s: &str, scale: i8) -> Result<i128> {
+    /// Convert a decimal string at row `i` to the digits of its integer representation at `scale`,
+    /// e.g. `"-1.5"` at scale 2 is `"-150"`.
+    ///
+    /// The string may be in exponent form, e.g. `"1.5e-7"`. Extra fractional digits are rounded
+    /// half away from zero, e.g. `"1.235"` at scale 2 is `"124"`. Returns an error if the value has more than `precision - scale` integer digits.
+    fn decimal_string_to_digits(
+        &self,
+        s: &str,
+        precision: u8,
+        scale: i8,
+        i: usize,
+    ) -> Result<String> {
         if scale < 0 {
-            return Err(anyhow::anyhow!(
-                "currently only supports non-negative scale"
-            ));
+            anyhow::bail!("currently only supports non-negative scale");
         }
-
-        let (integer, fractional): (i128, i128) = match s.split_once('.') {
-            Some((i, f)) => (
-                i.parse().context("failed to parse integer part")?,
-                (f[..scale as usize])
-                    .to_string()
-                    .parse()
-                    .context("failed to parse fractional part")?,
-            ),
-            None => (s.parse().context("failed to parse integer part")?, 0),
+        let (sign, unsigned) = match s.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", s),
         };
+        let (mantissa, exponent) = match unsigned.split_once(|c| matches!(c, 'e' | 'E')) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()),
+            None => (unsigned, Some(0)),
+        };
+        let (integer, fractional) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let exponent = match exponent {
+            Some(exponent)
+                if !integer.is_empty()
+                    && (integer.bytes().chain(fractional.bytes())).all(|b| b.is_ascii_digit()) =>
+            {
+                exponent
+            }
+            _ => anyhow::bail!("invalid decimal {s} at row {i}"),
+        };
+        // move the decimal point by the exponent, `point` is the number of integer digits
+        let digits = format!("{integer}{fractional}");
+        let significant = digits.trim_start_matches('0');
+        let point =
+            integer.len() as i64 + exponent as i64 - (digits.len() - significant.len()) as i64;
+        let (integer, fractional) = if significant.is_empty() || point < -(scale as i64) - 1 {
+            ("0".to_string(), String::new())
+        } else if point > precision as i64 {
+            anyhow::bail!(
+                "decimal {s} at row {i} is out of range for precision {precision} and scale {scale}"
+            );
+        } else if point <= 0 {
+            let zeros = "0".repeat(-point as usize);
+            ("0".to_string(), format!("{zeros}{significant}"))
+        } else if point as usize >= significant.len() {
+            let zeros = "0".repeat(point as usize - significant.len());
+            (format!("{significant}{zeros}"), String::new())
+        } else {
+            let (integer, fractional) = significant.split_at(point as usize);
+            (integer.to_string(), fractional.to_string())
+        };
+        let (fractional, dropped) = fractional.split_at(fractional.len().min(scale as usize));
+        let padding = "0".repeat(scale as usize - fractional.len());
+        let mut digits = format!("{integer}{fractional}{padding}").into_bytes();
+        if dropped.as_bytes().first().is_some_and(|&d| d >= b'5') {
+            // add one to the last digit and carry
+            match digits.iter().rposition(|&d| d != b'9') {
+                Some(pos) => {
+                    digits[pos] += 1;
+                    digits[pos + 1..].fill(b'0');
+                }
+                None => {
+                    digits.fill(b'0');
+                    digits.insert(0, b'1');
+                }
+            }
+        }
+        let digits = String::from_utf8(digits).expect("digits are ascii");
+        if digits.trim_start_matches('0').len() > precision as usize {
+            anyhow::bail!(
+                "decimal {s} at row {i} is out of range for precision {precision} and scale {scale}"
+            );
+        }
+        Ok(format!("{sign}{digits}"))
+    }
 
-        Ok((integer * 10_i128.pow(scale as u32)) + fractional)
+    fn decimal_string_to_i128(&self, s: &str, precision: u8, scale: i8, i: usize) -> Result<i128> {
+        let digits = self.decimal_string_to_digits(s, precision, scale, i)?;
+        digits
+            .parse()
+            .ok()
+            .with_context(|| format!("decimal {s} at row {i} is out of range for Decimal128"))
     }
 
-    fn decimal_string_to_i256(&self, s: &str, scale: i8) -> Result<i256> {
-        if scale < 0 {
-            return Err(anyhow::anyhow!(
-                "currently only supports non-negative scale"
-            ));
-        }
+    fn decimal_string_to_i256(&self, s: &str, precision: u8, scale: i8, i: usize) -> Result<i256> {
+        let digits = self.decimal_string_to_digits(s, precision, scale, i)?;
+        i256::from_string(&digits)
+            .with_context(|| format!("decimal {s} at row {i} is out of range for Decimal256"))
+    }
+}
 
-        // TODO: apply pattern from i128 here
-        let (integer, fractional) = match s.split_once('.') {
-            Some((i, f)) => (
-                i256::from_string(i)
-                    .ok_or_else(|| anyhow::anyhow!("failed to parse integer part"))?,
-                i256::from_string(&f[..scale as usize])
-                    .ok_or_else(|| anyhow::anyhow!("failed to parse fractional part"))?,
-            ),
-            None => (
-                i256::from_string(s)
-                    .ok_or_else(|| anyhow::anyhow!("failed to parse integer part"))?,
-                i256::ZERO,
-            ),
-        };
+/// Format 16 bytes as a hyphenated UUID string, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+fn format_uuid(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(36);
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            s.push('-');
+        }
+        s.push_str(&format!("{b:02x}"));
+    }
+    s
+}
 
-        Ok((integer * i256::from_i128(10).pow_checked(scale as u32)?) + fractional)
+/// Parse a hyphenated UUID string into 16 bytes. Both lowercase and uppercase digits are accepted.
+fn parse_uuid(s: &str) -> Option<[u8; 16]> {
+    let s = s.as_bytes();
+    if s.len() != 36 || [8, 13, 18, 23].iter().any(|&i| s[i] != b'-') {
+        return None;
+    }
+    let hex: Vec<u8> = s.iter().copied().filter(|&c| c != b'-').collect();
+    if hex.len() != 32 || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    let mut bytes = [0; 16];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
     }
+    Some(bytes)
 }
@@ -16,12 +16,20 @@
 
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::{atomic::Ordering, Arc};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context as _, Result};
-use arrow_array::{builder::Int32Builder, Array, ArrayRef, BooleanArray, RecordBatch};
+use arrow_array::{
+    builder::Int32Builder, make_array, Array, ArrayRef, BooleanArray, RecordBatch,
+    RecordBatchOptions,
+};
+use arrow_buffer::NullBuffer;
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
 use arrow_schema::{DataType, Field, FieldRef, Schema, SchemaRef};
+pub use rquickjs;
 pub use rquickjs::runtime::MemoryUsage;
 use rquickjs::{
     context::intrinsic::All, function::Args, module::Evaluated, Context, Ctx, FromJs, Module,
@@ -29,9 +37,13 @@ use rquickjs::{
 };
 
 pub use self::into_field::IntoField;
+pub use self::pool::{RuntimeGuard, RuntimePool};
 
+#[cfg(feature = "datafusion")]
+pub mod datafusion;
 mod into_field;
 mod jsarrow;
+mod pool;
 
 /// A runtime to execute user defined functions in JavaScript.
 ///
@@ -60,14 +72,22 @@ mod jsarrow;
 pub struct Runtime {
     functions: HashMap<String, Function>,
     aggregates: HashMap<String, Aggregate>,
-    // NOTE: `functions` and `aggregates` must be put before the `runtime` and `context` to be dropped first.
+    /// A function deleting the global properties added after the runtime was created.
+    delete_globals: JsFunction,
+    // NOTE: `functions`, `aggregates` and `delete_globals` must be put before the `runtime` and `context` to be dropped first.
     converter: jsarrow::Converter,
     runtime: rquickjs::Runtime,
     context: Context,
     /// Timeout of each function call.
     timeout: Option<Duration>,
+    /// Maximum number of JS values converted from the input of each scalar function call.
+    max_converted_values: Option<usize>,
+    /// Whether to normalize thrown values of functions added from now on.
+    normalize_errors: bool,
     /// Deadline of the current function call.
     deadline: Arc<atomic_time::AtomicOptionInstant>,
+    /// Cancellation token of the current function call, or null if there is none.
+    cancel: Arc<AtomicPtr<AtomicBool>>,
 }
 
 impl Debug for Runtime {
@@ -76,19 +96,110 @@ impl Debug for Runtime {
             .field("functions", &self.functions.keys())
             .field("aggregates", &self.aggregates.keys())
             .field("timeout", &self.timeout)
+            .field("max_converted_values", &self.max_converted_values)
+            .field("normalize_errors", &self.normalize_errors)
             .finish()
     }
 }
 
+/// The error returned by [`Runtime::call_with_cancel`] if the call is cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "function call cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
 /// A user defined scalar function or table function.
 struct Function {
     function: JsFunction,
+    source: Source,
+    normalize_errors: bool,
     return_field: FieldRef,
     mode: CallMode,
+    object_mode: bool,
+    column_mode: bool,
+    typed_array_mode: bool,
+    stateful_mode: bool,
+    multi_column: bool,
+    arg_types: Option<Vec<DataType>>,
+    arg_names: Option<Vec<String>>,
+    variadic: bool,
+}
+
+impl Function {
+    /// Check the input columns against the argument types if they are specified.
+    ///
+    /// For variadic functions, the trailing columns are checked against the last argument type.
+    fn check_input(&self, input: &RecordBatch) -> Result<()> {
+        let Some(arg_types) = &self.arg_types else {
+            return Ok(());
+        };
+        if self.variadic {
+            let num_fixed = arg_types.len() - 1;
+            if input.num_columns() < num_fixed {
+                bail!(
+                    "expected at least {num_fixed} arguments, got {}",
+                    input.num_columns()
+                );
+            }
+        } else if input.num_columns() != arg_types.len() {
+            bail!(
+                "expected {} arguments, got {}",
+                arg_types.len(),
+                input.num_columns()
+            );
+        }
+        for (i, column) in input.columns().iter().enumerate() {
+            let expected = &arg_types[i.min(arg_types.len() - 1)];
+            if column.data_type() != expected {
+                bail!(
+                    "argument {i} expected {expected}, got {}",
+                    column.data_type()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Information of a registered scalar function or table function.
+///
+/// Returned by [`Runtime::function_info`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    /// The field of the returned values.
+    ///
+    /// This is the output column of scalar functions, and the value column of table functions.
+    pub return_field: FieldRef,
+    /// Whether the function is called on null input.
+    pub mode: CallMode,
+    /// The argument types specified by [`FunctionOptions::arg_types`].
+    pub arg_types: Option<Vec<DataType>>,
+    /// The argument names specified by [`FunctionOptions::arg_names`].
+    pub arg_names: Option<Vec<String>>,
+    /// Whether the function is variadic.
+    pub variadic: bool,
+}
+
+/// The JavaScript code of a scalar function or table function.
+///
+/// The code is kept to re-evaluate the function in [`Runtime::reset_globals`].
+enum Source {
+    /// A module and the name of the exported function.
+    Module { code: String, handler: String },
+    /// An expression that evaluates to the function.
+    Expr(String),
 }
 
 /// A user defined aggregate function.
 struct Aggregate {
+    code: String,
+    normalize_errors: bool,
     state_field: FieldRef,
     output_field: FieldRef,
     mode: CallMode,
@@ -102,12 +213,11 @@ struct Aggregate {
 /// A persistent function.
 type JsFunction = Persistent<rquickjs::Function<'static>>;
 
-// SAFETY: `rquickjs::Runtime` is `Send` and `Sync`
+// SAFETY: `rquickjs::Runtime` is `Send`
 unsafe impl Send for Runtime {}
-unsafe impl Sync for Runtime {}
 
 /// Whether the function will be called when some of its arguments are null.
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CallMode {
     /// The function will be called normally when some of its arguments are null.
     /// It is then the function author's responsibility to check for null values if necessary and respond appropriately.
@@ -120,22 +230,456 @@ pub enum CallMode {
     ReturnNullOnNullInput,
 }
 
-impl Runtime {
-    /// Create a new `Runtime`.
-    pub fn new() -> Result<Self> {
+/// How a non-integer number returned by a function is converted to an integer type.
+///
+/// | JS value                  | `Error`  | `Truncate`        | `Round`                 |
+/// | ------------------------- | -------- | ----------------- | ----------------------- |
+/// | integer, e.g. `3`         | `3`      | `3`               | `3`                     |
+/// | non-integer, e.g. `-3.7`  | error    | `-3`              | `-4`                    |
+/// | `NaN`, `Infinity`         | error    | error             | error                   |
+/// | out of range              | error    | error             | error                   |
+/// | not a number, e.g. `"3"`  | error    | error             | error                   |
+///
+/// The range is checked after the conversion, e.g. `127.5` is out of range for `Int8` with `Round`.
+/// Float types accept any number, and integer types of 64 bits also accept `BigInt`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NumberCoercion {
+    /// Report an error for non-integer numbers.
+    #[default]
+    Error,
+    /// Round non-integer numbers toward zero.
+    Truncate,
+    /// Round non-integer numbers to the nearest integer, and half-way cases away from zero.
+    Round,
+}
+
+impl NumberCoercion {
+    /// Convert the `number` at row `i` to an integer.
+    fn to_integer(self, number: f64, i: usize, type_name: &str) -> Result<f64> {
+        if number.fract() == 0.0 {
+            return Ok(number);
+        }
+        match self {
+            _ if !number.is_finite() => {
+                bail!("number {number} at row {i} is not an integer for {type_name}")
+            }
+            Self::Error => bail!("number {number} at row {i} is not an integer for {type_name}"),
+            Self::Truncate => Ok(number.trunc()),
+            Self::Round => Ok(number.round()),
+        }
+    }
+}
+
+/// How a non-finite number returned by a function is converted to a float type.
+///
+/// | JS value                     | `Propagate` | `NullOnNonFinite` | `ErrorOnNonFinite` |
+/// | ---------------------------- | ----------- | ----------------- | ------------------ |
+/// | finite, e.g. `1.5`           | `1.5`       | `1.5`             | `1.5`              |
+/// | `NaN`                        | `NaN`       | null              | error              |
+/// | `Infinity`, `-Infinity`      | `inf`       | null              | error              |
+/// | out of range for `Float32`   | `inf`       | null              | error              |
+///
+/// Float typed arrays returned in typed array mode are taken as is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NonFiniteFloat {
+    /// Keep `NaN` and infinities in the array.
+    #[default]
+    Propagate,
+    /// Convert `NaN` and infinities to null.
+    NullOnNonFinite,
+    /// Report an error for `NaN` and infinities.
+    ErrorOnNonFinite,
+}
+
+impl NonFiniteFloat {
+    /// Returns whether to keep the non-finite `number` at row `i`, or report an error.
+    fn keep(self, number: f64, i: usize, type_name: &str) -> Result<bool> {
+        match self {
+            Self::Propagate => Ok(true),
+            Self::NullOnNonFinite => Ok(false),
+            Self::ErrorOnNonFinite => {
+                bail!("number {number} at row {i} is not finite for {type_name}")
+            }
+        }
+    }
+}
+
+/// Options for adding a scalar function or table function.
+///
+/// # Example
+///
+/// ```
+/// # use arrow_udf_js::FunctionOptions;
+/// # use arrow_schema::DataType;
+/// let options = FunctionOptions::default()
+///     .handler("my_handler")
+///     .object_mode(true)
+///     .arg_types(vec![DataType::Int32, DataType::Utf8]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FunctionOptions {
+    handler: Option<String>,
+    object_mode: bool,
+    column_mode: bool,
+    typed_array_mode: bool,
+    stateful_mode: bool,
+    multi_column: bool,
+    arg_types: Option<Vec<DataType>>,
+    arg_names: Option<Vec<String>>,
+    variadic: bool,
+}
+
+impl FunctionOptions {
+    /// Set the name of the function in JavaScript code to be called.
+    ///
+    /// The default value is the name of the function.
+    pub fn handler(mut self, handler: &str) -> Self {
+        self.handler = Some(handler.to_string());
+        self
+    }
+
+    /// Pass each row to the function as a single object keyed by the field names of the input schema,
+    /// instead of positional arguments.
+    ///
+    /// The default value is `false`.
+    pub fn object_mode(mut self, object_mode: bool) -> Self {
+        self.object_mode = object_mode;
+        self
+    }
+
+    /// Call the scalar function once per batch with a reader object for each input column,
+    /// instead of once per row.
+    ///
+    /// Each reader has a `length` property, and `get(i)` and `isNull(i)` methods that convert
+    /// the value at row `i` on demand. The function must return an array with one value per row.
+    /// With [`CallMode::ReturnNullOnNullInput`], the rows where any input is null are set to null
+    /// regardless of the returned value.
+    ///
+    /// The column mode can not be used together with the object mode or for table functions.
+    /// The default value is `false`.
+    pub fn column_mode(mut self, column_mode: bool) -> Self {
+        self.column_mode = column_mode;
+        self
+    }
+
+    /// Call the scalar function once per batch with the input columns as typed arrays,
+    /// i.e. a structure of arrays, instead of once per row.
+    ///
+    /// The function is called with an object keyed by the field names of the input schema,
+    /// e.g. `{ a: Int32Array, b: Float64Array }`, and the number of rows. It must return a typed
+    /// array of the return type, e.g. `Int32Array` for `Int32`, or an array with one value per
+    /// row. This avoids converting each value, so that tight loops over the columns can be
+    /// optimized by the JS engine.
+    ///
+    /// All input columns must be numeric. Since typed arrays can not hold nulls, the values of
+    /// null elements are unspecified, and the mode must be [`CallMode::ReturnNullOnNullInput`]:
+    /// the rows where any input is null are set to null regardless of the returned value.
+    ///
+    /// The typed array mode can not be used together with the object mode, the column mode,
+    /// multi-column output, or for table functions. The default value is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_with_options(
+    ///         "add",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         r#"
+    ///         export function add({ a, b }, n) {
+    ///             const c = new Int32Array(n);
+    ///             for (let i = 0; i < n; i++) c[i] = a[i] + b[i];
+    ///             return c;
+    ///         }
+    ///         "#,
+    ///         FunctionOptions::default().typed_array_mode(true),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn typed_array_mode(mut self, typed_array_mode: bool) -> Self {
+        self.typed_array_mode = typed_array_mode;
+        self
+    }
+
+    /// Process all rows of a batch with a single generator of the table function,
+    /// so that it can keep state across rows.
+    ///
+    /// By default, the table function is called for each row, and the returned generator yields
+    /// the values of that row. This suits functions whose output only depends on the row, e.g.
+    /// `range(n)`.
+    ///
+    /// In stateful mode, the function is called without arguments once per input batch.
+    /// The arguments of each row are sent to the returned generator as an array, i.e. the
+    /// result of `yield`, and the generator then yields the values of that row as an iterable,
+    /// or `undefined` for no values. The value of the first `yield` is ignored. This suits
+    /// functions that depend on the previous rows, e.g. running totals or sequence numbers.
+    /// If the generator returns before all rows are sent, the remaining rows have no output.
+    ///
+    /// In object mode, the array contains the object of each row. Rows with null arguments are
+    /// not sent with [`CallMode::ReturnNullOnNullInput`].
+    ///
+    /// The stateful mode can only be used for table functions.
+    /// The default value is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_with_options(
+    ///         "running_sum",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         r#"
+    ///         export function* running_sum() {
+    ///             let sum = 0;
+    ///             let values;
+    ///             while (true) {
+    ///                 const [x] = yield values;
+    ///                 sum += x;
+    ///                 values = [sum];
+    ///             }
+    ///         }
+    ///         "#,
+    ///         FunctionOptions::default().stateful_mode(true),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn stateful_mode(mut self, stateful_mode: bool) -> Self {
+        self.stateful_mode = stateful_mode;
+        self
+    }
+
+    /// Return multiple columns instead of a single column.
+    ///
+    /// The return type must be a struct, whose fields are the output columns. The function must
+    /// return an array with one value per column for each row, e.g. `[sum, product]` for the
+    /// return type `struct<sum int, product int>`, or null for a row of nulls. The output of
+    /// [`Runtime::call`] then has a column for each field instead of a single struct column.
+    ///
+    /// The multi-column output can not be used for table functions.
+    /// The default value is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_schema::{DataType, Field};
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_with_options(
+    ///         "sum_product",
+    ///         DataType::Struct(
+    ///             vec![
+    ///                 Field::new("sum", DataType::Int32, true),
+    ///                 Field::new("product", DataType::Int32, true),
+    ///             ]
+    ///             .into(),
+    ///         ),
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         "export function sum_product(a, b) { return [a + b, a * b]; }",
+    ///         FunctionOptions::default().multi_column(true),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn multi_column(mut self, multi_column: bool) -> Self {
+        self.multi_column = multi_column;
+        self
+    }
+
+    /// Set the expected data types of arguments.
+    ///
+    /// If specified, the number and types of input columns are checked before each call.
+    /// The default value is `None`, which means no check.
+    pub fn arg_types(mut self, arg_types: Vec<DataType>) -> Self {
+        self.arg_types = Some(arg_types);
+        self
+    }
+
+    /// Set the names of arguments.
+    ///
+    /// They are used by [`Runtime::call_by_name`] to select the input columns by field name.
+    /// The default value is `None`.
+    pub fn arg_names(mut self, arg_names: Vec<String>) -> Self {
+        self.arg_names = Some(arg_names);
+        self
+    }
+
+    /// Accept any number of trailing arguments of the last type in [`arg_types`].
+    ///
+    /// All input columns are passed as positional arguments, so the trailing ones can be collected
+    /// by a rest parameter, e.g. `function greatest(...args)`. There may be no trailing argument.
+    /// The default value is `false`.
+    ///
+    /// [`arg_types`]: FunctionOptions::arg_types
+    pub fn variadic(mut self, variadic: bool) -> Self {
+        self.variadic = variadic;
+        self
+    }
+}
+
+/// A builder to configure and create a [`Runtime`].
+///
+/// Each option has the same meaning as the corresponding setter of [`Runtime`].
+///
+/// # Example
+///
+/// ```
+/// # use arrow_udf_js::Runtime;
+/// # use std::time::Duration;
+/// let runtime = Runtime::builder()
+///     .memory_limit(1 << 20) // 1MB
+///     .timeout(Duration::from_secs(1))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct RuntimeBuilder {
+    memory_limit: Option<usize>,
+    timeout: Option<Duration>,
+    max_converted_values: Option<usize>,
+    normalize_errors: bool,
+    number_coercion: NumberCoercion,
+    non_finite_float: NonFiniteFloat,
+}
+
+impl RuntimeBuilder {
+    /// Set the memory limit of the runtime. See [`Runtime::set_memory_limit`].
+    pub fn memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Set the timeout of each function call. See [`Runtime::set_timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum number of JS values converted from the input of each scalar function call.
+    /// See [`Runtime::set_max_converted_values`].
+    pub fn max_converted_values(mut self, limit: usize) -> Self {
+        self.max_converted_values = Some(limit);
+        self
+    }
+
+    /// Set whether to normalize the values thrown by functions. See [`Runtime::set_normalize_errors`].
+    pub fn normalize_errors(mut self, normalize_errors: bool) -> Self {
+        self.normalize_errors = normalize_errors;
+        self
+    }
+
+    /// Set how non-integer numbers are converted to integer types. See [`Runtime::set_number_coercion`].
+    pub fn number_coercion(mut self, coercion: NumberCoercion) -> Self {
+        self.number_coercion = coercion;
+        self
+    }
+
+    /// Set how non-finite numbers are converted to float types. See [`Runtime::set_non_finite_float`].
+    pub fn non_finite_float(mut self, non_finite: NonFiniteFloat) -> Self {
+        self.non_finite_float = non_finite;
+        self
+    }
+
+    /// Create a new `Runtime` with the options.
+    pub fn build(self) -> Result<Runtime> {
         let runtime = rquickjs::Runtime::new().context("failed to create quickjs runtime")?;
+        self.build_with_quickjs(runtime)
+    }
+
+    /// Create a new `Runtime` with the options on top of an existing quickjs runtime.
+    ///
+    /// See [`Runtime::with_quickjs`] for how the quickjs runtime is used.
+    pub fn build_with_quickjs(self, runtime: rquickjs::Runtime) -> Result<Runtime> {
         let context = rquickjs::Context::custom::<All>(&runtime)
             .context("failed to create quickjs context")?;
+        let delete_globals = context.with(|ctx| {
+            let function: rquickjs::Function = ctx
+                .eval(DELETE_GLOBALS)
+                .map_err(|e| check_exception(e, &ctx))
+                .context("failed to snapshot globals")?;
+            Ok(Persistent::save(&ctx, function)) as Result<_>
+        })?;
 
-        Ok(Self {
+        let mut runtime = Runtime {
             functions: HashMap::new(),
             aggregates: HashMap::new(),
+            delete_globals,
             runtime,
             context,
             timeout: None,
+            max_converted_values: self.max_converted_values,
+            normalize_errors: self.normalize_errors,
             deadline: Default::default(),
+            cancel: Default::default(),
             converter: jsarrow::Converter::new(),
-        })
+        };
+        let deadline = runtime.deadline.clone();
+        let cancel = runtime.cancel.clone();
+        runtime
+            .runtime
+            .set_interrupt_handler(Some(Box::new(move || {
+                let token = cancel.load(Ordering::Relaxed);
+                // SAFETY: the token is only set during `call_with_cancel`, which borrows it.
+                if !token.is_null() && unsafe { (*token).load(Ordering::Relaxed) } {
+                    return true;
+                }
+                if let Some(deadline) = deadline.load(Ordering::Relaxed) {
+                    return deadline <= Instant::now();
+                }
+                false
+            })));
+        runtime.set_number_coercion(self.number_coercion);
+        runtime.set_non_finite_float(self.non_finite_float);
+        if let Some(limit) = self.memory_limit {
+            runtime.set_memory_limit(Some(limit));
+        }
+        if let Some(timeout) = self.timeout {
+            runtime.set_timeout(Some(timeout));
+        }
+        Ok(runtime)
+    }
+}
+
+impl Runtime {
+    /// Create a new `Runtime` with default options.
+    ///
+    /// This is equivalent to `Runtime::builder().build()`.
+    pub fn new() -> Result<Self> {
+        RuntimeBuilder::default().build()
+    }
+
+    /// Create a new `Runtime` with default options on top of an existing quickjs runtime.
+    ///
+    /// This allows embedders to configure the quickjs runtime, e.g. the allocator or module loader,
+    /// before creating the `Runtime`. A new context is created in the runtime with all intrinsics,
+    /// since functions rely on `BaseObjects`, `Eval`, `Json`, `Date`, `TypedArrays` and
+    /// `BigDecimal`. The interrupt handler of the runtime is replaced to support timeouts and
+    /// cancellation, and the memory limit is only changed if it is set.
+    ///
+    /// This is equivalent to `Runtime::builder().build_with_quickjs(runtime)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{rquickjs, Runtime};
+    /// let quickjs = rquickjs::Runtime::new().unwrap();
+    /// quickjs.set_max_stack_size(1 << 20);
+    /// let runtime = Runtime::with_quickjs(quickjs).unwrap();
+    /// ```
+    pub fn with_quickjs(runtime: rquickjs::Runtime) -> Result<Self> {
+        RuntimeBuilder::default().build_with_quickjs(runtime)
+    }
+
+    /// Return a [`RuntimeBuilder`] to configure a new `Runtime`.
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
     }
 
     /// Set the memory limit of the runtime.
@@ -163,17 +707,77 @@ impl Runtime {
     /// ```
     pub fn set_timeout(&mut self, timeout: Option<Duration>) {
         self.timeout = timeout;
-        if timeout.is_some() {
-            let deadline = self.deadline.clone();
-            self.runtime.set_interrupt_handler(Some(Box::new(move || {
-                if let Some(deadline) = deadline.load(Ordering::Relaxed) {
-                    return deadline <= Instant::now();
-                }
-                false
-            })));
-        } else {
-            self.runtime.set_interrupt_handler(None);
-        }
+    }
+
+    /// Set the maximum number of JS values converted from the input of each scalar function call.
+    ///
+    /// Each row converts one value per input column. If converting the next row would exceed the
+    /// limit, [`call`] returns an error. The default value is `None`, which means unlimited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::Runtime;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime.set_max_converted_values(Some(1 << 20));
+    /// ```
+    ///
+    /// [`call`]: Runtime::call
+    pub fn set_max_converted_values(&mut self, limit: Option<usize>) {
+        self.max_converted_values = limit;
+    }
+
+    /// Set whether to normalize the values thrown by functions.
+    ///
+    /// If enabled, each function added afterwards is wrapped in a `try`/`catch` in JavaScript,
+    /// which converts any thrown value into an object `{ message, stack }` before returning to Rust.
+    /// This also applies to errors thrown by generators of table functions.
+    /// The resulting error message is always `exception generated by QuickJS: {message}`,
+    /// followed by the stack if available.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::Runtime;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime.set_normalize_errors(true);
+    /// ```
+    pub fn set_normalize_errors(&mut self, normalize_errors: bool) {
+        self.normalize_errors = normalize_errors;
+    }
+
+    /// Set how non-integer numbers returned by functions are converted to integer types.
+    ///
+    /// See [`NumberCoercion`] for the conversion of each value.
+    /// The default value is [`NumberCoercion::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{NumberCoercion, Runtime};
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime.set_number_coercion(NumberCoercion::Truncate);
+    /// ```
+    pub fn set_number_coercion(&mut self, coercion: NumberCoercion) {
+        self.converter.set_number_coercion(coercion);
+    }
+
+    /// Set how `NaN` and infinities returned by functions are converted to float types.
+    ///
+    /// See [`NonFiniteFloat`] for the conversion of each value.
+    /// The default value is [`NonFiniteFloat::Propagate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{NonFiniteFloat, Runtime};
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime.set_non_finite_float(NonFiniteFloat::NullOnNonFinite);
+    /// ```
+    pub fn set_non_finite_float(&mut self, non_finite: NonFiniteFloat) {
+        self.converter.set_non_finite_float(non_finite);
     }
 
     /// Get memory usage of the internal quickjs runtime.
@@ -189,6 +793,26 @@ impl Runtime {
         self.runtime.memory_usage()
     }
 
+    /// Run the garbage collector of the internal quickjs runtime.
+    ///
+    /// Values are freed as soon as they are unreachable unless they form a cycle, which is only
+    /// freed by the garbage collector. Call this before [`memory_usage`] to get the memory retained
+    /// by functions, e.g. to detect functions accumulating state across calls.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::Runtime;
+    /// let runtime = Runtime::new().unwrap();
+    /// runtime.run_gc();
+    /// let usage = runtime.memory_usage();
+    /// ```
+    ///
+    /// [`memory_usage`]: Runtime::memory_usage
+    pub fn run_gc(&self) {
+        self.runtime.run_gc();
+    }
+
     /// Return the converter where you can configure the extension metadata key and values.
     pub fn converter_mut(&mut self) -> &mut jsarrow::Converter {
         &mut self.converter
@@ -206,6 +830,9 @@ impl Runtime {
     /// The code should define an **exported** function with the same name as the function.
     /// The function should return a value for scalar functions, or yield values for table functions.
     ///
+    /// If a function with the same name exists, it is replaced. Use [`try_add_function`] to
+    /// report an error instead, or [`replace_function`] to get the replaced function.
+    ///
     /// # Example
     ///
     /// ```
@@ -246,6 +873,9 @@ impl Runtime {
     ///     )
     ///     .unwrap();
     /// ```
+    ///
+    /// [`try_add_function`]: Runtime::try_add_function
+    /// [`replace_function`]: Runtime::replace_function
     pub fn add_function(
         &mut self,
         name: &str,
@@ -256,49 +886,498 @@ impl Runtime {
         self.add_function_with_handler(name, return_type, mode, code, name)
     }
 
-    /// Add a new scalar function or table function with custom handler name.
+    /// Add a new scalar function or table function if no function with the same name exists.
     ///
-    /// # Arguments
-    ///
-    /// - `handler`: The name of function in Python code to be called.
-    /// - others: Same as [`add_function`].
+    /// Returns an error without compiling the code if the name is already registered.
+    /// See [`add_function`] for the arguments.
     ///
     /// [`add_function`]: Runtime::add_function
-    pub fn add_function_with_handler(
+    pub fn try_add_function(
         &mut self,
         name: &str,
         return_type: impl IntoField,
         mode: CallMode,
         code: &str,
-        handler: &str,
     ) -> Result<()> {
-        let function = self.context.with(|ctx| {
-            let (module, _) = Module::declare(ctx.clone(), name, code)
-                .map_err(|e| check_exception(e, &ctx))
-                .context("failed to declare module")?
-                .eval()
-                .map_err(|e| check_exception(e, &ctx))
-                .context("failed to evaluate module")?;
-            Self::get_function(&ctx, &module, handler)
-        })?;
-        let function = Function {
-            function,
-            return_field: return_type.into_field(name).into(),
-            mode,
-        };
-        self.functions.insert(name.to_string(), function);
-        Ok(())
+        if self.functions.contains_key(name) {
+            bail!("function \"{name}\" already exists");
+        }
+        self.add_function(name, return_type, mode, code)
+    }
+
+    /// Add a new scalar function or table function, replacing the function with the same name.
+    ///
+    /// Returns the return type and mode of the replaced function, or `None` if there was none.
+    /// See [`add_function`] for the arguments.
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn replace_function(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+    ) -> Result<Option<(DataType, CallMode)>> {
+        let (function, _) =
+            self.compile_function(name, return_type, mode, code, FunctionOptions::default())?;
+        let previous = self.functions.insert(name.to_string(), function);
+        Ok(previous.map(|f| (f.return_field.data_type().clone(), f.mode)))
+    }
+
+    /// Add a new scalar function or table function with custom handler name.
+    ///
+    /// # Arguments
+    ///
+    /// - `handler`: The name of function in Python code to be called.
+    /// - others: Same as [`add_function`].
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn add_function_with_handler(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+        handler: &str,
+    ) -> Result<()> {
+        self.add_function_with_options(
+            name,
+            return_type,
+            mode,
+            code,
+            FunctionOptions::default().handler(handler),
+        )
+    }
+
+    /// Add a new scalar function or table function with options.
+    ///
+    /// # Arguments
+    ///
+    /// - `options`: See [`FunctionOptions`].
+    /// - others: Same as [`add_function`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_with_options(
+    ///         "full_name",
+    ///         DataType::Utf8,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         r#"
+    ///         export function full_name(row) {
+    ///             return row.first_name + " " + row.last_name;
+    ///         }
+    /// "#,
+    ///         FunctionOptions::default().object_mode(true),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn add_function_with_options(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+        options: FunctionOptions,
+    ) -> Result<()> {
+        self.add_function_with_warnings(name, return_type, mode, code, options)?;
+        Ok(())
     }
 
-    /// Get a function from a module.
+    /// Add a new scalar function or table function with options, returning the warnings about it.
+    ///
+    /// Module code is always in strict mode, and syntax errors and exceptions thrown by the
+    /// top-level code of the module fail the registration. In addition, this method checks the
+    /// exported function for mistakes that would fail or misbehave on the first call:
+    ///
+    /// - The function is async, so it returns promises instead of values.
+    /// - The function declares more parameters than the arguments it is called with, which are
+    ///   known if `arg_types` is set. In object mode, it is called with one argument.
+    ///
+    /// The function is added even if there are warnings. The body of the function is not
+    /// analyzed, so references to undefined globals are only detected when they are evaluated,
+    /// which throws a `ReferenceError` in strict mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// let warnings = runtime
+    ///     .add_function_with_warnings(
+    ///         "add",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         "export function add(a, b, c) { return a + b + c; }",
+    ///         FunctionOptions::default().arg_types(vec![DataType::Int32, DataType::Int32]),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     warnings,
+    ///     ["function \"add\" declares 3 parameters, but is called with 2 arguments"]
+    /// );
+    /// ```
+    pub fn add_function_with_warnings(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+        options: FunctionOptions,
+    ) -> Result<Vec<String>> {
+        let (function, warnings) = self.compile_function(name, return_type, mode, code, options)?;
+        self.functions.insert(name.to_string(), function);
+        Ok(warnings)
+    }
+
+    /// Compile a scalar function or table function with options.
+    ///
+    /// Returns the function and the warnings about it.
+    fn compile_function(
+        &self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+        options: FunctionOptions,
+    ) -> Result<(Function, Vec<String>)> {
+        if options.object_mode && options.column_mode {
+            bail!("object mode and column mode can not be used together");
+        }
+        if options.typed_array_mode {
+            if options.object_mode || options.column_mode || options.multi_column {
+                bail!("typed array mode can not be used with object mode, column mode or multi-column output");
+            }
+            if mode != CallMode::ReturnNullOnNullInput {
+                bail!("typed array mode requires `CallMode::ReturnNullOnNullInput`");
+            }
+        }
+        if options.stateful_mode
+            && (options.column_mode || options.typed_array_mode || options.multi_column)
+        {
+            bail!("stateful mode can not be used with column mode, typed array mode or multi-column output");
+        }
+        if options.variadic && options.arg_types.as_ref().is_some_and(|t| t.is_empty()) {
+            bail!("variadic functions must have at least one argument type");
+        }
+        let return_field = return_type.into_field(name);
+        if options.multi_column && !matches!(return_field.data_type(), DataType::Struct(_)) {
+            bail!(
+                "multi-column functions must return a struct type, got {}",
+                return_field.data_type()
+            );
+        }
+        let source = Source::Module {
+            code: code.to_string(),
+            handler: options.handler.clone().unwrap_or_else(|| name.to_string()),
+        };
+        let (function, warnings) = self.context.with(|ctx| {
+            let function = Self::eval_source(&ctx, name, &source)?;
+            let warnings = function_warnings(name, &function, &options);
+            let function = Self::save_function(&ctx, function, self.normalize_errors)?;
+            Ok((function, warnings)) as Result<_>
+        })?;
+        let function = Function {
+            function,
+            source,
+            normalize_errors: self.normalize_errors,
+            return_field: return_field.into(),
+            mode,
+            object_mode: options.object_mode,
+            column_mode: options.column_mode,
+            typed_array_mode: options.typed_array_mode,
+            stateful_mode: options.stateful_mode,
+            multi_column: options.multi_column,
+            arg_types: options.arg_types,
+            arg_names: options.arg_names,
+            variadic: options.variadic,
+        };
+        Ok((function, warnings))
+    }
+
+    /// Add a new scalar function or table function from a JavaScript expression.
+    ///
+    /// Unlike [`add_function`], the code is not a module but an expression that evaluates to a
+    /// function, e.g. an arrow function. The function is registered under `name`. Like modules,
+    /// the expression is evaluated in strict mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_expr(
+    ///         "add",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         "(a, b) => a + b",
+    ///     )
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn add_function_expr(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        expr: &str,
+    ) -> Result<()> {
+        let source = Source::Expr(expr.to_string());
+        let function = self.compile_source(name, &source, self.normalize_errors)?;
+        let function = Function {
+            function,
+            source,
+            normalize_errors: self.normalize_errors,
+            return_field: return_type.into_field(name).into(),
+            mode,
+            object_mode: false,
+            column_mode: false,
+            typed_array_mode: false,
+            stateful_mode: false,
+            multi_column: false,
+            arg_types: None,
+            arg_names: None,
+            variadic: false,
+        };
+        self.functions.insert(name.to_string(), function);
+        Ok(())
+    }
+
+    /// Add many scalar functions or table functions at once.
+    ///
+    /// Each item is a tuple of `(name, return_type, mode, code)`, the same as the arguments of
+    /// [`add_function`]. The functions are added in order. If one of them fails, the error
+    /// contains the name of that function, and the functions before it remain registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .register_all([
+    ///         (
+    ///             "add",
+    ///             DataType::Int32,
+    ///             CallMode::ReturnNullOnNullInput,
+    ///             "export function add(a, b) { return a + b; }",
+    ///         ),
+    ///         (
+    ///             "sub",
+    ///             DataType::Int32,
+    ///             CallMode::ReturnNullOnNullInput,
+    ///             "export function sub(a, b) { return a - b; }",
+    ///         ),
+    ///     ])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn register_all<N, T, C>(
+        &mut self,
+        functions: impl IntoIterator<Item = (N, T, CallMode, C)>,
+    ) -> Result<()>
+    where
+        N: AsRef<str>,
+        T: IntoField,
+        C: AsRef<str>,
+    {
+        for (name, return_type, mode, code) in functions {
+            let name = name.as_ref();
+            self.add_function(name, return_type, mode, code.as_ref())
+                .with_context(|| format!("failed to add function \"{name}\""))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the information of a registered scalar function or table function.
+    ///
+    /// Returns `None` if there is no function with the name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{Runtime, CallMode};
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function(
+    ///         "add",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         "export function add(a, b) { return a + b; }",
+    ///     )
+    ///     .unwrap();
+    /// let info = runtime.function_info("add").unwrap();
+    /// assert_eq!(info.return_field.data_type(), &DataType::Int32);
+    /// assert_eq!(info.mode, CallMode::ReturnNullOnNullInput);
+    /// ```
+    pub fn function_info(&self, name: &str) -> Option<FunctionInfo> {
+        let function = self.functions.get(name)?;
+        Some(FunctionInfo {
+            return_field: function.return_field.clone(),
+            mode: function.mode,
+            arg_types: function.arg_types.clone(),
+            arg_names: function.arg_names.clone(),
+            variadic: function.variadic,
+        })
+    }
+
+    /// List the names of all exports of the JavaScript code.
+    ///
+    /// The code is compiled and evaluated in the same way as [`add_function`],
+    /// but no function is registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::Runtime;
+    /// let runtime = Runtime::new().unwrap();
+    /// let exports = runtime
+    ///     .module_exports(
+    ///         r#"
+    ///         export function add(a, b) {
+    ///             return a + b;
+    ///         }
+    ///         export function sub(a, b) {
+    ///             return a - b;
+    ///         }
+    ///         "#,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(exports, ["add", "sub"]);
+    /// ```
+    ///
+    /// [`add_function`]: Runtime::add_function
+    pub fn module_exports(&self, code: &str) -> Result<Vec<String>> {
+        self.context.with(|ctx| {
+            let module = Self::compile_module(&ctx, "module_exports", code)?;
+            let names = module
+                .names::<String>()
+                .collect::<rquickjs::Result<Vec<_>>>()
+                .map_err(|e| check_exception(e, &ctx))
+                .context("failed to get exports of module")?;
+            Ok(names)
+        })
+    }
+
+    /// Evaluate the code of a scalar function or table function.
+    fn compile_source(
+        &self,
+        name: &str,
+        source: &Source,
+        normalize_errors: bool,
+    ) -> Result<JsFunction> {
+        self.context.with(|ctx| {
+            let function = Self::eval_source(&ctx, name, source)?;
+            Self::save_function(&ctx, function, normalize_errors)
+        })
+    }
+
+    /// Evaluate the code of a scalar function or table function, returning the function.
+    fn eval_source<'a>(
+        ctx: &Ctx<'a>,
+        name: &str,
+        source: &Source,
+    ) -> Result<rquickjs::Function<'a>> {
+        match source {
+            Source::Module { code, handler } => {
+                let module = Self::compile_module(ctx, name, code)?;
+                Self::export_function(&module, handler)
+            }
+            Source::Expr(expr) => {
+                // the newlines allow the expression to end with a line comment
+                let value: rquickjs::Value = ctx
+                    .eval(format!("\"use strict\";\n(\n{expr}\n)"))
+                    .map_err(|e| check_exception(e, ctx))
+                    .context("failed to evaluate expression")?;
+                let Some(function) = value.as_function() else {
+                    bail!(
+                        "expression of function \"{name}\" must evaluate to a function, got {}",
+                        value.type_of().as_str()
+                    );
+                };
+                Ok(function.clone())
+            }
+        }
+    }
+
+    /// Declare and evaluate a module.
+    fn compile_module<'a>(ctx: &Ctx<'a>, name: &str, code: &str) -> Result<Module<'a, Evaluated>> {
+        let (module, _) = Module::declare(ctx.clone(), name, code)
+            .map_err(|e| check_compile_exception(e, ctx))
+            .context("failed to declare module")?
+            .eval()
+            .map_err(|e| check_exception(e, ctx))
+            .context("failed to evaluate module")?;
+        Ok(module)
+    }
+
+    /// Get a function from a module and save it to be called later.
     fn get_function<'a>(
         ctx: &Ctx<'a>,
         module: &Module<'a, Evaluated>,
         name: &str,
+        normalize_errors: bool,
     ) -> Result<JsFunction> {
-        let function: rquickjs::Function = module.get(name).with_context(|| {
-            format!("function \"{name}\" not found. HINT: make sure the function is exported")
-        })?;
+        let function = Self::export_function(module, name)?;
+        Self::save_function(ctx, function, normalize_errors)
+    }
+
+    /// Get an exported function from a module.
+    ///
+    /// If the function is not exported, the error lists the names the module does export.
+    fn export_function<'a>(
+        module: &Module<'a, Evaluated>,
+        name: &str,
+    ) -> Result<rquickjs::Function<'a>> {
+        let function: rquickjs::Function = match module.get(name) {
+            Ok(function) => function,
+            Err(_) => {
+                let names = module
+                    .names::<String>()
+                    .collect::<rquickjs::Result<Vec<_>>>()
+                    .unwrap_or_default();
+                if names.iter().any(|n| n == name) {
+                    bail!("export \"{name}\" is not a function");
+                }
+                bail!(
+                    "function \"{name}\" not found; exports are: [{}]",
+                    names.join(", ")
+                );
+            }
+        };
+        Ok(function)
+    }
+
+    /// Save a function to be called later.
+    ///
+    /// If `normalize_errors` is enabled, the function is wrapped to normalize thrown values.
+    fn save_function<'a>(
+        ctx: &Ctx<'a>,
+        mut function: rquickjs::Function<'a>,
+        normalize_errors: bool,
+    ) -> Result<JsFunction> {
+        if normalize_errors {
+            let wrap: rquickjs::Function = ctx
+                .eval(NORMALIZE_ERRORS_WRAPPER)
+                .map_err(|e| check_exception(e, ctx))
+                .context("failed to create error wrapper")?;
+            function = wrap
+                .call((function,))
+                .map_err(|e| check_exception(e, ctx))?;
+        }
         Ok(Persistent::save(ctx, function))
     }
 
@@ -364,24 +1443,14 @@ impl Runtime {
         mode: CallMode,
         code: &str,
     ) -> Result<()> {
-        let aggregate = self.context.with(|ctx| {
-            let (module, _) = Module::declare(ctx.clone(), name, code)
-                .map_err(|e| check_exception(e, &ctx))
-                .context("failed to declare module")?
-                .eval()
-                .map_err(|e| check_exception(e, &ctx))
-                .context("failed to evaluate module")?;
-            Ok(Aggregate {
-                state_field: state_type.into_field(name).into(),
-                output_field: output_type.into_field(name).into(),
-                mode,
-                create_state: Self::get_function(&ctx, &module, "create_state")?,
-                accumulate: Self::get_function(&ctx, &module, "accumulate")?,
-                retract: Self::get_function(&ctx, &module, "retract").ok(),
-                finish: Self::get_function(&ctx, &module, "finish").ok(),
-                merge: Self::get_function(&ctx, &module, "merge").ok(),
-            }) as Result<Aggregate>
-        })?;
+        let aggregate = self.compile_aggregate(
+            name,
+            state_type.into_field(name).into(),
+            output_type.into_field(name).into(),
+            mode,
+            code,
+            self.normalize_errors,
+        )?;
         if aggregate.finish.is_none() && aggregate.state_field != aggregate.output_field {
             bail!("`output_type` must be the same as `state_type` when `finish` is not defined");
         }
@@ -389,64 +1458,622 @@ impl Runtime {
         Ok(())
     }
 
-    /// Call a scalar function.
+    /// Evaluate the code of an aggregate function.
+    fn compile_aggregate(
+        &self,
+        name: &str,
+        state_field: FieldRef,
+        output_field: FieldRef,
+        mode: CallMode,
+        code: &str,
+        normalize_errors: bool,
+    ) -> Result<Aggregate> {
+        self.context.with(|ctx| {
+            let module = Self::compile_module(&ctx, name, code)?;
+            let get = |name| Self::get_function(&ctx, &module, name, normalize_errors);
+            Ok(Aggregate {
+                code: code.to_string(),
+                normalize_errors,
+                state_field,
+                output_field,
+                mode,
+                create_state: get("create_state")?,
+                accumulate: get("accumulate")?,
+                retract: get("retract").ok(),
+                finish: get("finish").ok(),
+                merge: get("merge").ok(),
+            })
+        })
+    }
+
+    /// Reset the global state of all functions to right after they were added.
+    ///
+    /// The properties added to `globalThis` since the runtime was created are deleted, and the
+    /// code of every function and aggregate is evaluated again, so that the top-level variables
+    /// of their modules are initialized again.
+    ///
+    /// Scalar functions should not keep state between calls. Calling this method between batches
+    /// in tests helps to catch functions whose results depend on previous calls. It is a
+    /// correctness aid, not a sandbox: modified built-in objects, e.g. `Array.prototype`, and
+    /// non-configurable properties are not restored, and the modules are evaluated again in no
+    /// particular order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use arrow_udf_js::{CallMode, Runtime};
+    /// # use arrow_array::Int32Array;
+    /// # use arrow_schema::DataType;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function(
+    ///         "counter",
+    ///         DataType::Int32,
+    ///         CallMode::CalledOnNullInput,
+    ///         "let count = 0; export function counter() { return ++count; }",
+    ///     )
+    ///     .unwrap();
+    /// let output = runtime.call_scalar("counter", &[]).unwrap();
+    /// assert_eq!(&*output, &Int32Array::from(vec![1]));
+    ///
+    /// runtime.reset_globals().unwrap();
+    /// let output = runtime.call_scalar("counter", &[]).unwrap();
+    /// assert_eq!(&*output, &Int32Array::from(vec![1]));
+    /// ```
+    pub fn reset_globals(&mut self) -> Result<()> {
+        self.context.with(|ctx| {
+            let delete_globals = self.delete_globals.clone().restore(&ctx)?;
+            delete_globals
+                .call::<_, ()>(())
+                .map_err(|e| check_exception(e, &ctx))
+                .context("failed to delete globals")
+        })?;
+        let mut functions = Vec::with_capacity(self.functions.len());
+        for (name, function) in &self.functions {
+            let js_function = self
+                .compile_source(name, &function.source, function.normalize_errors)
+                .with_context(|| format!("failed to reset function \"{name}\""))?;
+            functions.push((name.clone(), js_function));
+        }
+        let mut aggregates = Vec::with_capacity(self.aggregates.len());
+        for (name, aggregate) in &self.aggregates {
+            let aggregate = self
+                .compile_aggregate(
+                    name,
+                    aggregate.state_field.clone(),
+                    aggregate.output_field.clone(),
+                    aggregate.mode,
+                    &aggregate.code,
+                    aggregate.normalize_errors,
+                )
+                .with_context(|| format!("failed to reset aggregate \"{name}\""))?;
+            aggregates.push((name.clone(), aggregate));
+        }
+        for (name, js_function) in functions {
+            self.functions.get_mut(&name).unwrap().function = js_function;
+        }
+        self.aggregates.extend(aggregates);
+        Ok(())
+    }
+
+    /// Call a scalar function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    #[doc = include_str!("doc_create_function.txt")]
+    /// // suppose we have created a scalar function `gcd`
+    /// // see the example in `add_function`
+    ///
+    /// let schema = Schema::new(vec![
+    ///     Field::new("x", DataType::Int32, true),
+    ///     Field::new("y", DataType::Int32, true),
+    /// ]);
+    /// let arg0 = Int32Array::from(vec![Some(25), None]);
+    /// let arg1 = Int32Array::from(vec![Some(15), None]);
+    /// let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    ///
+    /// let output = runtime.call("gcd", &input).unwrap();
+    /// assert_eq!(&**output.column(0), &Int32Array::from(vec![Some(5), None]));
+    /// ```
+    pub fn call(&self, name: &str, input: &RecordBatch) -> Result<RecordBatch> {
+        self.call_impl(name, input, 0..input.num_rows(), None)
+    }
+
+    /// Call a scalar function on the rows `offset..offset + len` of the input.
+    ///
+    /// This is equivalent to calling [`call`] on `input.slice(offset, len)`, but without slicing
+    /// the arrays. The output has `len` rows. Row indexes in error messages refer to the input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    #[doc = include_str!("doc_create_function.txt")]
+    /// // suppose we have created a scalar function `gcd`
+    /// // see the example in `add_function`
+    ///
+    /// let schema = Schema::new(vec![
+    ///     Field::new("x", DataType::Int32, true),
+    ///     Field::new("y", DataType::Int32, true),
+    /// ]);
+    /// let arg0 = Int32Array::from(vec![Some(25), Some(12), None]);
+    /// let arg1 = Int32Array::from(vec![Some(15), Some(18), None]);
+    /// let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    ///
+    /// let output = runtime.call_range("gcd", &input, 1, 2).unwrap();
+    /// assert_eq!(&**output.column(0), &Int32Array::from(vec![Some(6), None]));
+    /// ```
+    ///
+    /// [`call`]: Runtime::call
+    pub fn call_range(
+        &self,
+        name: &str,
+        input: &RecordBatch,
+        offset: usize,
+        len: usize,
+    ) -> Result<RecordBatch> {
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= input.num_rows());
+        let Some(end) = end else {
+            bail!(
+                "range {offset}..{} is out of bounds for {} rows",
+                offset.saturating_add(len),
+                input.num_rows()
+            );
+        };
+        self.call_impl(name, input, offset..end, None)
+    }
+
+    /// Call a scalar function, which can be cancelled by setting `token` from another thread.
+    ///
+    /// If `token` is set before or during the call, the call is aborted and returns a
+    /// [`Cancelled`] error. The runtime can still be used after the call is cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    #[doc = include_str!("doc_create_function.txt")]
+    /// # use std::sync::atomic::AtomicBool;
+    /// // suppose we have created a scalar function `gcd`
+    /// // see the example in `add_function`
+    ///
+    /// let schema = Schema::new(vec![
+    ///     Field::new("x", DataType::Int32, true),
+    ///     Field::new("y", DataType::Int32, true),
+    /// ]);
+    /// let arg0 = Int32Array::from(vec![Some(25), None]);
+    /// let arg1 = Int32Array::from(vec![Some(15), None]);
+    /// let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    ///
+    /// let token = AtomicBool::new(false);
+    /// let output = runtime.call_with_cancel("gcd", &input, &token).unwrap();
+    /// assert_eq!(&**output.column(0), &Int32Array::from(vec![Some(5), None]));
+    /// ```
+    pub fn call_with_cancel(
+        &self,
+        name: &str,
+        input: &RecordBatch,
+        token: &AtomicBool,
+    ) -> Result<RecordBatch> {
+        self.call_impl(name, input, 0..input.num_rows(), Some(token))
+    }
+
+    /// Call a scalar function with the input columns bound to arguments by field name.
+    ///
+    /// The function must be added with [`FunctionOptions::arg_names`]. The columns named after
+    /// the arguments are passed in the order of the arguments, and other columns are ignored.
+    /// Returns an error if any of the columns is missing.
     ///
     /// # Example
     ///
     /// ```
-    #[doc = include_str!("doc_create_function.txt")]
-    /// // suppose we have created a scalar function `gcd`
-    /// // see the example in `add_function`
+    /// # use arrow_udf_js::{Runtime, CallMode, FunctionOptions};
+    /// # use arrow_array::{Int32Array, RecordBatch};
+    /// # use arrow_schema::{DataType, Field, Schema};
+    /// # use std::sync::Arc;
+    /// let mut runtime = Runtime::new().unwrap();
+    /// runtime
+    ///     .add_function_with_options(
+    ///         "sub",
+    ///         DataType::Int32,
+    ///         CallMode::ReturnNullOnNullInput,
+    ///         "export function sub(x, y) { return x - y; }",
+    ///         FunctionOptions::default().arg_names(vec!["x".into(), "y".into()]),
+    ///     )
+    ///     .unwrap();
     ///
     /// let schema = Schema::new(vec![
-    ///     Field::new("x", DataType::Int32, true),
     ///     Field::new("y", DataType::Int32, true),
+    ///     Field::new("x", DataType::Int32, true),
     /// ]);
-    /// let arg0 = Int32Array::from(vec![Some(25), None]);
-    /// let arg1 = Int32Array::from(vec![Some(15), None]);
+    /// let arg0 = Int32Array::from(vec![1]);
+    /// let arg1 = Int32Array::from(vec![10]);
     /// let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
     ///
-    /// let output = runtime.call("gcd", &input).unwrap();
-    /// assert_eq!(&**output.column(0), &Int32Array::from(vec![Some(5), None]));
+    /// let output = runtime.call_by_name("sub", &input).unwrap();
+    /// assert_eq!(&**output.column(0), &Int32Array::from(vec![9]));
     /// ```
-    pub fn call(&self, name: &str, input: &RecordBatch) -> Result<RecordBatch> {
+    pub fn call_by_name(&self, name: &str, input: &RecordBatch) -> Result<RecordBatch> {
         let function = self.functions.get(name).context("function not found")?;
+        let Some(arg_names) = &function.arg_names else {
+            bail!("function \"{name}\" has no argument names");
+        };
+        let schema = input.schema();
+        let indices = arg_names
+            .iter()
+            .map(|arg| {
+                schema
+                    .index_of(arg)
+                    .map_err(|_| anyhow!("missing column \"{arg}\" for function \"{name}\""))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.call(name, &input.project(&indices)?)
+    }
+
+    fn call_impl(
+        &self,
+        name: &str,
+        input: &RecordBatch,
+        rows: Range<usize>,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<RecordBatch> {
+        let function = self.functions.get(name).context("function not found")?;
+        if function.stateful_mode {
+            bail!("functions in stateful mode can only be called as table functions");
+        }
+        function.check_input(input)?;
+        let is_cancelled = || cancel.is_some_and(|token| token.load(Ordering::Relaxed));
         // convert each row to python objects and call the function
         self.context.with(|ctx| {
             let js_function = function.function.clone().restore(&ctx)?;
-            let mut results = Vec::with_capacity(input.num_rows());
-            let mut row = Vec::with_capacity(input.num_columns());
-            for i in 0..input.num_rows() {
-                row.clear();
-                for (column, field) in input.columns().iter().zip(input.schema().fields()) {
-                    let val = self
-                        .converter
-                        .get_jsvalue(&ctx, field, column, i)
-                        .context("failed to get jsvalue from arrow array")?;
-
-                    row.push(val);
+            if function.column_mode {
+                // the readers access the whole arrays, so the range is sliced here
+                let input = &input.slice(rows.start, rows.len());
+                let results = self.call_column_mode(&ctx, function, &js_function, input, cancel)?;
+                return self.build_output(&ctx, function, results, rows.start);
+            }
+            if function.typed_array_mode {
+                let input = &input.slice(rows.start, rows.len());
+                return self.call_typed_array_mode(&ctx, function, &js_function, input, cancel);
+            }
+            // rows with any null argument return null without converting the arguments
+            let null_rows = match function.mode {
+                CallMode::ReturnNullOnNullInput => null_rows(input),
+                CallMode::CalledOnNullInput => None,
+            };
+            let is_null_row = |i| null_rows.as_ref().is_some_and(|nulls| nulls.is_null(i));
+            // convert the arguments column by column if they are all converted anyway
+            let within_limit = self
+                .max_converted_values
+                .map_or(true, |limit| rows.len() * input.num_columns() <= limit);
+            let columns = match !function.object_mode && within_limit {
+                true => Some(self.columns_to_js(&ctx, input, rows.clone(), null_rows.as_ref())?),
+                false => None,
+            };
+            // primitive return values are appended to the builder directly
+            let mut builder = self.converter.primitive_builder(
+                &ctx,
+                function.return_field.data_type(),
+                rows.len(),
+            )?;
+            let mut results = match builder {
+                Some(_) => vec![],
+                None => Vec::with_capacity(rows.len()),
+            };
+            let mut push = |result, i| match &mut builder {
+                Some(builder) => builder
+                    .append(result, i)
+                    .context("failed to build arrow array from return values"),
+                None => {
+                    results.push(result);
+                    Ok(())
                 }
-                if function.mode == CallMode::ReturnNullOnNullInput
-                    && row.iter().any(|v| v.is_null())
-                {
-                    results.push(Value::new_null(ctx.clone()));
+            };
+            for i in rows.clone() {
+                if is_cancelled() {
+                    return Err(Cancelled.into());
+                }
+                if let Some(limit) = self.max_converted_values {
+                    let converted = (i - rows.start + 1) * input.num_columns();
+                    if converted > limit {
+                        bail!(
+                            "failed to convert row {i}: the number of converted JS values exceeds the limit of {limit}"
+                        );
+                    }
+                }
+                if is_null_row(i) {
+                    push(Value::new_null(ctx.clone()), i)?;
                     continue;
                 }
-                let mut args = Args::new(ctx.clone(), row.len());
-                args.push_args(row.drain(..))?;
-                let result = self
-                    .call_user_fn(&ctx, &js_function, args)
-                    .context("failed to call function")?;
-                results.push(result);
+                let args = match &columns {
+                    Some(columns) => {
+                        self.get_args_from_columns(&ctx, function, columns, i - rows.start)?
+                    }
+                    None => self.get_args(&ctx, function, input, null_rows.as_ref(), i)?,
+                };
+                let Some(args) = args else {
+                    push(Value::new_null(ctx.clone()), i)?;
+                    continue;
+                };
+                if let Some(token) = cancel {
+                    self.cancel
+                        .store(token as *const AtomicBool as *mut _, Ordering::Relaxed);
+                }
+                let result = self.call_user_fn(&ctx, &js_function, args);
+                self.cancel.store(std::ptr::null_mut(), Ordering::Relaxed);
+                let result = match result {
+                    Err(_) if is_cancelled() => return Err(Cancelled.into()),
+                    result => result.with_context(|| call_error(input, i))?,
+                };
+                push(result, i)?;
+            }
+            if let Some(mut builder) = builder {
+                let schema = Schema::new(vec![function.return_field.clone()]);
+                return Ok(RecordBatch::try_new(Arc::new(schema), vec![builder.finish()])?);
             }
+            self.build_output(&ctx, function, results, rows.start)
+        })
+    }
 
-            let array = self
+    /// Call a scalar function once with a reader object for each input column.
+    ///
+    /// Returns the value for each row.
+    fn call_column_mode<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        js_function: &rquickjs::Function<'js>,
+        input: &RecordBatch,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Vec<Value<'js>>> {
+        let is_cancelled = || cancel.is_some_and(|token| token.load(Ordering::Relaxed));
+        if is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        let mut args = Args::new(ctx.clone(), input.num_columns());
+        for (column, field) in input.columns().iter().zip(input.schema().fields()) {
+            let reader = self
                 .converter
-                .build_array(&function.return_field, &ctx, results)
-                .context("failed to build arrow array from return values")?;
-            let schema = Schema::new(vec![function.return_field.clone()]);
-            Ok(RecordBatch::try_new(Arc::new(schema), vec![array])?)
-        })
+                .get_jsreader(ctx, field, column)
+                .context("failed to create reader of arrow array")?;
+            args.push_arg(reader)?;
+        }
+        if let Some(token) = cancel {
+            self.cancel
+                .store(token as *const AtomicBool as *mut _, Ordering::Relaxed);
+        }
+        let result: Result<Value> = self.call_user_fn(ctx, js_function, args);
+        self.cancel.store(std::ptr::null_mut(), Ordering::Relaxed);
+        let result = match result {
+            Err(_) if is_cancelled() => return Err(Cancelled.into()),
+            result => result.context("failed to call function")?,
+        };
+        let Some(array) = result.as_array() else {
+            bail!(
+                "expected an array of return values, got {}",
+                result.type_of().as_str()
+            );
+        };
+        if array.len() != input.num_rows() {
+            bail!(
+                "expected {} return values, got {}",
+                input.num_rows(),
+                array.len()
+            );
+        }
+        let mut results = array.iter().collect::<rquickjs::Result<Vec<Value>>>()?;
+        let null_rows = match function.mode {
+            CallMode::ReturnNullOnNullInput => null_rows(input),
+            CallMode::CalledOnNullInput => None,
+        };
+        if let Some(nulls) = null_rows {
+            for (result, valid) in results.iter_mut().zip(nulls.iter()) {
+                if !valid {
+                    *result = Value::new_null(ctx.clone());
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Call a function in typed array mode on the whole input and build the output batch.
+    fn call_typed_array_mode<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        js_function: &rquickjs::Function<'js>,
+        input: &RecordBatch,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<RecordBatch> {
+        let is_cancelled = || cancel.is_some_and(|token| token.load(Ordering::Relaxed));
+        if is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        let columns = Object::new(ctx.clone())?;
+        for (column, field) in input.columns().iter().zip(input.schema().fields()) {
+            let Some(array) = self
+                .converter
+                .get_typed_array(ctx, column.as_ref())
+                .context("failed to create typed array of arrow array")?
+            else {
+                bail!(
+                    "typed array mode only supports numeric columns, got {} for column \"{}\"",
+                    column.data_type(),
+                    field.name()
+                );
+            };
+            columns.set(field.name().as_str(), array)?;
+        }
+        let mut args = Args::new(ctx.clone(), 2);
+        args.push_arg(columns)?;
+        args.push_arg(input.num_rows())?;
+        if let Some(token) = cancel {
+            self.cancel
+                .store(token as *const AtomicBool as *mut _, Ordering::Relaxed);
+        }
+        let result: Result<Value> = self.call_user_fn(ctx, js_function, args);
+        self.cancel.store(std::ptr::null_mut(), Ordering::Relaxed);
+        let result = match result {
+            Err(_) if is_cancelled() => return Err(Cancelled.into()),
+            result => result.context("failed to call function")?,
+        };
+        let check_len = |len: usize| {
+            if len != input.num_rows() {
+                bail!("expected {} return values, got {len}", input.num_rows());
+            }
+            Ok(())
+        };
+        // rows with any null argument return null
+        let nulls = null_rows(input);
+        let array =
+            match self
+                .converter
+                .build_from_typed_array(&function.return_field, ctx, &result)
+            {
+                Some(array) => {
+                    check_len(array.len())?;
+                    match nulls {
+                        Some(nulls) => {
+                            make_array(array.to_data().into_builder().nulls(Some(nulls)).build()?)
+                        }
+                        None => array,
+                    }
+                }
+                None => {
+                    let Some(array) = result.as_array() else {
+                        bail!(
+                            "expected a typed array or an array of return values, got {}",
+                            result.type_of().as_str()
+                        );
+                    };
+                    check_len(array.len())?;
+                    let mut results = array.iter().collect::<rquickjs::Result<Vec<Value>>>()?;
+                    if let Some(nulls) = &nulls {
+                        for (result, valid) in results.iter_mut().zip(nulls.iter()) {
+                            if !valid {
+                                *result = Value::new_null(ctx.clone());
+                            }
+                        }
+                    }
+                    self.converter
+                        .build_array(&function.return_field, ctx, results)
+                        .context("failed to build arrow array from return values")?
+                }
+            };
+        let schema = Schema::new(vec![function.return_field.clone()]);
+        Ok(RecordBatch::try_new(Arc::new(schema), vec![array])?)
+    }
+
+    /// Build the output batch of a scalar function from the return values.
+    fn build_output<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        results: Vec<Value<'js>>,
+        offset: usize,
+    ) -> Result<RecordBatch> {
+        if function.multi_column {
+            return self.build_columns(ctx, function, results, offset);
+        }
+        let array = self
+            .converter
+            .build_array(&function.return_field, ctx, results)
+            .context("failed to build arrow array from return values")?;
+        let schema = Schema::new(vec![function.return_field.clone()]);
+        Ok(RecordBatch::try_new(Arc::new(schema), vec![array])?)
+    }
+
+    /// Build the output columns of a multi-column function from the arrays returned for each row.
+    ///
+    /// `offset` is the index of the first row in the input.
+    fn build_columns<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        results: Vec<Value<'js>>,
+        offset: usize,
+    ) -> Result<RecordBatch> {
+        let DataType::Struct(fields) = function.return_field.data_type() else {
+            unreachable!("checked when the function is added");
+        };
+        let mut columns = vec![Vec::with_capacity(results.len()); fields.len()];
+        for (i, result) in results.into_iter().enumerate() {
+            if result.is_null() || result.is_undefined() {
+                for column in &mut columns {
+                    column.push(Value::new_null(ctx.clone()));
+                }
+                continue;
+            }
+            let row = offset + i;
+            let Some(values) = result.as_array() else {
+                bail!(
+                    "expected array at row {row}, got {}",
+                    result.type_of().as_str()
+                );
+            };
+            if values.len() != fields.len() {
+                bail!(
+                    "expected {} values at row {row}, got {}",
+                    fields.len(),
+                    values.len()
+                );
+            }
+            for (j, column) in columns.iter_mut().enumerate() {
+                column.push(values.get(j)?);
+            }
+        }
+        let arrays = fields
+            .iter()
+            .zip(columns)
+            .map(|(field, values)| self.converter.build_array(field, ctx, values))
+            .collect::<Result<Vec<_>>>()
+            .context("failed to build arrow array from return values")?;
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(fields.clone())),
+            arrays,
+        )?)
+    }
+
+    /// Call a scalar function with a single value for each argument.
+    ///
+    /// Each argument is an array of length 1. They are wrapped into a 1-row batch with fields named
+    /// `arg0`, `arg1`, etc. and passed to [`call`]. Returns the result as an array of length 1.
+    ///
+    /// Since the arrays carry no field metadata, use [`call`] for extension types such as JSON or decimal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    #[doc = include_str!("doc_create_function.txt")]
+    /// // suppose we have created a scalar function `gcd`
+    /// // see the example in `add_function`
+    ///
+    /// let output = runtime
+    ///     .call_scalar("gcd", &[Arc::new(Int32Array::from(vec![25])), Arc::new(Int32Array::from(vec![15]))])
+    ///     .unwrap();
+    /// assert_eq!(&*output, &Int32Array::from(vec![5]));
+    /// ```
+    ///
+    /// [`call`]: Runtime::call
+    pub fn call_scalar(&self, name: &str, args: &[ArrayRef]) -> Result<ArrayRef> {
+        let mut fields = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            if arg.len() != 1 {
+                bail!("argument {i} expected 1 value, got {}", arg.len());
+            }
+            fields.push(Field::new(format!("arg{i}"), arg.data_type().clone(), true));
+        }
+        let input = RecordBatch::try_new_with_options(
+            Arc::new(Schema::new(fields)),
+            args.to_vec(),
+            &RecordBatchOptions::default().with_row_count(Some(1)),
+        )?;
+        let output = self.call(name, &input)?;
+        if output.num_columns() != 1 {
+            bail!("multi-column functions can not be called by `call_scalar`, use `call` instead");
+        }
+        Ok(output.column(0).clone())
     }
 
     /// Call a table function.
@@ -480,13 +2107,88 @@ impl Runtime {
         name: &'a str,
         input: &'a RecordBatch,
         chunk_size: usize,
+    ) -> Result<RecordBatchIter<'a>> {
+        self.table_function_iter(name, input.clone(), chunk_size)
+    }
+
+    /// Call a scalar function on each batch of a stream.
+    ///
+    /// The batches are evaluated lazily as the returned iterator is advanced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    #[doc = include_str!("doc_create_function.txt")]
+    /// // suppose we have created a scalar function `gcd`
+    /// // see the example in `add_function`
+    ///
+    /// let schema = Arc::new(Schema::new(vec![
+    ///     Field::new("x", DataType::Int32, true),
+    ///     Field::new("y", DataType::Int32, true),
+    /// ]));
+    /// let batches = (1..=3).map(|i| {
+    ///     let arg0 = Int32Array::from(vec![i * 10]);
+    ///     let arg1 = Int32Array::from(vec![15]);
+    ///     RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap()
+    /// });
+    ///
+    /// for output in runtime.call_stream("gcd", batches) {
+    ///     let output = output.unwrap();
+    ///     assert_eq!(output.num_rows(), 1);
+    /// }
+    /// ```
+    pub fn call_stream<'a>(
+        &'a self,
+        name: &'a str,
+        inputs: impl IntoIterator<Item = RecordBatch> + 'a,
+    ) -> impl Iterator<Item = Result<RecordBatch>> + 'a {
+        inputs.into_iter().map(move |input| self.call(name, &input))
+    }
+
+    /// Call a table function on each batch of a stream.
+    ///
+    /// The outputs of all batches are flattened into one iterator. The `row` column is the index
+    /// of the row in its input batch. Each batch is evaluated lazily as the returned iterator is
+    /// advanced.
+    pub fn call_table_function_stream<'a>(
+        &'a self,
+        name: &'a str,
+        inputs: impl IntoIterator<Item = RecordBatch> + 'a,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<RecordBatch>> + 'a {
+        inputs.into_iter().flat_map(move |input| {
+            let outputs: Box<dyn Iterator<Item = Result<RecordBatch>> + 'a> =
+                match self.table_function_iter(name, input, chunk_size) {
+                    Ok(iter) => Box::new(iter),
+                    Err(e) => Box::new(std::iter::once(Err(e))),
+                };
+            outputs
+        })
+    }
+
+    fn table_function_iter<'a>(
+        &'a self,
+        name: &str,
+        input: RecordBatch,
+        chunk_size: usize,
     ) -> Result<RecordBatchIter<'a>> {
         assert!(chunk_size > 0);
         let function = self.functions.get(name).context("function not found")?;
+        if function.column_mode {
+            bail!("column mode is not supported for table functions");
+        }
+        if function.typed_array_mode {
+            bail!("typed array mode is not supported for table functions");
+        }
+        if function.multi_column {
+            bail!("multi-column output is not supported for table functions");
+        }
+        function.check_input(&input)?;
 
         // initial state
         Ok(RecordBatchIter {
             rt: self,
+            null_rows: null_rows(&input),
             input,
             function,
             schema: Arc::new(Schema::new(vec![
@@ -496,6 +2198,7 @@ impl Runtime {
             chunk_size,
             row: 0,
             generator: None,
+            state: None,
             converter: &self.converter,
         })
     }
@@ -551,11 +2254,13 @@ impl Runtime {
                 .converter
                 .get_jsvalue(&ctx, &aggregate.state_field, state, 0)?;
 
+            let null_rows = match aggregate.mode {
+                CallMode::ReturnNullOnNullInput => null_rows(input),
+                CallMode::CalledOnNullInput => None,
+            };
             let mut row = Vec::with_capacity(1 + input.num_columns());
             for i in 0..input.num_rows() {
-                if aggregate.mode == CallMode::ReturnNullOnNullInput
-                    && input.columns().iter().any(|column| column.is_null(i))
-                {
+                if null_rows.as_ref().is_some_and(|nulls| nulls.is_null(i)) {
                     continue;
                 }
                 row.clear();
@@ -568,7 +2273,12 @@ impl Runtime {
                 args.push_args(row.drain(..))?;
                 state = self
                     .call_user_fn(&ctx, &accumulate, args)
-                    .context("failed to call accumulate")?;
+                    .with_context(|| {
+                        format!(
+                            "failed to call accumulate at row {i} with arguments {}",
+                            format_row(input, i)
+                        )
+                    })?;
             }
             let output = self
                 .converter
@@ -617,11 +2327,13 @@ impl Runtime {
                 .converter
                 .get_jsvalue(&ctx, &aggregate.state_field, state, 0)?;
 
+            let null_rows = match aggregate.mode {
+                CallMode::ReturnNullOnNullInput => null_rows(input),
+                CallMode::CalledOnNullInput => None,
+            };
             let mut row = Vec::with_capacity(1 + input.num_columns());
             for i in 0..input.num_rows() {
-                if aggregate.mode == CallMode::ReturnNullOnNullInput
-                    && input.columns().iter().any(|column| column.is_null(i))
-                {
+                if null_rows.as_ref().is_some_and(|nulls| nulls.is_null(i)) {
                     continue;
                 }
                 row.clear();
@@ -637,9 +2349,12 @@ impl Runtime {
                 };
                 let mut args = Args::new(ctx.clone(), row.len());
                 args.push_args(row.drain(..))?;
-                state = self
-                    .call_user_fn(&ctx, func, args)
-                    .context("failed to call accumulate or retract")?;
+                state = self.call_user_fn(&ctx, func, args).with_context(|| {
+                    format!(
+                        "failed to call accumulate or retract at row {i} with arguments {}",
+                        format_row(input, i)
+                    )
+                })?;
             }
             let output = self
                 .converter
@@ -670,8 +2385,11 @@ impl Runtime {
             let mut state = self
                 .converter
                 .get_jsvalue(&ctx, &aggregate.state_field, states, 0)?;
+            let null_states = states.logical_nulls();
             for i in 1..states.len() {
-                if aggregate.mode == CallMode::ReturnNullOnNullInput && states.is_null(i) {
+                if aggregate.mode == CallMode::ReturnNullOnNullInput
+                    && null_states.as_ref().is_some_and(|nulls| nulls.is_null(i))
+                {
                     continue;
                 }
                 let state2 = self
@@ -711,8 +2429,11 @@ impl Runtime {
         let output = self.context.with(|ctx| {
             let finish = finish.clone().restore(&ctx)?;
             let mut results = Vec::with_capacity(states.len());
+            let null_states = states.logical_nulls();
             for i in 0..states.len() {
-                if aggregate.mode == CallMode::ReturnNullOnNullInput && states.is_null(i) {
+                if aggregate.mode == CallMode::ReturnNullOnNullInput
+                    && null_states.as_ref().is_some_and(|nulls| nulls.is_null(i))
+                {
                     results.push(Value::new_null(ctx.clone()));
                     continue;
                 }
@@ -734,6 +2455,94 @@ impl Runtime {
         Ok(output)
     }
 
+    /// Convert the `i`-th row of `input` to the arguments of `function`.
+    ///
+    /// `null_rows` are the rows of `input` with a null argument, as returned by [`null_rows`].
+    /// Returns `None` if the function should not be called because of null input.
+    fn get_args<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        input: &RecordBatch,
+        null_rows: Option<&NullBuffer>,
+        i: usize,
+    ) -> Result<Option<Args<'js>>> {
+        if function.object_mode {
+            if function.mode == CallMode::ReturnNullOnNullInput
+                && null_rows.is_some_and(|nulls| nulls.is_null(i))
+            {
+                return Ok(None);
+            }
+            let object = self
+                .converter
+                .get_jsobject(ctx, input.schema().fields(), input.columns(), i)
+                .context("failed to get jsvalue from arrow array")?;
+            let mut args = Args::new(ctx.clone(), 1);
+            args.push_args([object])?;
+            return Ok(Some(args));
+        }
+        // `Args` is consumed by each call, so it can not be reused across rows.
+        // Instead, push values into a pre-sized `Args` directly without an intermediate buffer.
+        let mut args = Args::new(ctx.clone(), input.num_columns());
+        let mut has_null = false;
+        for (column, field) in input.columns().iter().zip(input.schema().fields()) {
+            let val = self
+                .converter
+                .get_jsvalue(ctx, field, column, i)
+                .context("failed to get jsvalue from arrow array")?;
+            has_null |= val.is_null();
+            args.push_arg(val)?;
+        }
+        if function.mode == CallMode::ReturnNullOnNullInput && has_null {
+            return Ok(None);
+        }
+        Ok(Some(args))
+    }
+
+    /// Convert the `rows` of all columns of `input` to JS values.
+    ///
+    /// Rows that are null in `skip` are not converted.
+    fn columns_to_js<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        input: &RecordBatch,
+        rows: Range<usize>,
+        skip: Option<&NullBuffer>,
+    ) -> Result<Vec<Vec<Value<'js>>>> {
+        input
+            .columns()
+            .iter()
+            .zip(input.schema().fields())
+            .map(|(column, field)| {
+                self.converter
+                    .column_to_js(ctx, field, column, rows.clone(), skip)
+                    .context("failed to get jsvalue from arrow array")
+            })
+            .collect()
+    }
+
+    /// Collect the `i`-th row of the converted `columns` to the arguments of `function`.
+    ///
+    /// Returns `None` if the function should not be called because of null input.
+    fn get_args_from_columns<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        function: &Function,
+        columns: &[Vec<Value<'js>>],
+        i: usize,
+    ) -> Result<Option<Args<'js>>> {
+        if function.mode == CallMode::ReturnNullOnNullInput
+            && columns.iter().any(|column| column[i].is_null())
+        {
+            return Ok(None);
+        }
+        let mut args = Args::new(ctx.clone(), columns.len());
+        for column in columns {
+            args.push_arg(column[i].clone())?;
+        }
+        Ok(Some(args))
+    }
+
     /// Call a user function.
     ///
     /// If `timeout` is set, the function will be interrupted after the timeout.
@@ -759,7 +2568,9 @@ impl Runtime {
 /// An iterator over the result of a table function.
 pub struct RecordBatchIter<'a> {
     rt: &'a Runtime,
-    input: &'a RecordBatch,
+    input: RecordBatch,
+    /// Rows of `input` with a null argument.
+    null_rows: Option<NullBuffer>,
     function: &'a Function,
     schema: SchemaRef,
     chunk_size: usize,
@@ -768,6 +2579,8 @@ pub struct RecordBatchIter<'a> {
     row: usize,
     /// Generator of the current row.
     generator: Option<Persistent<Object<'static>>>,
+    /// Generator of the whole batch in stateful mode.
+    state: Option<Persistent<Object<'static>>>,
     converter: &'a jsarrow::Converter,
 }
 
@@ -788,7 +2601,6 @@ impl RecordBatchIter<'_> {
             let js_function = self.function.function.clone().restore(&ctx)?;
             let mut indexes = Int32Builder::with_capacity(self.chunk_size);
             let mut results = Vec::with_capacity(self.input.num_rows());
-            let mut row = Vec::with_capacity(self.input.num_columns());
             // restore generator from state
             let mut generator = match self.generator.take() {
                 Some(generator) => {
@@ -799,33 +2611,61 @@ impl RecordBatchIter<'_> {
                 }
                 None => None,
             };
+            // restore or start the generator of the whole batch in stateful mode
+            let state = match self.state.take() {
+                Some(state) => Some(state.restore(&ctx)?),
+                None if self.function.stateful_mode && self.row == 0 => {
+                    let state = self.start_state(&ctx, &js_function)?;
+                    if state.is_none() {
+                        self.row = self.input.num_rows();
+                    }
+                    state
+                }
+                None => None,
+            };
+            let feed: Option<rquickjs::Function> = match state {
+                Some(_) => Some(
+                    ctx.eval(FEED_GENERATOR)
+                        .map_err(|e| check_exception(e, &ctx))?,
+                ),
+                None => None,
+            };
             while self.row < self.input.num_rows() && results.len() < self.chunk_size {
                 let (gen, next) = if let Some(g) = generator.as_ref() {
                     g
                 } else {
-                    // call the table function to get a generator
-                    row.clear();
-                    for (column, field) in
-                        (self.input.columns().iter()).zip(self.input.schema().fields())
-                    {
-                        let val = self
-                            .converter
-                            .get_jsvalue(&ctx, field, column, self.row)
-                            .context("failed to get jsvalue from arrow array")?;
-                        row.push(val);
-                    }
-                    if self.function.mode == CallMode::ReturnNullOnNullInput
-                        && row.iter().any(|v| v.is_null())
-                    {
+                    let Some(mut args) = self.rt.get_args(
+                        &ctx,
+                        self.function,
+                        &self.input,
+                        self.null_rows.as_ref(),
+                        self.row,
+                    )?
+                    else {
                         self.row += 1;
                         continue;
-                    }
-                    let mut args = Args::new(ctx.clone(), row.len());
-                    args.push_args(row.drain(..))?;
-                    let gen: Object = self
-                        .rt
-                        .call_user_fn(&ctx, &js_function, args)
-                        .context("failed to call function")?;
+                    };
+                    let gen: Object = match (&state, &feed) {
+                        // send the row to the generator of the batch to get the iterator of its values
+                        (Some(batch_gen), Some(feed)) => {
+                            args.this(batch_gen.clone())?;
+                            let iter: Option<Object> = self
+                                .rt
+                                .call_user_fn(&ctx, feed, args)
+                                .with_context(|| call_error(&self.input, self.row))?;
+                            let Some(iter) = iter else {
+                                // the generator has returned, so the remaining rows have no output
+                                self.row = self.input.num_rows();
+                                break;
+                            };
+                            iter
+                        }
+                        // call the table function to get a generator
+                        _ => self
+                            .rt
+                            .call_user_fn(&ctx, &js_function, args)
+                            .with_context(|| call_error(&self.input, self.row))?,
+                    };
                     let next: rquickjs::Function =
                         gen.get("next").context("failed to get 'next' method")?;
                     let mut args = Args::new(ctx.clone(), 0);
@@ -849,6 +2689,10 @@ impl RecordBatchIter<'_> {
                 results.push(value);
             }
             self.generator = generator.map(|(gen, _)| Persistent::save(&ctx, gen));
+            // the generator of the batch is dropped after all rows are sent
+            self.state = state
+                .filter(|_| self.row < self.input.num_rows())
+                .map(|state| Persistent::save(&ctx, state));
 
             if results.is_empty() {
                 return Ok(None);
@@ -866,6 +2710,31 @@ impl RecordBatchIter<'_> {
     }
 }
 
+impl RecordBatchIter<'_> {
+    /// Call the table function in stateful mode and run the generator to its first `yield`.
+    ///
+    /// Returns `None` if the generator returns without waiting for any row.
+    fn start_state<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        js_function: &rquickjs::Function<'js>,
+    ) -> Result<Option<Object<'js>>> {
+        let state: Object = self
+            .rt
+            .call_user_fn(ctx, js_function, Args::new(ctx.clone(), 0))
+            .context("failed to call the table function")?;
+        let next: rquickjs::Function = state.get("next").context("failed to get 'next' method")?;
+        let mut args = Args::new(ctx.clone(), 0);
+        args.this(state.clone())?;
+        let object: Object = self
+            .rt
+            .call_user_fn(ctx, &next, args)
+            .context("failed to call next")?;
+        let done: bool = object.get("done")?;
+        Ok((!done).then_some(state))
+    }
+}
+
 impl Iterator for RecordBatchIter<'_> {
     type Item = Result<RecordBatch>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -873,12 +2742,190 @@ impl Iterator for RecordBatchIter<'_> {
     }
 }
 
+/// A JS function that sends the arguments of a row to the generator of a table function in
+/// stateful mode, i.e. `this`. Returns an iterator over the values yielded for the row, or null if
+/// the generator has returned.
+const FEED_GENERATOR: &str = r#"
+(function (...args) {
+    const { value, done } = this.next(args);
+    if (done) {
+        return null;
+    }
+    return (value ?? [])[Symbol.iterator]();
+})
+"#;
+
+/// Returns a function deleting the properties added to `globalThis` after this code is evaluated.
+const DELETE_GLOBALS: &str = r#"
+(function () {
+    const names = new Set(Object.getOwnPropertyNames(globalThis));
+    return function () {
+        for (const name of Object.getOwnPropertyNames(globalThis)) {
+            if (!names.has(name)) {
+                delete globalThis[name];
+            }
+        }
+    };
+})()
+"#;
+
+/// A JS function that wraps a function to convert thrown values into `{ message, stack }`.
+const NORMALIZE_ERRORS_WRAPPER: &str = r#"
+(function (f) {
+    function normalize(e) {
+        if (e !== null && typeof e === "object" && "message" in e) {
+            return { message: String(e.message), stack: typeof e.stack === "string" ? e.stack : "" };
+        }
+        return { message: String(e), stack: "" };
+    }
+    return function (...args) {
+        let result;
+        try {
+            result = f.apply(this, args);
+        } catch (e) {
+            throw normalize(e);
+        }
+        // errors of generators are thrown on `next`
+        if (Object.prototype.toString.call(result) === "[object Generator]") {
+            const next = result.next;
+            result.next = function (...args) {
+                try {
+                    return next.apply(this, args);
+                } catch (e) {
+                    throw normalize(e);
+                }
+            };
+        }
+        return result;
+    };
+})
+"#;
+
+/// Get the compile error with its location from `ctx` if the error is an exception.
+///
+/// The message looks like `SyntaxError at gcd:5:12: unexpected token`.
+fn check_compile_exception(err: rquickjs::Error, ctx: &Ctx) -> anyhow::Error {
+    let rquickjs::Error::Exception = err else {
+        return err.into();
+    };
+    let exception = ctx.catch();
+    let Some(error) = exception.as_exception() else {
+        return anyhow!("exception generated by QuickJS: {:?}", exception);
+    };
+    let name = error
+        .get::<_, String>("name")
+        .unwrap_or_else(|_| "Error".into());
+    let message = error.message().unwrap_or_default();
+    // the first frame of the stack is the location of the error, e.g. "    at gcd:5:12"
+    let location = error.stack().and_then(|stack| {
+        stack
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("at ").map(str::to_string))
+    });
+    match location {
+        Some(location) => anyhow!("{name} at {location}: {message}"),
+        None => anyhow!("{name}: {message}"),
+    }
+}
+
 /// Get exception from `ctx` if the error is an exception.
 fn check_exception(err: rquickjs::Error, ctx: &Ctx) -> anyhow::Error {
     match err {
         rquickjs::Error::Exception => {
-            anyhow!("exception generated by QuickJS: {:?}", ctx.catch())
+            let exception = ctx.catch();
+            // a value normalized by `NORMALIZE_ERRORS_WRAPPER`
+            if let Some(object) = exception.as_object().filter(|_| !exception.is_error()) {
+                if let (Ok(message), Ok(stack)) = (
+                    object.get::<_, String>("message"),
+                    object.get::<_, String>("stack"),
+                ) {
+                    return if stack.is_empty() {
+                        anyhow!("exception generated by QuickJS: {message}")
+                    } else {
+                        anyhow!("exception generated by QuickJS: {message}\n{stack}")
+                    };
+                }
+            }
+            anyhow!("exception generated by QuickJS: {:?}", exception)
         }
         e => e.into(),
     }
 }
+
+/// Returns the warnings about a function that can be detected before it is called.
+///
+/// See [`Runtime::add_function_with_warnings`] for the checks.
+fn function_warnings(
+    name: &str,
+    function: &rquickjs::Function,
+    options: &FunctionOptions,
+) -> Vec<String> {
+    let mut warnings = vec![];
+    let constructor: Option<String> = function
+        .get::<_, Object>("constructor")
+        .and_then(|c| c.get("name"))
+        .ok();
+    if matches!(
+        constructor.as_deref(),
+        Some("AsyncFunction" | "AsyncGeneratorFunction")
+    ) {
+        warnings.push(format!(
+            "function \"{name}\" is async, so it returns promises instead of values"
+        ));
+    }
+    if let Some(arg_types) = options.arg_types.as_ref().filter(|_| !options.variadic) {
+        let num_args = if options.stateful_mode {
+            0
+        } else if options.object_mode {
+            1
+        } else {
+            arg_types.len()
+        };
+        let num_params = function.get::<_, u32>("length").unwrap_or(0) as usize;
+        if num_params > num_args {
+            warnings.push(format!(
+                "function \"{name}\" declares {num_params} parameters, but is called with {num_args} arguments"
+            ));
+        }
+    }
+    warnings
+}
+
+/// Returns the rows of `input` where any column is null, or `None` if there is no such row.
+fn null_rows(input: &RecordBatch) -> Option<NullBuffer> {
+    let nulls = input.columns().iter().fold(None, |acc, column| {
+        NullBuffer::union(acc.as_ref(), column.logical_nulls().as_ref())
+    });
+    nulls.filter(|nulls| nulls.null_count() > 0)
+}
+
+/// Returns the error context of a function call that failed at row `i`.
+fn call_error(input: &RecordBatch, i: usize) -> String {
+    format!(
+        "failed to call function at row {i} with arguments {}",
+        format_row(input, i)
+    )
+}
+
+/// Format the values of row `i` for error messages, e.g. `(1, abc, null)`.
+///
+/// Long values are truncated to keep the message readable.
+fn format_row(input: &RecordBatch, i: usize) -> String {
+    const MAX_VALUE_LEN: usize = 64;
+    let options = FormatOptions::default().with_null("null");
+    let values: Vec<String> = input
+        .columns()
+        .iter()
+        .map(|column| match ArrayFormatter::try_new(column, &options) {
+            Ok(formatter) => {
+                let value = formatter.value(i).to_string();
+                match value.char_indices().nth(MAX_VALUE_LEN) {
+                    Some((end, _)) => format!("{}...", &value[..end]),
+                    None => value,
+                }
+            }
+            Err(_) => "?".to_string(),
+        })
+        .collect();
+    format!("({})", values.join(", "))
+}
@@ -0,0 +1,218 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{CallMode, FunctionOptions, IntoField, Runtime, RuntimeBuilder};
+
+/// A pool of independent [`Runtime`]s with the same registered functions.
+///
+/// Each runtime can only be used by one thread at a time. Use [`get`] to borrow a runtime,
+/// so that multiple threads can evaluate functions in parallel.
+///
+/// # Example
+///
+/// ```
+/// # use arrow_udf_js::{CallMode, Runtime, RuntimePool};
+/// # use arrow_schema::DataType;
+/// let mut pool = RuntimePool::new(4, Runtime::builder()).unwrap();
+/// pool.add_function(
+///     "square",
+///     DataType::Int32,
+///     CallMode::ReturnNullOnNullInput,
+///     "export function square(x) { return x * x; }",
+/// )
+/// .unwrap();
+///
+/// std::thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             let _runtime = pool.get();
+///             // call functions on the runtime
+///         });
+///     }
+/// });
+/// ```
+///
+/// [`get`]: RuntimePool::get
+#[derive(Debug)]
+pub struct RuntimePool {
+    runtimes: Vec<Mutex<Runtime>>,
+    /// The index of the runtime to wait for if all runtimes are busy.
+    next: AtomicUsize,
+}
+
+/// A runtime borrowed from a [`RuntimePool`].
+///
+/// The runtime is returned to the pool when the guard is dropped.
+pub struct RuntimeGuard<'a> {
+    guard: MutexGuard<'a, Runtime>,
+}
+
+impl Deref for RuntimeGuard<'_> {
+    type Target = Runtime;
+
+    fn deref(&self) -> &Runtime {
+        &self.guard
+    }
+}
+
+impl RuntimePool {
+    /// Create a pool of `size` runtimes configured by `builder`.
+    pub fn new(size: usize, builder: RuntimeBuilder) -> Result<Self> {
+        if size == 0 {
+            bail!("the size of runtime pool must be positive");
+        }
+        let runtimes = (0..size)
+            .map(|_| Ok(Mutex::new(builder.clone().build()?)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            runtimes,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the number of runtimes in the pool.
+    pub fn size(&self) -> usize {
+        self.runtimes.len()
+    }
+
+    /// Borrow a runtime from the pool.
+    ///
+    /// Returns an idle runtime if any, otherwise blocks until one of the runtimes is available.
+    pub fn get(&self) -> RuntimeGuard<'_> {
+        for runtime in &self.runtimes {
+            if let Ok(guard) = runtime.try_lock() {
+                return RuntimeGuard { guard };
+            }
+        }
+        // all runtimes are busy, wait for them in turn
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.runtimes.len();
+        let guard = self.runtimes[i]
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        RuntimeGuard { guard }
+    }
+
+    /// Add a new scalar function or table function to all runtimes.
+    ///
+    /// See [`Runtime::add_function`]. If an error occurs, the function may have been added to
+    /// some of the runtimes.
+    pub fn add_function(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+    ) -> Result<()> {
+        self.add_function_with_options(name, return_type, mode, code, FunctionOptions::default())
+    }
+
+    /// Add a new scalar function or table function with options to all runtimes.
+    ///
+    /// See [`Runtime::add_function_with_options`].
+    pub fn add_function_with_options(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+        options: FunctionOptions,
+    ) -> Result<()> {
+        let return_field = return_type.into_field(name);
+        self.for_each(|runtime| {
+            runtime.add_function_with_options(
+                name,
+                return_field.clone(),
+                mode,
+                code,
+                options.clone(),
+            )
+        })
+    }
+
+    /// Add a new scalar function or table function from an expression to all runtimes.
+    ///
+    /// See [`Runtime::add_function_expr`].
+    pub fn add_function_expr(
+        &mut self,
+        name: &str,
+        return_type: impl IntoField,
+        mode: CallMode,
+        expr: &str,
+    ) -> Result<()> {
+        let return_field = return_type.into_field(name);
+        self.for_each(|runtime| runtime.add_function_expr(name, return_field.clone(), mode, expr))
+    }
+
+    /// Add many scalar functions or table functions to all runtimes.
+    ///
+    /// See [`Runtime::register_all`].
+    pub fn register_all<N, T, C>(
+        &mut self,
+        functions: impl IntoIterator<Item = (N, T, CallMode, C)>,
+    ) -> Result<()>
+    where
+        N: AsRef<str>,
+        T: IntoField,
+        C: AsRef<str>,
+    {
+        for (name, return_type, mode, code) in functions {
+            let name = name.as_ref();
+            self.add_function(name, return_type, mode, code.as_ref())
+                .with_context(|| format!("failed to add function \"{name}\""))?;
+        }
+        Ok(())
+    }
+
+    /// Add a new aggregate function to all runtimes.
+    ///
+    /// See [`Runtime::add_aggregate`].
+    pub fn add_aggregate(
+        &mut self,
+        name: &str,
+        state_type: impl IntoField,
+        output_type: impl IntoField,
+        mode: CallMode,
+        code: &str,
+    ) -> Result<()> {
+        let state_field = state_type.into_field(name);
+        let output_field = output_type.into_field(name);
+        self.for_each(|runtime| {
+            runtime.add_aggregate(name, state_field.clone(), output_field.clone(), mode, code)
+        })
+    }
+
+    /// Reset the global state of all runtimes.
+    ///
+    /// See [`Runtime::reset_globals`].
+    pub fn reset_globals(&mut self) -> Result<()> {
+        self.for_each(Runtime::reset_globals)
+    }
+
+    /// Apply `f` to each runtime in the pool.
+    fn for_each(&mut self, mut f: impl FnMut(&mut Runtime) -> Result<()>) -> Result<()> {
+        for runtime in &mut self.runtimes {
+            let runtime = runtime
+                .get_mut()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(runtime)?;
+        }
+        Ok(())
+    }
+}
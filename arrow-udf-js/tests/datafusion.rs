@@ -0,0 +1,169 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "datafusion")]
+
+use std::sync::Arc;
+
+use arrow_array::{Array, Int32Array, StringArray};
+use arrow_schema::DataType;
+use arrow_udf_js::datafusion::JsScalarUDF;
+use arrow_udf_js::{CallMode, FunctionOptions, Runtime, RuntimePool};
+use datafusion::prelude::SessionContext;
+use datafusion_common::ScalarValue;
+use datafusion_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, TypeSignature, Volatility};
+
+fn pool() -> Arc<RuntimePool> {
+    let mut pool = RuntimePool::new(2, Runtime::builder()).unwrap();
+    pool.add_function_with_options(
+        "gcd",
+        DataType::Int32,
+        CallMode::ReturnNullOnNullInput,
+        r#"
+            export function gcd(a, b) {
+                while (b != 0) {
+                    [a, b] = [b, a % b];
+                }
+                return a;
+            }
+            "#,
+        FunctionOptions::default().arg_types(vec![DataType::Int32, DataType::Int32]),
+    )
+    .unwrap();
+    pool.add_function_with_options(
+        "greet",
+        DataType::Utf8,
+        CallMode::ReturnNullOnNullInput,
+        "export function greet({ name }) { return `Hello, ${name}!`; }",
+        FunctionOptions::default()
+            .object_mode(true)
+            .arg_names(vec!["name".into()]),
+    )
+    .unwrap();
+    pool.add_function_expr(
+        "one",
+        DataType::Int32,
+        CallMode::CalledOnNullInput,
+        "() => 1",
+    )
+    .unwrap();
+    Arc::new(pool)
+}
+
+#[test]
+fn test_scalar_udf() {
+    let pool = pool();
+    let udf = JsScalarUDF::new(pool.clone(), "gcd", Volatility::Immutable).unwrap();
+    assert_eq!(udf.name(), "gcd");
+    assert_eq!(
+        udf.signature().type_signature,
+        TypeSignature::Exact(vec![DataType::Int32, DataType::Int32])
+    );
+    assert_eq!(udf.return_type(&[]).unwrap(), DataType::Int32);
+
+    // arrays
+    let args = [
+        ColumnarValue::Array(Arc::new(Int32Array::from(vec![Some(25), None, Some(8)]))),
+        ColumnarValue::Array(Arc::new(Int32Array::from(vec![
+            Some(15),
+            Some(1),
+            Some(12),
+        ]))),
+    ];
+    let ColumnarValue::Array(array) = udf.invoke(&args).unwrap() else {
+        panic!("expected an array");
+    };
+    assert_eq!(
+        &*array,
+        &Int32Array::from(vec![Some(5), None, Some(4)]) as &dyn Array
+    );
+
+    // an array and a scalar
+    let args = [
+        ColumnarValue::Array(Arc::new(Int32Array::from(vec![25, 8]))),
+        ColumnarValue::Scalar(ScalarValue::Int32(Some(10))),
+    ];
+    let ColumnarValue::Array(array) = udf.invoke(&args).unwrap() else {
+        panic!("expected an array");
+    };
+    assert_eq!(&*array, &Int32Array::from(vec![5, 2]) as &dyn Array);
+
+    // scalars
+    let args = [
+        ColumnarValue::Scalar(ScalarValue::Int32(Some(25))),
+        ColumnarValue::Scalar(ScalarValue::Int32(Some(15))),
+    ];
+    let ColumnarValue::Scalar(scalar) = udf.invoke(&args).unwrap() else {
+        panic!("expected a scalar");
+    };
+    assert_eq!(scalar, ScalarValue::Int32(Some(5)));
+
+    // errors of the runtime
+    let args = [ColumnarValue::Scalar(ScalarValue::Int32(Some(25)))];
+    let err = udf.invoke(&args).unwrap_err();
+    assert!(
+        err.to_string().contains("expected 2 arguments, got 1"),
+        "{err}"
+    );
+
+    assert!(JsScalarUDF::new(pool, "unknown", Volatility::Immutable).is_err());
+}
+
+#[test]
+fn test_scalar_udf_arg_names() {
+    let udf = ScalarUDF::from(JsScalarUDF::new(pool(), "greet", Volatility::Immutable).unwrap());
+    assert_eq!(udf.signature().type_signature, TypeSignature::VariadicAny);
+    let args = [ColumnarValue::Array(Arc::new(StringArray::from(vec![
+        "Alice", "Bob",
+    ])))];
+    let ColumnarValue::Array(array) = udf.invoke(&args).unwrap() else {
+        panic!("expected an array");
+    };
+    assert_eq!(
+        &*array,
+        &StringArray::from(vec!["Hello, Alice!", "Hello, Bob!"]) as &dyn Array
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_session_context() {
+    let ctx = SessionContext::new();
+    ctx.register_udf(ScalarUDF::from(
+        JsScalarUDF::new(pool(), "gcd", Volatility::Immutable).unwrap(),
+    ));
+    let batches = ctx
+        .sql(
+            "SELECT gcd(CAST(a AS INT), CAST(b AS INT)) AS g \
+             FROM (VALUES (25, 15), (8, 12), (7, NULL)) AS t(a, b)",
+        )
+        .await
+        .unwrap()
+        .collect()
+        .await
+        .unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(
+        &**batches[0].column(0),
+        &Int32Array::from(vec![Some(5), Some(4), None]) as &dyn Array
+    );
+}
+
+#[test]
+fn test_scalar_udf_no_args() {
+    let udf = JsScalarUDF::new(pool(), "one", Volatility::Volatile).unwrap();
+    let ColumnarValue::Array(array) = udf.invoke_no_args(3).unwrap() else {
+        panic!("expected an array");
+    };
+    assert_eq!(&*array, &Int32Array::from(vec![1, 1, 1]) as &dyn Array);
+}
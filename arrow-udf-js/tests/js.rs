@@ -12,18 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{sync::Arc, time::Duration};
 
 use arrow_array::{
-    types::*, ArrayRef, BinaryArray, Date32Array, Decimal128Array, Decimal256Array, Int32Array,
-    LargeBinaryArray, LargeStringArray, ListArray, RecordBatch, StringArray, StructArray,
-    TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
-    TimestampSecondArray,
+    types::*, Array, ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array,
+    Decimal256Array, FixedSizeBinaryArray, Float32Array, Float64Array, Int32Array, Int8Array,
+    IntervalMonthDayNanoArray, LargeBinaryArray, LargeStringArray, ListArray, NullArray,
+    RecordBatch, StringArray, StructArray, Time32SecondArray, TimestampMicrosecondArray,
+    TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray,
 };
 use arrow_buffer::i256;
 use arrow_cast::pretty::{pretty_format_batches, pretty_format_columns};
-use arrow_schema::{DataType, Field, Schema};
-use arrow_udf_js::{CallMode, Runtime};
+use arrow_schema::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use arrow_udf_js::{
+    CallMode, Cancelled, FunctionInfo, FunctionOptions, NonFiniteFloat, NumberCoercion, Runtime,
+    RuntimePool,
+};
 use expect_test::{expect, Expect};
 
 #[test]
@@ -71,6 +76,153 @@ fn test_gcd() {
     );
 }
 
+#[test]
+fn test_function_info() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_with_options(
+            "concat",
+            Field::new("result", DataType::Utf8, true),
+            CallMode::CalledOnNullInput,
+            "export function concat(...args) { return args.join(''); }",
+            FunctionOptions::default()
+                .arg_types(vec![DataType::Utf8])
+                .variadic(true),
+        )
+        .unwrap();
+
+    let info = runtime.function_info("concat").unwrap();
+    assert_eq!(
+        info,
+        FunctionInfo {
+            return_field: Arc::new(Field::new("result", DataType::Utf8, true)),
+            mode: CallMode::CalledOnNullInput,
+            arg_types: Some(vec![DataType::Utf8]),
+            arg_names: None,
+            variadic: true,
+        }
+    );
+    assert!(runtime.function_info("unknown").is_none());
+
+    // the output schema can be built before calling the function
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("concat", &input).unwrap();
+    assert_eq!(output.schema().field(0), info.return_field.as_ref());
+}
+
+#[test]
+fn test_call_range() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "repeat",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            "export function repeat(s, n) { return s.repeat(n); }",
+        )
+        .unwrap();
+    runtime
+        .add_function_with_options(
+            "lengths",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function lengths(s, n) {
+                return Array.from({ length: s.length }, (_, i) => s.get(i)?.length);
+            }
+            "#,
+            FunctionOptions::default().column_mode(true),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("s", DataType::Utf8, true),
+        Field::new("n", DataType::Int32, true),
+    ]);
+    let arg0 = StringArray::from(vec![Some("a"), Some("bc"), None, Some("d")]);
+    let arg1 = Int32Array::from(vec![Some(1), Some(2), Some(3), Some(4)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    for name in ["repeat", "lengths"] {
+        let output = runtime.call_range(name, &input, 1, 3).unwrap();
+        let expected = runtime.call(name, &input.slice(1, 3)).unwrap();
+        assert_eq!(output, expected);
+    }
+    let output = runtime.call_range("repeat", &input, 1, 3).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | repeat |
+        +--------+
+        | bcbc   |
+        |        |
+        | dddd   |
+        +--------+"#]],
+    );
+
+    let output = runtime.call_range("repeat", &input, 4, 0).unwrap();
+    assert_eq!(output.num_rows(), 0);
+
+    let err = runtime.call_range("repeat", &input, 3, 2).unwrap_err();
+    assert_eq!(err.to_string(), "range 3..5 is out of bounds for 4 rows");
+}
+
+#[test]
+fn test_skip_null_rows() {
+    let mut runtime = Runtime::new().unwrap();
+    let js_code = r#"
+        export function first(a, b) {
+            return 1;
+        }
+    "#;
+    runtime
+        .add_function(
+            "first",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "first_on_null",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            &js_code.replace("first", "first_on_null"),
+        )
+        .unwrap();
+
+    // `time32` can not be converted to JS, but `b` is all null
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Time32(TimeUnit::Second), true),
+        Field::new("b", DataType::Int32, true),
+    ]);
+    let arg0 = Time32SecondArray::from(vec![1, 2]);
+    let arg1 = Int32Array::from(vec![None, None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    // the arguments of null rows are not converted
+    let output = runtime.call("first", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------+
+        | first |
+        +-------+
+        |       |
+        |       |
+        +-------+"#]],
+    );
+
+    let err = runtime.call("first_on_null", &input).unwrap_err();
+    assert_eq!(err.to_string(), "failed to get jsvalue from arrow array");
+}
+
 #[test]
 fn test_to_string() {
     let mut runtime = Runtime::new().unwrap();
@@ -120,7 +272,10 @@ fn test_concat() {
             CallMode::ReturnNullOnNullInput,
             r#"
             export function concat(a, b) {
-                return a.concat(b);
+                const c = new Uint8Array(a.length + b.length);
+                c.set(a);
+                c.set(b, a.length);
+                return c;
             }
             "#,
         )
@@ -147,6 +302,81 @@ fn test_concat() {
     );
 }
 
+#[test]
+fn test_binary() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "fnv1a",
+            DataType::UInt32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function fnv1a(bytes) {
+                if (!(bytes instanceof Uint8Array)) {
+                    throw new Error("expect Uint8Array");
+                }
+                let hash = 0x811c9dc5;
+                for (const b of bytes) {
+                    hash = Math.imul(hash ^ b, 0x01000193) >>> 0;
+                }
+                return hash;
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "to_bytes",
+            DataType::LargeBinary,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_bytes(kind) {
+                switch (kind) {
+                    case 0: return new Uint8Array([1, 2, 3]);
+                    case 1: return new Uint8Array([4, 5]).buffer;
+                    default: return [6];
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Binary, true)]);
+    let arg0 = BinaryArray::from(vec![Some(&b""[..]), Some(&b"a"[..]), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("fnv1a", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | fnv1a      |
+        +------------+
+        | 2166136261 |
+        | 3826002220 |
+        |            |
+        +------------+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![0, 1, 2]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("to_bytes", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | to_bytes |
+        +----------+
+        | 010203   |
+        | 0405     |
+        | 06       |
+        +----------+"#]],
+    );
+}
+
 #[test]
 fn test_json_array_access() {
     let mut runtime = Runtime::new().unwrap();
@@ -218,6 +448,129 @@ fn test_json_stringify() {
     );
 }
 
+#[test]
+fn test_fixed_size_binary() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "set_uuid_version",
+            DataType::FixedSizeBinary(16),
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function set_uuid_version(uuid) {
+                if (uuid.length == 15) {
+                    return uuid;
+                }
+                const out = new Uint8Array(uuid);
+                out[6] = (out[6] & 0x0f) | 0x40;
+                out[8] = (out[8] & 0x3f) | 0x80;
+                return out;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::FixedSizeBinary(16), true)]);
+    let bytes: Vec<u8> = (0..16).collect();
+    let arg0 = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+        [Some(bytes.as_slice()), None].into_iter(),
+        16,
+    )
+    .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("set_uuid_version", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------------------+
+        | set_uuid_version                 |
+        +----------------------------------+
+        | 000102030405460788090a0b0c0d0e0f |
+        |                                  |
+        +----------------------------------+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("x", DataType::FixedSizeBinary(15), true)]);
+    let arg0 = FixedSizeBinaryArray::try_from_iter([&bytes[..15]].into_iter()).unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("set_uuid_version", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "expected 16 bytes at row 0, got 15"
+    );
+}
+
+#[test]
+fn test_uuid() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "uuid_string",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            "export function uuid_string(id) { return id; }",
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "upper_uuid",
+            uuid_field("upper_uuid"),
+            CallMode::ReturnNullOnNullInput,
+            "export function upper_uuid(id) { return id.toUpperCase(); }",
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "bad_uuid",
+            uuid_field("bad_uuid"),
+            CallMode::ReturnNullOnNullInput,
+            "export function bad_uuid(id) { return id.slice(1); }",
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![uuid_field("x")]);
+    let bytes: Vec<u8> = (0..16).collect();
+    let arg0 = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+        [Some(bytes.as_slice()), None].into_iter(),
+        16,
+    )
+    .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("uuid_string", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------------------------------------+
+        | uuid_string                          |
+        +--------------------------------------+
+        | 00010203-0405-0607-0809-0a0b0c0d0e0f |
+        |                                      |
+        +--------------------------------------+"#]],
+    );
+
+    let output = runtime.call("upper_uuid", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------------------+
+        | upper_uuid                       |
+        +----------------------------------+
+        | 000102030405060708090a0b0c0d0e0f |
+        |                                  |
+        +----------------------------------+"#]],
+    );
+
+    let err = runtime.call("bad_uuid", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "invalid UUID \"0010203-0405-0607-0809-0a0b0c0d0e0f\" at row 0"
+    );
+}
+
 #[test]
 fn test_binary_json_stringify() {
     let mut runtime = Runtime::new().unwrap();
@@ -358,6 +711,108 @@ fn test_decimal128() {
     );
 }
 
+#[test]
+fn test_decimal128_rounding() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "to_decimal",
+            DataType::Decimal128(10, 2),
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_decimal(s) {
+                return BigDecimal(s);
+            }
+            "#,
+        )
+        .unwrap();
+
+    // extra fractional digits are rounded half away from zero
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![
+        "1.239",
+        "1.234",
+        "-1.235",
+        "99.995",
+        "1.23499999999",
+        "1e-7",
+        "1.5e2",
+    ]);
+    let input = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("to_decimal", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | to_decimal |
+        +------------+
+        | 1.24       |
+        | 1.23       |
+        | -1.24      |
+        | 100.00     |
+        | 1.23       |
+        | 0.00       |
+        | 150.00     |
+        +------------+"#]],
+    );
+
+    // the integer digits must fit in the precision
+    let arg0 = StringArray::from(vec!["123456789"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_decimal", &input).unwrap_err();
+    let message = err.root_cause().to_string();
+    assert!(
+        message.ends_with("at row 0 is out of range for precision 10 and scale 2"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_decimal_objects() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime.converter_mut().set_decimal_objects(true);
+
+    runtime
+        .add_function(
+            "describe",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function describe(a, b) {
+                return `${a.value} (${a.precision},${a.scale}) ${b.value} (${b.precision},${b.scale})`;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(10, 2), true),
+        Field::new("b", DataType::Decimal256(40, 3), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![Some(12340), None])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let arg1 = Decimal256Array::from(vec![Some(i256::from(-5)), Some(i256::from(1))])
+        .with_precision_and_scale(40, 3)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = runtime.call("describe", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----------------------------+
+        | describe                    |
+        +-----------------------------+
+        | 123.40 (10,2) -0.005 (40,3) |
+        |                             |
+        +-----------------------------+"#]],
+    );
+}
+
 #[test]
 fn test_decimal256() {
     let mut runtime = Runtime::new().unwrap();
@@ -721,6 +1176,58 @@ fn test_return_array() {
     );
 }
 
+#[test]
+fn test_null_for_rows() {
+    let struct_type = DataType::Struct(vec![Field::new("a", DataType::Int32, true)].into());
+    let cases = [
+        (DataType::Int32, "x"),
+        (DataType::Utf8, "String(x)"),
+        (DataType::new_list(DataType::Int32, true), "[x]"),
+        (struct_type, "{a: x}"),
+    ];
+    for mode in [CallMode::CalledOnNullInput, CallMode::ReturnNullOnNullInput] {
+        for (return_type, expr) in &cases {
+            let mut runtime = Runtime::new().unwrap();
+            runtime
+                .add_function(
+                    "f",
+                    return_type.clone(),
+                    mode,
+                    &format!(
+                        r#"
+                        export function f(x) {{
+                            if (x % 2 == 0) {{
+                                return null;
+                            }}
+                            if (x == 3) {{
+                                return undefined;
+                            }}
+                            return {expr};
+                        }}
+                        "#
+                    ),
+                )
+                .unwrap();
+
+            let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+            let arg0 = Int32Array::from(vec![Some(0), Some(1), None, Some(3), Some(5)]);
+            let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+            let output = runtime.call("f", &input).unwrap();
+            let column = output.column(0);
+            assert_eq!(column.data_type(), return_type);
+            let valid = (0..column.len())
+                .map(|i| column.is_valid(i))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                valid,
+                [false, true, false, false, true],
+                "unexpected validity for {return_type} in {mode:?}"
+            );
+        }
+    }
+}
+
 #[test]
 fn test_key_value() {
     let mut runtime = Runtime::new().unwrap();
@@ -762,71 +1269,99 @@ fn test_key_value() {
 }
 
 #[test]
-fn test_struct_to_json() {
+fn test_split_name() {
     let mut runtime = Runtime::new().unwrap();
 
+    let fields: arrow_schema::Fields = vec![
+        Field::new("first", DataType::Utf8, true),
+        Field::new("last", DataType::Utf8, true),
+    ]
+    .into();
     runtime
         .add_function(
-            "to_json",
-            json_field("to_json"),
+            "split_name",
+            DataType::Struct(fields.clone()),
             CallMode::ReturnNullOnNullInput,
             r#"
-            export function to_json(object) {
-                return object;
+            export function split_name(name) {
+                const [first, last] = name.split(" ", 2);
+                return {first, last};
             }
             "#,
         )
         .unwrap();
 
-    let schema = Schema::new(vec![Field::new(
-        "struct",
-        DataType::Struct(
-            vec![
-                Field::new("key", DataType::Utf8, true),
-                Field::new("value", DataType::Utf8, true),
-            ]
-            .into(),
-        ),
-        true,
-    )]);
-    let arg0 = StructArray::from(vec![
-        (
-            Arc::new(Field::new("key", DataType::Utf8, true)),
-            Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef,
-        ),
-        (
-            Arc::new(Field::new("value", DataType::Utf8, true)),
-            Arc::new(StringArray::from(vec![Some("b"), None])),
-        ),
-    ]);
+    let schema = Schema::new(vec![Field::new("name", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("Ada Lovelace"), Some("Plato"), None]);
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let output = runtime.call("to_json", &input).unwrap();
+    let output = runtime.call("split_name", &input).unwrap();
+    assert_eq!(output.num_columns(), 1);
+    assert_eq!(
+        output.schema().field(0).data_type(),
+        &DataType::Struct(fields)
+    );
+    let array = output
+        .column(0)
+        .as_any()
+        .downcast_ref::<StructArray>()
+        .unwrap();
+    assert_eq!(
+        array.column_by_name("last").unwrap().as_ref(),
+        &StringArray::from(vec![Some("Lovelace"), None, None])
+    );
     check(
         &[output],
         expect![[r#"
-        +---------------------------+
-        | to_json                   |
-        +---------------------------+
-        | {"key":"a","value":"b"}   |
-        | {"key":null,"value":null} |
-        +---------------------------+"#]],
+        +------------------------------+
+        | split_name                   |
+        +------------------------------+
+        | {first: Ada, last: Lovelace} |
+        | {first: Plato, last: }       |
+        |                              |
+        +------------------------------+"#]],
     );
 }
 
 #[test]
-fn test_range() {
+fn test_partial_struct() {
     let mut runtime = Runtime::new().unwrap();
 
+    let geo = DataType::Struct(
+        vec![
+            Field::new("lat", DataType::Float64, true),
+            Field::new("lon", DataType::Float64, true),
+        ]
+        .into(),
+    );
+    let address = DataType::Struct(
+        vec![
+            Field::new("city", DataType::Utf8, true),
+            Field::new("geo", geo, true),
+        ]
+        .into(),
+    );
     runtime
         .add_function(
-            "range",
-            DataType::Int32,
+            "person",
+            DataType::Struct(
+                vec![
+                    Field::new("id", DataType::Int32, true),
+                    Field::new("name", DataType::Utf8, true),
+                    Field::new("address", address, true),
+                ]
+                .into(),
+            ),
             CallMode::ReturnNullOnNullInput,
             r#"
-            export function* range(n) {
-                for (let i = 0; i < n; i++) {
-                    yield i;
+            export function person(x) {
+                switch (x) {
+                    case 0: return {id: 0, name: "a", address: {city: "c", geo: {lat: 1, lon: 2}}};
+                    case 1: return {};
+                    case 2: return {id: 2, address: {}};
+                    case 3: return {id: 3, address: {city: "x"}};
+                    case 4: return null;
+                    case 5: return {address: {geo: {lat: 1.5}}};
                 }
             }
             "#,
@@ -834,175 +1369,2358 @@ fn test_range() {
         .unwrap();
 
     let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
-    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let arg0 = Int32Array::from(vec![0, 1, 2, 3, 4, 5]);
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let mut outputs = runtime.call_table_function("range", &input, 2).unwrap();
+    let output = runtime.call("person", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----------------------------------------------------------------+
+        | person                                                          |
+        +-----------------------------------------------------------------+
+        | {id: 0, name: a, address: {city: c, geo: {lat: 1.0, lon: 2.0}}} |
+        | {id: , name: , address: }                                       |
+        | {id: 2, name: , address: {city: , geo: }}                       |
+        | {id: 3, name: , address: {city: x, geo: }}                      |
+        |                                                                 |
+        | {id: , name: , address: {city: , geo: {lat: 1.5, lon: }}}       |
+        +-----------------------------------------------------------------+"#]],
+    );
+}
 
-    assert_eq!(outputs.schema().field(0).name(), "row");
-    assert_eq!(outputs.schema().field(1).name(), "range");
-    assert_eq!(outputs.schema().field(1).data_type(), &DataType::Int32);
+#[test]
+fn test_struct_null_fields() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "pair",
+            DataType::Struct(
+                vec![
+                    Field::new("a", DataType::Int32, true),
+                    Field::new("b", DataType::Utf8, true),
+                ]
+                .into(),
+            ),
+            CallMode::CalledOnNullInput,
+            r#"
+            export function pair(x) {
+                switch (x) {
+                    case 0: return {a: 1, b: "x"};
+                    case 1: return {a: null, b: "x"};
+                    case 2: return {a: undefined, b: null};
+                    case 3: return {b: "x"};
+                    case 4: return null;
+                    case 5: return undefined;
+                }
+            }
+            "#,
+        )
+        .unwrap();
 
-    let o1 = outputs.next().unwrap().unwrap();
-    let o2 = outputs.next().unwrap().unwrap();
-    assert_eq!(o1.num_rows(), 2);
-    assert_eq!(o2.num_rows(), 2);
-    assert!(outputs.next().is_none());
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![0, 1, 2, 3, 4, 5]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    check(
-        &[o1, o2],
-        expect![[r#"
-        +-----+-------+
-        | row | range |
-        +-----+-------+
-        | 0   | 0     |
-        | 2   | 0     |
-        | 2   | 1     |
-        | 2   | 2     |
-        +-----+-------+"#]],
+    let output = runtime.call("pair", &input).unwrap();
+    let array = output
+        .column(0)
+        .as_any()
+        .downcast_ref::<StructArray>()
+        .unwrap();
+    let valid = |array: &dyn Array| {
+        (0..array.len())
+            .map(|i| array.is_valid(i))
+            .collect::<Vec<_>>()
+    };
+    // a null or missing field is a null child of a valid struct
+    assert_eq!(valid(array), [true, true, true, true, false, false]);
+    assert_eq!(
+        valid(array.column(0)),
+        [true, false, false, false, false, false]
+    );
+    assert_eq!(
+        valid(array.column(1)),
+        [true, true, false, true, false, false]
     );
 }
 
 #[test]
-fn test_weighted_avg() {
+fn test_partial_struct_non_nullable() {
     let mut runtime = Runtime::new().unwrap();
+
     runtime
-        .add_aggregate(
-            "weighted_avg",
+        .add_function(
+            "key_value",
             DataType::Struct(
                 vec![
-                    Field::new("sum", DataType::Int32, false),
-                    Field::new("weight", DataType::Int32, false),
+                    Field::new("key", DataType::Utf8, false),
+                    Field::new("value", DataType::Utf8, true),
                 ]
                 .into(),
             ),
-            DataType::Float32,
             CallMode::ReturnNullOnNullInput,
             r#"
-            export function create_state() {
-                return {sum: 0, weight: 0};
+            export function key_value(s) {
+                return s == "" ? null : {value: s};
             }
-            export function accumulate(state, value, weight) {
-                state.sum += value * weight;
-                state.weight += weight;
-                return state;
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(StringArray::from(vec![""]))],
+    )
+    .unwrap();
+    // a null struct may have null values for its non-nullable fields
+    runtime.call("key_value", &input).unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(StringArray::from(vec!["a"]))],
+    )
+    .unwrap();
+    let err = runtime.call("key_value", &input).unwrap_err();
+    assert!(
+        err.root_cause().to_string().contains("non-nullable"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_object_mode() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function_with_options(
+            "sub",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function sub(row) {
+                return row.x - row.y;
             }
-            export function retract(state, value, weight) {
-                state.sum -= value * weight;
-                state.weight -= weight;
-                return state;
+            "#,
+            FunctionOptions::default().object_mode(true),
+        )
+        .unwrap();
+
+    // the result does not depend on the order of columns
+    let schema = Schema::new(vec![
+        Field::new("y", DataType::Int32, true),
+        Field::new("x", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let arg1 = Int32Array::from(vec![Some(10), Some(20), Some(30)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = runtime.call("sub", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+
+        | sub |
+        +-----+
+        | 9   |
+        |     |
+        | 27  |
+        +-----+"#]],
+    );
+}
+
+#[test]
+fn test_column_mode() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_with_options(
+            "minus_mean",
+            DataType::Float64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function minus_mean(x) {
+                let sum = 0, count = 0;
+                for (let i = 0; i < x.length; i++) {
+                    if (!x.isNull(i)) {
+                        sum += x.get(i);
+                        count += 1;
+                    }
+                }
+                const result = [];
+                for (let i = 0; i < x.length; i++) {
+                    result.push(x.get(i) - sum / count);
+                }
+                return result;
             }
-            export function merge(state1, state2) {
-                state1.sum += state2.sum;
-                state1.weight += state2.weight;
-                return state1;
+            "#,
+            FunctionOptions::default().column_mode(true),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Float64, true)]);
+    let arg0 = Float64Array::from(vec![Some(1.0), None, Some(5.0)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("minus_mean", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | minus_mean |
+        +------------+
+        | -2.0       |
+        |            |
+        | 2.0        |
+        +------------+"#]],
+    );
+}
+
+#[test]
+fn test_typed_array_mode() {
+    let mut runtime = Runtime::new().unwrap();
+    let options = FunctionOptions::default().typed_array_mode(true);
+    runtime
+        .add_function_with_options(
+            "add",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function add({ a, b }, n) {
+                if (!(a instanceof Int32Array && b instanceof Int32Array)) return null;
+                const c = new Int32Array(n);
+                for (let i = 0; i < n; i++) c[i] = a[i] + b[i];
+                return c;
             }
-            export function finish(state) {
-                return state.sum / state.weight;
+            "#,
+            options.clone(),
+        )
+        .unwrap();
+    // the return values can also be an array
+    runtime
+        .add_function_with_options(
+            "ratio",
+            DataType::Float64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function ratio({ a, b }, n) {
+                return Array.from({ length: n }, (_, i) => a[i] / b[i]);
             }
-"#,
+            "#,
+            options,
         )
         .unwrap();
 
     let schema = Schema::new(vec![
-        Field::new("value", DataType::Int32, true),
-        Field::new("weight", DataType::Int32, true),
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Int32, true),
     ]);
-    let arg0 = Int32Array::from(vec![Some(1), None, Some(3), Some(5)]);
-    let arg1 = Int32Array::from(vec![Some(2), None, Some(4), Some(6)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3), Some(4)]);
+    let arg1 = Int32Array::from(vec![Some(2), Some(2), None, Some(8)]);
     let input =
         RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
 
-    let state = runtime.create_state("weighted_avg").unwrap();
-    check_array(
-        std::slice::from_ref(&state),
+    let output = runtime.call("add", &input).unwrap();
+    check(
+        &[output],
         expect![[r#"
-            +---------------------+
-            | array               |
-            +---------------------+
-            | {sum: 0, weight: 0} |
-            +---------------------+"#]],
+        +-----+
+        | add |
+        +-----+
+        | 3   |
+        |     |
+        |     |
+        | 12  |
+        +-----+"#]],
     );
 
-    let state = runtime.accumulate("weighted_avg", &state, &input).unwrap();
-    check_array(
-        std::slice::from_ref(&state),
+    let output = runtime.call("ratio", &input.slice(2, 2)).unwrap();
+    check(
+        &[output],
         expect![[r#"
-            +-----------------------+
-            | array                 |
-            +-----------------------+
-            | {sum: 44, weight: 12} |
-            +-----------------------+"#]],
+        +-------+
+        | ratio |
+        +-------+
+        |       |
+        | 0.5   |
+        +-------+"#]],
+    );
+}
+
+#[test]
+fn test_typed_array_mode_errors() {
+    let mut runtime = Runtime::new().unwrap();
+    let options = FunctionOptions::default().typed_array_mode(true);
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1, 2]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let mut error = |code: &str, input: &RecordBatch| {
+        runtime
+            .add_function_with_options(
+                "f",
+                DataType::Int32,
+                CallMode::ReturnNullOnNullInput,
+                code,
+                options.clone(),
+            )
+            .unwrap();
+        runtime
+            .call("f", input)
+            .unwrap_err()
+            .root_cause()
+            .to_string()
+    };
+    assert_eq!(
+        error("export function f({ x }) { return 1; }", &input),
+        "expected a typed array or an array of return values, got int"
+    );
+    assert_eq!(
+        error(
+            "export function f({ x }) { return new Int32Array(1); }",
+            &input
+        ),
+        "expected 2 return values, got 1"
     );
+    // the typed array must have the same type as the return type
+    assert_eq!(
+        error(
+            "export function f({ x }) { return new Float64Array(2); }",
+            &input
+        ),
+        "expected a typed array or an array of return values, got object"
+    );
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a"]);
+    let strings = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    assert_eq!(
+        error("export function f({ s }) { return s; }", &strings),
+        "typed array mode only supports numeric columns, got Utf8 for column \"s\""
+    );
+
+    let err = runtime
+        .add_function_with_options(
+            "f",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            "export function f({ x }) { return x; }",
+            options.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "typed array mode requires `CallMode::ReturnNullOnNullInput`"
+    );
+    let err = runtime
+        .add_function_with_options(
+            "f",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function f({ x }) { return x; }",
+            options.column_mode(true),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "typed array mode can not be used with object mode, column mode or multi-column output"
+    );
+}
+
+#[test]
+fn test_column_mode_errors() {
+    let mut runtime = Runtime::new().unwrap();
+    let options = FunctionOptions::default().column_mode(true);
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1, 2]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let mut error = |code: &str| {
+        runtime
+            .add_function_with_options(
+                "f",
+                DataType::Int32,
+                CallMode::CalledOnNullInput,
+                code,
+                options.clone(),
+            )
+            .unwrap();
+        runtime
+            .call("f", &input)
+            .unwrap_err()
+            .root_cause()
+            .to_string()
+    };
+    assert_eq!(
+        error("export function f(x) { return 1; }"),
+        "expected an array of return values, got int"
+    );
+    assert_eq!(
+        error("export function f(x) { return [1]; }"),
+        "expected 2 return values, got 1"
+    );
+    assert!(error("export function f(x) { return [x.get(2), 0]; }")
+        .contains("index 2 out of bounds for length 2"));
+
+    let err = runtime
+        .add_function_with_options(
+            "f",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            "export function f(x) { return x; }",
+            options.object_mode(true),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "object mode and column mode can not be used together"
+    );
+}
+
+#[test]
+fn test_call_by_name() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_with_options(
+            "sub",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function sub(x, y) { return x - y; }",
+            FunctionOptions::default().arg_names(vec!["x".into(), "y".into()]),
+        )
+        .unwrap();
+
+    // columns are selected by name regardless of their order
+    let schema = Schema::new(vec![
+        Field::new("z", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+        Field::new("x", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(100), Some(200)]);
+    let arg1 = Int32Array::from(vec![Some(1), None]);
+    let arg2 = Int32Array::from(vec![Some(10), Some(20)]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0), Arc::new(arg1), Arc::new(arg2)],
+    )
+    .unwrap();
+    let output = runtime.call_by_name("sub", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+
+        | sub |
+        +-----+
+        | 9   |
+        |     |
+        +-----+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call_by_name("sub", &input).unwrap_err();
+    assert_eq!(err.to_string(), "missing column \"y\" for function \"sub\"");
+
+    runtime
+        .add_function(
+            "neg",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function neg(x) { return -x; }",
+        )
+        .unwrap();
+    let err = runtime.call_by_name("neg", &input).unwrap_err();
+    assert_eq!(err.to_string(), "function \"neg\" has no argument names");
+}
+
+#[test]
+fn test_object_mode_table_function() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function_with_options(
+            "range",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function* js_range(row) {
+                for (let i = row.start ?? 0; i < row.stop; i++) {
+                    yield i;
+                }
+            }
+            "#,
+            FunctionOptions::default()
+                .handler("js_range")
+                .object_mode(true),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("stop", DataType::Int32, true),
+        Field::new("start", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(2), Some(3)]);
+    let arg1 = Int32Array::from(vec![None, Some(1)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let mut outputs = runtime.call_table_function("range", &input, 10).unwrap();
+    let output = outputs.next().unwrap().unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+-------+
+        | row | range |
+        +-----+-------+
+        | 0   | 0     |
+        | 0   | 1     |
+        | 1   | 1     |
+        | 1   | 2     |
+        +-----+-------+"#]],
+    );
+    assert!(outputs.next().is_none());
+}
+
+#[test]
+fn test_arg_types() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function_with_options(
+            "repeat",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function repeat(s, n) {
+                return s.repeat(n);
+            }
+            "#,
+            FunctionOptions::default().arg_types(vec![DataType::Utf8, DataType::Int32]),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("s", DataType::Utf8, true),
+        Field::new("n", DataType::Int32, true),
+    ]);
+    let arg0 = StringArray::from(vec!["ab"]);
+    let arg1 = Int32Array::from(vec![2]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = runtime.call("repeat", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | repeat |
+        +--------+
+        | abab   |
+        +--------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        Field::new("s", DataType::Utf8, true),
+        Field::new("n", DataType::Utf8, true),
+    ]);
+    let arg0 = StringArray::from(vec!["ab"]);
+    let arg1 = StringArray::from(vec!["2"]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let err = runtime.call("repeat", &input).unwrap_err();
+    assert_eq!(err.to_string(), "argument 1 expected Int32, got Utf8");
+
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["ab"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("repeat", &input).unwrap_err();
+    assert_eq!(err.to_string(), "expected 2 arguments, got 1");
+    let err = runtime
+        .call_table_function("repeat", &input, 1)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "expected 2 arguments, got 1");
+}
+
+#[test]
+fn test_variadic() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function_with_options(
+            "greatest",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function greatest(sep, ...args) {
+                return `${args.length}${sep}${args.length ? Math.max(...args) : ""}`;
+            }
+            "#,
+            FunctionOptions::default()
+                .arg_types(vec![DataType::Utf8, DataType::Int32])
+                .variadic(true),
+        )
+        .unwrap();
+
+    let input = RecordBatch::try_from_iter([
+        (
+            "sep",
+            Arc::new(StringArray::from(vec![":", ":"])) as ArrayRef,
+        ),
+        ("a", Arc::new(Int32Array::from(vec![Some(1), Some(5)]))),
+        ("b", Arc::new(Int32Array::from(vec![Some(4), None]))),
+        ("c", Arc::new(Int32Array::from(vec![Some(2), Some(3)]))),
+        ("d", Arc::new(Int32Array::from(vec![Some(3), Some(7)]))),
+    ])
+    .unwrap();
+    let output = runtime.call("greatest", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | greatest |
+        +----------+
+        | 4:4      |
+        |          |
+        +----------+"#]],
+    );
+
+    // the rest parameter is empty without trailing arguments
+    let output = runtime
+        .call("greatest", &input.project(&[0]).unwrap())
+        .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | greatest |
+        +----------+
+        | 0:       |
+        | 0:       |
+        +----------+"#]],
+    );
+
+    let err = runtime
+        .call("greatest", &input.project(&[0, 1, 0]).unwrap())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "argument 2 expected Int32, got Utf8");
+
+    let input = input.project(&[1]).unwrap();
+    let err = runtime.call("greatest", &input).unwrap_err();
+    assert_eq!(err.to_string(), "argument 0 expected Utf8, got Int32");
+}
+
+#[test]
+fn test_struct_to_json() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "to_json",
+            json_field("to_json"),
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_json(object) {
+                return object;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new(
+        "struct",
+        DataType::Struct(
+            vec![
+                Field::new("key", DataType::Utf8, true),
+                Field::new("value", DataType::Utf8, true),
+            ]
+            .into(),
+        ),
+        true,
+    )]);
+    let arg0 = StructArray::from(vec![
+        (
+            Arc::new(Field::new("key", DataType::Utf8, true)),
+            Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("value", DataType::Utf8, true)),
+            Arc::new(StringArray::from(vec![Some("b"), None])),
+        ),
+    ]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("to_json", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------------------------+
+        | to_json                   |
+        +---------------------------+
+        | {"key":"a","value":"b"}   |
+        | {"key":null,"value":null} |
+        +---------------------------+"#]],
+    );
+}
+
+#[test]
+fn test_range() {
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime
+        .add_function(
+            "range",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function* range(n) {
+                for (let i = 0; i < n; i++) {
+                    yield i;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let mut outputs = runtime.call_table_function("range", &input, 2).unwrap();
+
+    assert_eq!(outputs.schema().field(0).name(), "row");
+    assert_eq!(outputs.schema().field(1).name(), "range");
+    assert_eq!(outputs.schema().field(1).data_type(), &DataType::Int32);
+
+    let o1 = outputs.next().unwrap().unwrap();
+    let o2 = outputs.next().unwrap().unwrap();
+    assert_eq!(o1.num_rows(), 2);
+    assert_eq!(o2.num_rows(), 2);
+    assert!(outputs.next().is_none());
+
+    check(
+        &[o1, o2],
+        expect![[r#"
+        +-----+-------+
+        | row | range |
+        +-----+-------+
+        | 0   | 0     |
+        | 2   | 0     |
+        | 2   | 1     |
+        | 2   | 2     |
+        +-----+-------+"#]],
+    );
+}
+
+#[test]
+fn test_stateful_mode() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_with_options(
+            "running_sum",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function* running_sum() {
+                let sum = 0;
+                let values;
+                while (true) {
+                    const [x] = yield values;
+                    sum += x;
+                    values = x % 2 == 0 ? [sum, -sum] : [sum];
+                }
+            }
+            "#,
+            FunctionOptions::default().stateful_mode(true),
+        )
+        .unwrap();
+    runtime
+        .add_function_with_options(
+            "first_two",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function* first_two() {
+                const [a] = yield;
+                const [b] = yield [a];
+                yield [b];
+            }
+            "#,
+            FunctionOptions::default().stateful_mode(true),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(2), Some(3), Some(4)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // the state is kept across rows and chunks
+    let outputs = runtime
+        .call_table_function("running_sum", &input, 2)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+-------------+
+        | row | running_sum |
+        +-----+-------------+
+        | 0   | 1           |
+        | 2   | 3           |
+        | 2   | -3          |
+        | 3   | 6           |
+        | 4   | 10          |
+        | 4   | -10         |
+        +-----+-------------+"#]],
+    );
+
+    // the generator is created for each batch
+    let outputs = runtime
+        .call_table_function_stream("running_sum", [input.slice(0, 1), input.slice(0, 1)], 10)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+-------------+
+        | row | running_sum |
+        +-----+-------------+
+        | 0   | 1           |
+        | 0   | 1           |
+        +-----+-------------+"#]],
+    );
+
+    // the remaining rows have no output after the generator returns
+    let outputs = runtime
+        .call_table_function("first_two", &input, 10)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+-----------+
+        | row | first_two |
+        +-----+-----------+
+        | 0   | 1         |
+        | 2   | 2         |
+        +-----+-----------+"#]],
+    );
+
+    let err = runtime.call("running_sum", &input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "functions in stateful mode can only be called as table functions"
+    );
+    let err = runtime
+        .add_function_with_options(
+            "stateful_column",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function* stateful_column() {}",
+            FunctionOptions::default()
+                .stateful_mode(true)
+                .column_mode(true),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "stateful mode can not be used with column mode, typed array mode or multi-column output"
+    );
+}
+
+#[test]
+fn test_call_stream() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "square",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function square(x) { return x * x; }",
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "range",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function* range(n) {
+                for (let i = 0; i < n; i++) {
+                    yield i;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let batches = [vec![Some(1), None], vec![Some(3)]].map(|values| {
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))]).unwrap()
+    });
+
+    let outputs = runtime
+        .call_stream("square", batches.clone())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    check(
+        &outputs,
+        expect![[r#"
+        +--------+
+        | square |
+        +--------+
+        | 1      |
+        |        |
+        | 9      |
+        +--------+"#]],
+    );
+
+    let outputs = runtime
+        .call_table_function_stream("range", batches, 2)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+-------+
+        | row | range |
+        +-----+-------+
+        | 0   | 0     |
+        | 0   | 0     |
+        | 0   | 1     |
+        | 0   | 2     |
+        +-----+-------+"#]],
+    );
+
+    // errors are returned in place of the outputs
+    let mut outputs = runtime.call_stream("unknown", [RecordBatch::new_empty(schema)]);
+    assert!(outputs.next().unwrap().is_err());
+    assert!(outputs.next().is_none());
+}
+
+#[test]
+fn test_weighted_avg() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_aggregate(
+            "weighted_avg",
+            DataType::Struct(
+                vec![
+                    Field::new("sum", DataType::Int32, false),
+                    Field::new("weight", DataType::Int32, false),
+                ]
+                .into(),
+            ),
+            DataType::Float32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function create_state() {
+                return {sum: 0, weight: 0};
+            }
+            export function accumulate(state, value, weight) {
+                state.sum += value * weight;
+                state.weight += weight;
+                return state;
+            }
+            export function retract(state, value, weight) {
+                state.sum -= value * weight;
+                state.weight -= weight;
+                return state;
+            }
+            export function merge(state1, state2) {
+                state1.sum += state2.sum;
+                state1.weight += state2.weight;
+                return state1;
+            }
+            export function finish(state) {
+                return state.sum / state.weight;
+            }
+"#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("value", DataType::Int32, true),
+        Field::new("weight", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3), Some(5)]);
+    let arg1 = Int32Array::from(vec![Some(2), None, Some(4), Some(6)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let state = runtime.create_state("weighted_avg").unwrap();
+    check_array(
+        std::slice::from_ref(&state),
+        expect![[r#"
+            +---------------------+
+            | array               |
+            +---------------------+
+            | {sum: 0, weight: 0} |
+            +---------------------+"#]],
+    );
+
+    let state = runtime.accumulate("weighted_avg", &state, &input).unwrap();
+    check_array(
+        std::slice::from_ref(&state),
+        expect![[r#"
+            +-----------------------+
+            | array                 |
+            +-----------------------+
+            | {sum: 44, weight: 12} |
+            +-----------------------+"#]],
+    );
+
+    let states = arrow_select::concat::concat(&[&state, &state]).unwrap();
+    let state = runtime.merge("weighted_avg", &states).unwrap();
+    check_array(
+        std::slice::from_ref(&state),
+        expect![[r#"
+            +-----------------------+
+            | array                 |
+            +-----------------------+
+            | {sum: 88, weight: 24} |
+            +-----------------------+"#]],
+    );
+
+    let output = runtime.finish("weighted_avg", &state).unwrap();
+    check_array(
+        &[output],
+        expect![[r#"
+            +-----------+
+            | array     |
+            +-----------+
+            | 3.6666667 |
+            +-----------+"#]],
+    );
+}
+
+#[test]
+fn test_boolean() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "describe",
+            DataType::Utf8,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function describe(x) {
+                if (x === null) return "null";
+                if (x === true) return "true";
+                if (x === false) return "false";
+                return "unexpected";
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "negate",
+            DataType::Boolean,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function negate(x) {
+                if (x === null) return null;
+                return x ? 0 : 1;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Boolean, true)]);
+    let arg0 = BooleanArray::from(vec![Some(true), Some(false), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("describe", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | describe |
+        +----------+
+        | true     |
+        | false    |
+        | null     |
+        +----------+"#]],
+    );
+
+    let err = runtime.call("negate", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "expected boolean at row 0, got int"
+    );
+}
+
+#[test]
+fn test_bigint_return() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "to_int64",
+            DataType::Int64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_int64(x) {
+                switch (x) {
+                    case 0: return 9223372036854775807n;
+                    case 1: return -9223372036854775808n;
+                    case 2: return 9223372036854775808n;
+                    default: return x;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "to_uint64",
+            DataType::UInt64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_uint64(x) {
+                switch (x) {
+                    case 0: return 18446744073709551615n;
+                    case 1: return -1n;
+                    default: return x;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let arg0 = Int32Array::from(vec![Some(0), Some(1), Some(3), None]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("to_int64", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------+
+        | to_int64             |
+        +----------------------+
+        | 9223372036854775807  |
+        | -9223372036854775808 |
+        | 3                    |
+        |                      |
+        +----------------------+"#]],
+    );
+
+    let arg0 = Int32Array::from(vec![Some(0), Some(3)]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("to_uint64", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------+
+        | to_uint64            |
+        +----------------------+
+        | 18446744073709551615 |
+        | 3                    |
+        +----------------------+"#]],
+    );
+
+    let arg0 = Int32Array::from(vec![Some(3), Some(2)]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_int64", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "BigInt 9223372036854775808 at row 1 is out of range for i64"
+    );
+
+    let arg0 = Int32Array::from(vec![Some(1)]);
+    let input = RecordBatch::try_new(schema, vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_uint64", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "BigInt -1 at row 0 is out of range for u64"
+    );
+}
+
+#[test]
+fn test_unsafe_integer_return() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "to_int64",
+            DataType::Int64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_int64(x) {
+                switch (x) {
+                    case 0: return 2 ** 53 - 1;
+                    case 1: return -(2 ** 53 - 1);
+                    case 2: return 2n ** 53n + 1n;
+                    case 3: return 2 ** 53 + 1;
+                    case 4: return 1.5;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "to_uint64",
+            DataType::UInt64,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_uint64(x) {
+                return x == 0 ? 2 ** 53 - 1 : -(2 ** 40);
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let arg0 = Int32Array::from(vec![0, 1, 2]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("to_int64", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------------------+
+        | to_int64          |
+        +-------------------+
+        | 9007199254740991  |
+        | -9007199254740991 |
+        | 9007199254740993  |
+        +-------------------+"#]],
+    );
+
+    let arg0 = Int32Array::from(vec![0, 3]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_int64", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "number 9007199254740992 at row 1 is not a safe integer for i64. HINT: return a BigInt instead"
+    );
+
+    let arg0 = Int32Array::from(vec![4]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_int64", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "number 1.5 at row 0 is not a safe integer for i64. HINT: return a BigInt instead"
+    );
+
+    let arg0 = Int32Array::from(vec![0, 1]);
+    let input = RecordBatch::try_new(schema, vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("to_uint64", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "number -1099511627776 at row 1 is not a safe integer for u64. HINT: return a BigInt instead"
+    );
+}
+
+#[test]
+fn test_build_array_errors() {
+    // returns `value` for the second row
+    let error = |return_type: Field, value: &str| {
+        let mut runtime = Runtime::new().unwrap();
+        runtime
+            .add_function(
+                "f",
+                return_type,
+                CallMode::CalledOnNullInput,
+                &format!("export function f(x) {{ return x == 1 ? null : {value}; }}"),
+            )
+            .unwrap();
+        let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+        let arg0 = Int32Array::from(vec![1, 2]);
+        let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+        runtime
+            .call("f", &input)
+            .unwrap_err()
+            .root_cause()
+            .to_string()
+    };
+    let field = |ty| Field::new("f", ty, true);
+    let cases = [
+        (
+            field(DataType::Boolean),
+            "{}",
+            "expected boolean at row 1, got object",
+        ),
+        (
+            field(DataType::Int32),
+            "{}",
+            "expected number at row 1, got object",
+        ),
+        (
+            field(DataType::Int64),
+            "{}",
+            "expected number or BigInt at row 1, got object",
+        ),
+        (
+            field(DataType::UInt64),
+            "'1'",
+            "expected number or BigInt at row 1, got string",
+        ),
+        (
+            field(DataType::Float64),
+            "'1'",
+            "expected number at row 1, got string",
+        ),
+        (
+            field(DataType::Utf8),
+            "{}",
+            "expected string at row 1, got object",
+        ),
+        (
+            field(DataType::LargeUtf8),
+            "1",
+            "expected string at row 1, got int",
+        ),
+        (
+            field(DataType::Binary),
+            "{}",
+            "expected Uint8Array, ArrayBuffer or array of bytes at row 1, got object",
+        ),
+        (
+            field(DataType::LargeBinary),
+            "'abc'",
+            "expected Uint8Array, ArrayBuffer or array of bytes at row 1, got string",
+        ),
+        (
+            json_field("f"),
+            "(() => { const a = {}; a.a = a; return a; })()",
+            "expected JSON serializable value at row 1, got object",
+        ),
+        (
+            decimal_field("f"),
+            "{}",
+            "expected BigDecimal at row 1, got object",
+        ),
+        (
+            field(DataType::Decimal128(10, 2)),
+            "'1.5'",
+            "expected BigDecimal at row 1, got string",
+        ),
+        (
+            field(DataType::Decimal256(10, 2)),
+            "1",
+            "expected BigDecimal at row 1, got int",
+        ),
+        (
+            field(DataType::Date32),
+            "{}",
+            "expected Date at row 1, got object",
+        ),
+        (
+            field(DataType::Timestamp(TimeUnit::Millisecond, None)),
+            "new Date(NaN)",
+            "invalid Date at row 1",
+        ),
+        (
+            field(DataType::Timestamp(TimeUnit::Nanosecond, None)),
+            "new Date(8.64e15)",
+            "Date at row 1 is out of range",
+        ),
+        (
+            field(DataType::Interval(IntervalUnit::MonthDayNano)),
+            "1",
+            "expected interval object at row 1, got int",
+        ),
+        (
+            field(DataType::Interval(IntervalUnit::MonthDayNano)),
+            "{ months: 'a' }",
+            "expected integer months of interval at row 1",
+        ),
+        (
+            field(DataType::new_list(DataType::Int32, true)),
+            "1",
+            "expected array at row 1, got int",
+        ),
+        (
+            field(DataType::Struct(
+                vec![Field::new("a", DataType::Int32, true)].into(),
+            )),
+            "1",
+            "expected object at row 1, got int",
+        ),
+    ];
+    for (return_type, value, expected) in cases {
+        let data_type = return_type.data_type().clone();
+        assert_eq!(error(return_type, value), expected, "{data_type}");
+    }
+}
+
+#[test]
+fn test_register_all() {
+    let mut runtime = Runtime::new().unwrap();
+    let definitions = vec![
+        ("add", "export function add(a, b) { return a + b; }"),
+        ("sub", "export function sub(a, b) { return a - b; }"),
+        ("mul", "export function mul(a, b) { return a * b"),
+        ("div", "export function div(a, b) { return a / b; }"),
+    ];
+    let err = runtime
+        .register_all(definitions.iter().map(|(name, code)| {
+            (
+                name.to_string(),
+                DataType::Int32,
+                CallMode::ReturnNullOnNullInput,
+                code.to_string(),
+            )
+        }))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "failed to add function \"mul\"");
+
+    // functions before the failed one remain registered
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![5]);
+    let arg1 = Int32Array::from(vec![3]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = runtime.call("sub", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+
+        | sub |
+        +-----+
+        | 2   |
+        +-----+"#]],
+    );
+    assert!(runtime.call("mul", &input).is_err());
+    assert!(runtime.call("div", &input).is_err());
+}
+
+#[test]
+fn test_try_add_and_replace_function() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .try_add_function(
+            "f",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function f(x) { return x; }",
+        )
+        .unwrap();
+    let err = runtime
+        .try_add_function(
+            "f",
+            DataType::Int64,
+            CallMode::ReturnNullOnNullInput,
+            "export function f(x) { return x; }",
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "function \"f\" already exists");
+
+    let previous = runtime
+        .replace_function(
+            "f",
+            DataType::Utf8,
+            CallMode::CalledOnNullInput,
+            "export function f(x) { return `${x}`; }",
+        )
+        .unwrap();
+    assert_eq!(
+        previous,
+        Some((DataType::Int32, CallMode::ReturnNullOnNullInput))
+    );
+    let previous = runtime
+        .replace_function(
+            "g",
+            DataType::Utf8,
+            CallMode::CalledOnNullInput,
+            "export function g(x) { return `${x}`; }",
+        )
+        .unwrap();
+    assert_eq!(previous, None);
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("f", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------+
+        | f    |
+        +------+
+        | 1    |
+        | null |
+        +------+"#]],
+    );
+}
+
+#[test]
+fn test_function_expr() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_expr(
+            "add",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "(a, b) => a + b // comment",
+        )
+        .unwrap();
+    runtime
+        .add_function_expr(
+            "range",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "function* (n) { for (let i = 0; i < n; i++) yield i; }",
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), None]);
+    let arg1 = Int32Array::from(vec![Some(2), Some(3)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = runtime.call("add", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+
+        | add |
+        +-----+
+        | 3   |
+        |     |
+        +-----+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(2)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let mut outputs = runtime.call_table_function("range", &input, 10).unwrap();
+    let output = outputs.next().unwrap().unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+-------+
+        | row | range |
+        +-----+-------+
+        | 0   | 0     |
+        | 0   | 1     |
+        +-----+-------+"#]],
+    );
+
+    let err = runtime
+        .add_function_expr(
+            "hello",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            "'hello'",
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"expression of function "hello" must evaluate to a function, got string"#
+    );
+}
+
+#[test]
+fn test_function_not_exported() {
+    let mut runtime = Runtime::new().unwrap();
+    let code = r#"
+        function helper() {}
+        export function gcd(a, b) {
+            return a;
+        }
+        export function lcm(a, b) {
+            return a;
+        }
+        export const VERSION = 1;
+    "#;
+    let err = runtime
+        .add_function(
+            "gdc",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            code,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"function "gdc" not found; exports are: [gcd, lcm, VERSION]"#
+    );
+
+    let err = runtime
+        .add_function(
+            "helper",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            code,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"function "helper" not found; exports are: [gcd, lcm, VERSION]"#
+    );
+
+    let err = runtime
+        .add_function(
+            "VERSION",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            code,
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), r#"export "VERSION" is not a function"#);
+}
+
+#[test]
+fn test_module_exports() {
+    let runtime = Runtime::new().unwrap();
+    let exports = runtime
+        .module_exports(
+            r#"
+            function helper() {}
+            export function add(a, b) {
+                return a + b;
+            }
+            export function* series(n) {
+                for (let i = 0; i < n; i++) {
+                    yield i;
+                }
+            }
+            export const VERSION = 1;
+            "#,
+        )
+        .unwrap();
+    assert_eq!(exports, ["add", "series", "VERSION"]);
+
+    let err = runtime.module_exports("export function (").unwrap_err();
+    assert_eq!(err.to_string(), "failed to declare module");
+}
+
+#[test]
+fn test_interval() {
+    let mut runtime = Runtime::new().unwrap();
+    let interval = DataType::Interval(IntervalUnit::MonthDayNano);
+    runtime
+        .add_function(
+            "next_interval",
+            interval.clone(),
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function next_interval(iv, x) {
+                switch (x) {
+                    case 0: return {months: iv.months, days: iv.days + 1, nanos: iv.nanos};
+                    case 1: return {days: 7};
+                    case 2: return {nanos: 2n ** 60n};
+                    case 3: return iv;
+                    case 4: return null;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "nanos_type",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function nanos_type(iv) {
+                return typeof iv.nanos;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let value = IntervalMonthDayNanoType::make_value;
+    let schema = Schema::new(vec![
+        Field::new("iv", interval.clone(), true),
+        Field::new("x", DataType::Int32, true),
+    ]);
+    let arg0 = IntervalMonthDayNanoArray::from(vec![
+        value(1, 2, 3_000_000_000),
+        value(0, 0, 0),
+        value(0, 0, 0),
+        value(-1, -2, i64::MIN),
+        value(0, 0, 0),
+    ]);
+    let arg1 = Int32Array::from(vec![0, 1, 2, 3, 4]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = runtime.call("next_interval", &input).unwrap();
+    assert_eq!(
+        output.column(0).as_ref(),
+        &IntervalMonthDayNanoArray::from(vec![
+            Some(value(1, 3, 3_000_000_000)),
+            Some(value(0, 7, 0)),
+            Some(value(0, 0, 1 << 60)),
+            Some(value(-1, -2, i64::MIN)),
+            None,
+        ])
+    );
+
+    let output = runtime.call("nanos_type", &input).unwrap();
+    assert_eq!(
+        output.column(0).as_ref(),
+        &StringArray::from(vec!["number", "number", "number", "bigint", "number"])
+    );
+}
+
+#[test]
+fn test_error_row_index() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "check_positive",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function check_positive(x, name) {
+                if (x <= 0) {
+                    throw new Error(`${name} must be positive`);
+                }
+                return x;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("name", DataType::Utf8, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(2), Some(-3)]);
+    let arg1 = StringArray::from(vec![Some("a"), None, None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let err = runtime.call("check_positive", &input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "failed to call function at row 2 with arguments (-3, null)"
+    );
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("null must be positive"));
+}
+
+#[test]
+fn test_normalize_errors() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime.set_normalize_errors(true);
+    runtime
+        .add_function(
+            "throw_value",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function throw_value(x) {
+                switch (x) {
+                    case 0: throw new Error("boom");
+                    case 1: throw "a string";
+                    case 2: throw 42;
+                    case 3: throw {message: "custom", code: 1};
+                    case 4: throw null;
+                    case 5: return null.x;
+                    default: return x;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "throw_series",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function* throw_series(n) {
+                for (let i = 0; i < n; i++) {
+                    yield i;
+                }
+                throw new RangeError("end of series");
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let call = |x: i32| {
+        let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![x]))])
+            .unwrap();
+        runtime.call("throw_value", &input)
+    };
+    let message = |x: i32| call(x).unwrap_err().root_cause().to_string();
+
+    let error = message(0);
+    assert_eq!(
+        error.lines().next().unwrap(),
+        "exception generated by QuickJS: boom"
+    );
+    assert!(error.contains("throw_value"), "expect stack: {error}");
+    assert_eq!(message(1), "exception generated by QuickJS: a string");
+    assert_eq!(message(2), "exception generated by QuickJS: 42");
+    assert_eq!(message(3), "exception generated by QuickJS: custom");
+    assert_eq!(message(4), "exception generated by QuickJS: null");
+    assert!(message(5).starts_with("exception generated by QuickJS: "));
+    call(6).unwrap();
+
+    let input =
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![2]))]).unwrap();
+    let mut outputs = runtime
+        .call_table_function("throw_series", &input, 10)
+        .unwrap();
+    let error = outputs.next().unwrap().unwrap_err();
+    assert_eq!(
+        error.root_cause().to_string().lines().next().unwrap(),
+        "exception generated by QuickJS: end of series"
+    );
+}
+
+#[test]
+fn test_timeout() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime.set_timeout(Some(Duration::from_millis(1)));
+
+    let js_code = r#"
+        export function square(x) {
+            let sum = 0;
+            for (let i = 0; i < x; i++) {
+                sum += x;
+            }
+            return sum;
+        }
+    "#;
+    runtime
+        .add_function(
+            "square",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![100]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("square", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | square |
+        +--------+
+        | 10000  |
+        +--------+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![i32::MAX]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = runtime.call("square", &input).unwrap_err();
+    assert!(format!("{err:?}").contains("interrupted"))
+}
+
+#[test]
+fn test_call_with_cancel() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "spin",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function spin(x) {
+                while (x > 0) {}
+                return x;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // cancel a running call from another thread
+    let token = AtomicBool::new(false);
+    let err = std::thread::scope(|s| {
+        s.spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            token.store(true, Ordering::Relaxed);
+        });
+        runtime
+            .call_with_cancel("spin", &input, &token)
+            .unwrap_err()
+    });
+    assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled));
+
+    // a cancelled token aborts the call immediately
+    let err = runtime
+        .call_with_cancel("spin", &input, &token)
+        .unwrap_err();
+    assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled));
+
+    // the runtime is still usable
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![0]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let token = AtomicBool::new(false);
+    let output = runtime.call_with_cancel("spin", &input, &token).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![0]));
+}
+
+#[test]
+fn test_compile_error_location() {
+    let mut runtime = Runtime::new().unwrap();
+    let err = runtime
+        .add_function(
+            "add",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+export function add(a, b) {
+    return a + ;
+}
+"#,
+        )
+        .unwrap_err();
+    let message = err.root_cause().to_string();
+    assert!(
+        message.starts_with("SyntaxError at add:3"),
+        "unexpected message: {message}"
+    );
+    assert!(
+        message.contains("unexpected token"),
+        "unexpected message: {message}"
+    );
+}
+
+#[test]
+fn test_call_scalar() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "concat",
+            DataType::Utf8,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function concat(a, b) {
+                return `${a}-${b}`;
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "answer",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function answer() {
+                return 42;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let output = runtime
+        .call_scalar(
+            "concat",
+            &[
+                Arc::new(StringArray::from(vec!["a"])),
+                Arc::new(Int32Array::from(vec![None::<i32>])),
+            ],
+        )
+        .unwrap();
+    assert_eq!(&*output, &StringArray::from(vec!["a-null"]));
+
+    // a function without arguments is called once
+    let output = runtime.call_scalar("answer", &[]).unwrap();
+    assert_eq!(&*output, &Int32Array::from(vec![42]));
+
+    let err = runtime
+        .call_scalar("answer", &[Arc::new(Int32Array::from(vec![1, 2]))])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "argument 0 expected 1 value, got 2");
+}
+
+#[test]
+fn test_null_input() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "describe",
+            DataType::Utf8,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function describe(x) {
+                return x === null ? "null" : typeof x;
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "called",
+            DataType::Boolean,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function called(x) {
+                return true;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Null, true)]);
+    let arg0 = NullArray::new(3);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("describe", &input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | describe |
+        +----------+
+        | null     |
+        | null     |
+        | null     |
+        +----------+"#]],
+    );
+
+    // every row of a null array is a null input
+    let output = runtime.call("called", &input).unwrap();
+    assert_eq!(
+        &**output.column(0),
+        &BooleanArray::from(vec![None::<bool>; 3])
+    );
+
+    // also in column mode, object mode and aggregates
+    runtime
+        .add_function_with_options(
+            "called_column",
+            DataType::Boolean,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function called_column(x) {
+                return Array(x.length).fill(true);
+            }
+            "#,
+            FunctionOptions::default().column_mode(true),
+        )
+        .unwrap();
+    runtime
+        .add_function_with_options(
+            "called_object",
+            DataType::Boolean,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function called_object(row) {
+                return true;
+            }
+            "#,
+            FunctionOptions::default().object_mode(true),
+        )
+        .unwrap();
+    for name in ["called_column", "called_object"] {
+        let output = runtime.call(name, &input).unwrap();
+        assert_eq!(
+            &**output.column(0),
+            &BooleanArray::from(vec![None::<bool>; 3]),
+            "{name}"
+        );
+    }
+    runtime
+        .add_aggregate(
+            "count_calls",
+            DataType::Int32,
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function create_state() {
+                return 0;
+            }
+            export function accumulate(state, x) {
+                return state + 1;
+            }
+            "#,
+        )
+        .unwrap();
+    let state = runtime.create_state("count_calls").unwrap();
+    let state = runtime.accumulate("count_calls", &state, &input).unwrap();
+    assert_eq!(&*state, &Int32Array::from(vec![0]));
+}
+
+#[test]
+fn test_null_return() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "record",
+            DataType::Null,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function record(x) {
+                globalThis.total = (globalThis.total ?? 0) + x;
+                return x;
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "total",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            r#"
+            export function total() {
+                return globalThis.total;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = runtime.call("record", &input).unwrap();
+    assert_eq!(output.column(0).data_type(), &DataType::Null);
+    assert_eq!(output.num_rows(), 3);
+
+    let output = runtime.call_scalar("total", &[]).unwrap();
+    assert_eq!(&*output, &Int32Array::from(vec![4]));
+}
+
+#[test]
+fn test_number_coercion() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "to_int8",
+            DataType::Int8,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function to_int8(x) {
+                switch (x) {
+                    case 0: return 3.7;
+                    case 1: return -3.5;
+                    case 2: return "3";
+                    case 3: return 127.4;
+                    case 4: return 127.5;
+                    case 5: return NaN;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let call = |runtime: &Runtime, x: Vec<i32>| {
+        let arg0 = Int32Array::from(x);
+        let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+        runtime.call("to_int8", &input)
+    };
+    let error =
+        |runtime: &Runtime, x: i32| call(runtime, vec![x]).unwrap_err().root_cause().to_string();
+
+    // error by default
+    assert_eq!(
+        error(&runtime, 0),
+        "number 3.7 at row 0 is not an integer for i8"
+    );
+
+    runtime.set_number_coercion(NumberCoercion::Truncate);
+    let output = call(&runtime, vec![0, 1, 3, 4]).unwrap();
+    assert_eq!(&**output.column(0), &Int8Array::from(vec![3, -3, 127, 127]));
+
+    runtime.set_number_coercion(NumberCoercion::Round);
+    let output = call(&runtime, vec![0, 1, 3]).unwrap();
+    assert_eq!(&**output.column(0), &Int8Array::from(vec![4, -4, 127]));
+    assert_eq!(
+        error(&runtime, 4),
+        "number 127.5 at row 0 is out of range for i8"
+    );
+
+    // incompatible values are always errors
+    assert_eq!(error(&runtime, 2), "expected number at row 0, got string");
+    assert_eq!(
+        error(&runtime, 5),
+        "number NaN at row 0 is not an integer for i8"
+    );
+}
+
+#[test]
+fn test_non_finite_float() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "div",
+            DataType::Float64,
+            CallMode::ReturnNullOnNullInput,
+            "export function div(x, y) { return x / y; }",
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "to_float32",
+            DataType::Float32,
+            CallMode::ReturnNullOnNullInput,
+            "export function to_float32(x) { return x; }",
+        )
+        .unwrap();
 
-    let states = arrow_select::concat::concat(&[&state, &state]).unwrap();
-    let state = runtime.merge("weighted_avg", &states).unwrap();
-    check_array(
-        std::slice::from_ref(&state),
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Float64, true),
+        Field::new("y", DataType::Float64, true),
+    ]);
+    let arg0 = Float64Array::from(vec![3.0, 1.0, -1.0, 0.0]);
+    let arg1 = Float64Array::from(vec![2.0, 0.0, 0.0, 0.0]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let float32_input = {
+        let schema = Schema::new(vec![Field::new("x", DataType::Float64, true)]);
+        let arg0 = Float64Array::from(vec![1.5, 1e300]);
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap()
+    };
+
+    // propagate by default
+    let output = runtime.call("div", &input).unwrap();
+    check(
+        &[output],
         expect![[r#"
-            +-----------------------+
-            | array                 |
-            +-----------------------+
-            | {sum: 88, weight: 24} |
-            +-----------------------+"#]],
+        +------+
+        | div  |
+        +------+
+        | 1.5  |
+        | inf  |
+        | -inf |
+        | NaN  |
+        +------+"#]],
     );
 
-    let output = runtime.finish("weighted_avg", &state).unwrap();
-    check_array(
+    runtime.set_non_finite_float(NonFiniteFloat::NullOnNonFinite);
+    let output = runtime.call("div", &input).unwrap();
+    check(
         &[output],
         expect![[r#"
-            +-----------+
-            | array     |
-            +-----------+
-            | 3.6666667 |
-            +-----------+"#]],
+        +-----+
+        | div |
+        +-----+
+        | 1.5 |
+        |     |
+        |     |
+        |     |
+        +-----+"#]],
+    );
+    // numbers out of the range of `f32` are also non-finite
+    let output = runtime.call("to_float32", &float32_input).unwrap();
+    assert_eq!(
+        &**output.column(0),
+        &Float32Array::from(vec![Some(1.5), None])
+    );
+
+    runtime.set_non_finite_float(NonFiniteFloat::ErrorOnNonFinite);
+    let err = runtime.call("div", &input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "number inf at row 1 is not finite for f64"
+    );
+    let err = runtime.call("to_float32", &float32_input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "number inf at row 1 is not finite for f32"
     );
 }
 
 #[test]
-fn test_timeout() {
+fn test_max_converted_values() {
     let mut runtime = Runtime::new().unwrap();
-    runtime.set_timeout(Some(Duration::from_millis(1)));
-
-    let js_code = r#"
-        export function square(x) {
-            let sum = 0;
-            for (let i = 0; i < x; i++) {
-                sum += x;
-            }
-            return sum;
-        }
-    "#;
+    runtime.set_max_converted_values(Some(4));
     runtime
         .add_function(
-            "square",
+            "add",
             DataType::Int32,
             CallMode::ReturnNullOnNullInput,
-            js_code,
+            r#"
+            export function add(a, b) {
+                return a + b;
+            }
+            "#,
         )
         .unwrap();
 
-    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
-    let arg0 = Int32Array::from(vec![100]);
-    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
-
-    let output = runtime.call("square", &input).unwrap();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]));
+    let arg0 = Int32Array::from(vec![1, 2]);
+    let arg1 = Int32Array::from(vec![3, 4]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = runtime.call("add", &input).unwrap();
     check(
         &[output],
         expect![[r#"
-        +--------+
-        | square |
-        +--------+
-        | 10000  |
-        +--------+"#]],
+        +-----+
+        | add |
+        +-----+
+        | 4   |
+        | 6   |
+        +-----+"#]],
     );
 
+    let arg0 = Int32Array::from(vec![1, 2, 3]);
+    let arg1 = Int32Array::from(vec![4, 5, 6]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let err = runtime.call("add", &input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "failed to convert row 2: the number of converted JS values exceeds the limit of 4"
+    );
+
+    runtime.set_max_converted_values(None);
+    runtime.call("add", &input).unwrap();
+}
+
+#[test]
+fn test_run_gc() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "cycle",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function cycle(n) {
+                for (let i = 0; i < n; i++) {
+                    const a = {};
+                    a.self = a;
+                }
+                return n;
+            }
+            "#,
+        )
+        .unwrap();
+
     let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
-    let arg0 = Int32Array::from(vec![i32::MAX]);
+    let arg0 = Int32Array::from(vec![100]);
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let err = runtime.call("square", &input).unwrap_err();
-    assert!(format!("{err:?}").contains("interrupted"))
+    runtime.run_gc();
+    let before = runtime.memory_usage();
+    runtime.call("cycle", &input).unwrap();
+    let garbage = runtime.memory_usage();
+    // cycles are not freed until the garbage collector runs
+    assert!(garbage.obj_count > before.obj_count);
+    runtime.run_gc();
+    let after = runtime.memory_usage();
+    assert!(after.obj_count < garbage.obj_count);
 }
 
 #[test]
@@ -1048,11 +3766,369 @@ fn test_memory_limit() {
     assert!(format!("{err:?}").contains("out of memory"))
 }
 
-/// assert Runtime is Send and Sync
+#[test]
+fn test_runtime_builder() {
+    let mut runtime = Runtime::builder()
+        .memory_limit(1 << 20) // 1MB
+        .timeout(Duration::from_millis(1))
+        .max_converted_values(2)
+        .build()
+        .unwrap();
+
+    let js_code = r#"
+        export function alloc(x) {
+            new Array(x).fill(0);
+            return x;
+        }
+        export function spin(x) {
+            for (;;) {}
+        }
+    "#;
+    runtime
+        .add_function(
+            "alloc",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+    runtime
+        .add_function(
+            "spin",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            js_code,
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let arg0 = Int32Array::from(vec![1 << 20]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("alloc", &input).unwrap_err();
+    assert!(format!("{err:?}").contains("out of memory"));
+
+    let err = runtime.call("spin", &input).unwrap_err();
+    assert!(format!("{err:?}").contains("interrupted"));
+
+    let arg0 = Int32Array::from(vec![1, 2, 3]);
+    let input = RecordBatch::try_new(schema, vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("alloc", &input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "failed to convert row 2: the number of converted JS values exceeds the limit of 2"
+    );
+}
+
+#[test]
+fn test_with_quickjs() {
+    // the memory limit configured on the quickjs runtime is kept
+    let quickjs = arrow_udf_js::rquickjs::Runtime::new().unwrap();
+    quickjs.set_memory_limit(4 << 20); // 4MB
+    let mut runtime = Runtime::builder()
+        .timeout(Duration::from_millis(1))
+        .build_with_quickjs(quickjs)
+        .unwrap();
+
+    let js_code = r#"
+        export function alloc(x) {
+            new Array(x).fill(0);
+            return x;
+        }
+        export function spin(x) {
+            for (;;) {}
+        }
+    "#;
+    runtime
+        .register_all([
+            (
+                "alloc",
+                DataType::Int32,
+                CallMode::ReturnNullOnNullInput,
+                js_code,
+            ),
+            (
+                "spin",
+                DataType::Int32,
+                CallMode::ReturnNullOnNullInput,
+                js_code,
+            ),
+        ])
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+    let arg0 = Int32Array::from(vec![1]);
+    let input = RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0)]).unwrap();
+    let output = runtime.call("alloc", &input).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![1]));
+
+    let arg0 = Int32Array::from(vec![1 << 22]);
+    let input = RecordBatch::try_new(schema, vec![Arc::new(arg0)]).unwrap();
+    let err = runtime.call("alloc", &input).unwrap_err();
+    assert!(format!("{err:?}").contains("out of memory"));
+
+    let err = runtime.call("spin", &input).unwrap_err();
+    assert!(format!("{err:?}").contains("interrupted"));
+}
+
+#[test]
+fn test_multi_column() {
+    let mut runtime = Runtime::new().unwrap();
+    let columns = DataType::Struct(
+        vec![
+            Field::new("sum", DataType::Int32, true),
+            Field::new("product", DataType::Int32, true),
+        ]
+        .into(),
+    );
+    runtime
+        .add_function_with_options(
+            "sum_product",
+            columns.clone(),
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            export function sum_product(a, b) {
+                if (a == 0) return a == b ? null : [a];
+                return [a + b, a * b];
+            }
+            "#,
+            FunctionOptions::default().multi_column(true),
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(2), None, Some(0), Some(0)]);
+    let arg1 = Int32Array::from(vec![Some(3), Some(1), Some(0), Some(1)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = runtime.call_range("sum_product", &input, 0, 3).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+---------+
+        | sum | product |
+        +-----+---------+
+        | 5   | 6       |
+        |     |         |
+        |     |         |
+        +-----+---------+"#]],
+    );
+
+    // the number of returned values must be the number of columns
+    let err = runtime.call("sum_product", &input).unwrap_err();
+    assert_eq!(err.to_string(), "expected 2 values at row 3, got 1");
+
+    let err = runtime
+        .call_scalar(
+            "sum_product",
+            &[
+                Arc::new(Int32Array::from(vec![1])),
+                Arc::new(Int32Array::from(vec![2])),
+            ],
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("use `call` instead"), "{err}");
+    let err = runtime
+        .call_table_function("sum_product", &input, 10)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "multi-column output is not supported for table functions"
+    );
+
+    let err = runtime
+        .add_function_with_options(
+            "sum",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function sum(a, b) { return [a + b]; }",
+            FunctionOptions::default().multi_column(true),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "multi-column functions must return a struct type, got Int32"
+    );
+}
+
+#[test]
+fn test_function_warnings() {
+    let mut runtime = Runtime::new().unwrap();
+    let warnings = runtime
+        .add_function_with_warnings(
+            "add",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "export function add(a, b) { return a + b; }",
+            FunctionOptions::default().arg_types(vec![DataType::Int32, DataType::Int32]),
+        )
+        .unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let warnings = runtime
+        .add_function_with_warnings(
+            "full_name",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            "export async function full_name(first, last) { return first + last; }",
+            FunctionOptions::default()
+                .object_mode(true)
+                .arg_types(vec![DataType::Utf8, DataType::Utf8]),
+        )
+        .unwrap();
+    assert_eq!(
+        warnings,
+        [
+            "function \"full_name\" is async, so it returns promises instead of values",
+            "function \"full_name\" declares 2 parameters, but is called with 1 arguments",
+        ]
+    );
+
+    // the arguments of variadic functions are not checked
+    let warnings = runtime
+        .add_function_with_warnings(
+            "concat",
+            DataType::Utf8,
+            CallMode::ReturnNullOnNullInput,
+            "export function concat(a, b, c) { return a + b + c; }",
+            FunctionOptions::default()
+                .arg_types(vec![DataType::Utf8])
+                .variadic(true),
+        )
+        .unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
+
+#[test]
+fn test_expr_strict_mode() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function_expr(
+            "leak",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            "(x) => { undeclared = x; return x; }",
+        )
+        .unwrap();
+    let err = runtime
+        .call_scalar("leak", &[Arc::new(Int32Array::from(vec![1]))])
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("ReferenceError"), "{err:?}");
+}
+
+#[test]
+fn test_reset_globals() {
+    let mut runtime = Runtime::new().unwrap();
+    runtime
+        .add_function(
+            "counter",
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            let count = 0;
+            export function counter(x) {
+                globalThis.leaked = (globalThis.leaked ?? 0) + x;
+                count += 1;
+                return count;
+            }
+            "#,
+        )
+        .unwrap();
+    runtime
+        .add_function_expr(
+            "leaked",
+            DataType::Int32,
+            CallMode::CalledOnNullInput,
+            "() => globalThis.leaked ?? -1",
+        )
+        .unwrap();
+    runtime
+        .add_aggregate(
+            "count_calls",
+            DataType::Int32,
+            DataType::Int32,
+            CallMode::ReturnNullOnNullInput,
+            r#"
+            let calls = 0;
+            export function create_state() {
+                return 0;
+            }
+            export function accumulate(state, value) {
+                calls += 1;
+                return calls;
+            }
+            "#,
+        )
+        .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(2)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // state leaks across batches
+    runtime.call("counter", &input).unwrap();
+    let output = runtime.call("counter", &input).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![3, 4]));
+    let output = runtime.call_scalar("leaked", &[]).unwrap();
+    assert_eq!(&*output, &Int32Array::from(vec![6]));
+    let state = runtime.create_state("count_calls").unwrap();
+    let state = runtime.accumulate("count_calls", &state, &input).unwrap();
+    assert_eq!(&*state, &Int32Array::from(vec![2]));
+
+    runtime.reset_globals().unwrap();
+    let output = runtime.call_scalar("leaked", &[]).unwrap();
+    assert_eq!(&*output, &Int32Array::from(vec![-1]));
+    let output = runtime.call("counter", &input).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![1, 2]));
+    let state = runtime.create_state("count_calls").unwrap();
+    let state = runtime.accumulate("count_calls", &state, &input).unwrap();
+    assert_eq!(&*state, &Int32Array::from(vec![2]));
+}
+
+#[test]
+fn test_runtime_pool() {
+    let mut pool = RuntimePool::new(2, Runtime::builder()).unwrap();
+    assert_eq!(pool.size(), 2);
+    pool.add_function(
+        "square",
+        DataType::Int32,
+        CallMode::ReturnNullOnNullInput,
+        r#"
+        export function square(x) {
+            return x * x;
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let output = pool.get().call("square", &input).unwrap();
+                assert_eq!(
+                    &**output.column(0),
+                    &Int32Array::from(vec![Some(1), None, Some(9)])
+                );
+            });
+        }
+    });
+}
+
+/// assert Runtime is Send, and RuntimePool is Send and Sync
 #[test]
 fn test_send_sync() {
+    fn assert_send<T: Send>() {}
     fn assert_send_sync<T: Send + Sync>() {}
-    assert_send_sync::<Runtime>();
+    assert_send::<Runtime>();
+    assert_send_sync::<RuntimePool>();
 }
 
 /// Compare the actual output with the expected output.
@@ -1085,6 +4161,12 @@ fn large_binary_json_field(name: &str) -> Field {
         .with_metadata([("ARROW:extension:name".into(), "arrowudf.json".into())].into())
 }
 
+/// Returns a field with UUID type.
+fn uuid_field(name: &str) -> Field {
+    Field::new(name, DataType::FixedSizeBinary(16), true)
+        .with_metadata([("ARROW:extension:name".into(), "arrow.uuid".into())].into())
+}
+
 /// Returns a field with decimal type.
 fn decimal_field(name: &str) -> Field {
     Field::new(name, DataType::Utf8, true)
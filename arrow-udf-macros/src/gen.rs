@@ -21,8 +21,35 @@ use quote::{format_ident, quote};
 use super::*;
 
 impl FunctionAttr {
-    /// Expands the wildcard in function arguments or return type.
+    /// Expands the wildcard in function arguments or return type,
+    /// and the arguments with default values into functions of each number of arguments.
     pub fn expand(&self) -> Vec<Self> {
+        if !self.defaults.is_empty() {
+            let required = self.args.len() - self.defaults.len();
+            return (0..=self.defaults.len())
+                .flat_map(|n| {
+                    // the omitted arguments have no input columns to check
+                    let arg_metadata = self
+                        .arg_metadata
+                        .iter()
+                        .filter(|(index, _, _)| *index < required + n)
+                        .cloned()
+                        .collect();
+                    FunctionAttr {
+                        args: self.args[..required + n].to_vec(),
+                        defaults: self.defaults[n..].to_vec(),
+                        arg_metadata,
+                        ..self.clone()
+                    }
+                    .expand_wildcard()
+                })
+                .collect();
+        }
+        self.expand_wildcard()
+    }
+
+    /// Expands the wildcard in function arguments or return type.
+    fn expand_wildcard(&self) -> Vec<Self> {
         let args = self.args.iter().map(|ty| types::expand_type_wildcard(ty));
         let ret = types::expand_type_wildcard(&self.ret);
         // multi_cartesian_product should emit an empty set if the input is empty.
@@ -57,7 +84,70 @@ impl FunctionAttr {
         .iter()
         .map(|ty| field("", ty))
         .collect_vec();
-        let ret = field(&self.name, &self.ret);
+        let ret = self.return_field(user_fn);
+        if self.rows_per_input.is_some() && !self.is_table_function {
+            return Err(Error::new(
+                Span::call_site(),
+                "`rows_per_input` can only be used for table functions",
+            ));
+        }
+        if let Some(max) = self.max_rows_per_input {
+            if !self.is_table_function || self.batches {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`max_rows_per_input` can only be used for table functions without `batches`",
+                ));
+            }
+            if !(1..=1024).contains(&max) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`max_rows_per_input` must be in 1..=1024",
+                ));
+            }
+        }
+        if self.nullable.is_some() && self.is_table_function {
+            return Err(Error::new(
+                Span::call_site(),
+                "`nullable` can only be used for scalar functions",
+            ));
+        }
+        if self.no_row_index && !self.is_table_function {
+            return Err(Error::new(
+                Span::call_site(),
+                "`no_row_index` can only be used for table functions",
+            ));
+        }
+        if self.utc && !self.args.iter().any(|t| is_timestamp(t)) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`utc` can only be used for functions with `timestamp` arguments",
+            ));
+        }
+        if self.round_decimal && (self.is_window || !self.args.iter().any(|t| is_scaled_decimal(t)))
+        {
+            return Err(Error::new(
+                Span::call_site(),
+                "`round_decimal` can only be used for scalar and table functions with `decimal128` or `decimal256` arguments",
+            ));
+        }
+        let rows_per_input = self.rows_per_input.unwrap_or(1);
+        let volatile = self.volatile;
+        self.check_any_types()?;
+        let type_infer = match &self.type_infer {
+            Some(f) => {
+                let f: syn::Expr = syn::parse_str(f)?;
+                quote! { Some(#f) }
+            }
+            // infer the return type from the first `any` or `map<K, any>` argument
+            None if self.ret == "any" => {
+                let name = self.output_name.as_ref().unwrap_or(&self.name);
+                let (i, map) = self.any_arg().expect("checked by check_any_types");
+                quote! {
+                    Some(|args: &[Field]| ::arrow_udf::codegen::infer_any_type(#name, args, #i, #map))
+                }
+            }
+            None => quote! { None },
+        };
 
         let eval_name = match &self.output {
             Some(output) => format_ident!("{}", output),
@@ -65,24 +155,74 @@ impl FunctionAttr {
         };
         let sig_name = format_ident!("{}_sig", self.ident_name());
         let ffi_name = format_ident!("{}_ffi", self.ident_name());
+        let cursor_ffi_name = format_ident!("{}_cursor_ffi", self.ident_name());
         let export_name = format!("arrowudf_{}", base64_encode(&self.normalize_signature()));
+        let export_name_const = format_ident!("{}_EXPORT_NAME", self.ident_name().to_uppercase());
+        let cursor_export_name = format!(
+            "arrowudfcursor_{}",
+            base64_encode(&self.normalize_signature())
+        );
+        let session_ffi_name = format_ident!("{}_session_ffi", self.ident_name());
+        let session_export_name = format!(
+            "arrowudfsession_{}",
+            base64_encode(&self.normalize_signature())
+        );
         let eval_function = self.generate_function(user_fn, &eval_name)?;
-        let kind = match self.is_table_function {
-            true => quote! { Table },
-            false => quote! { Scalar },
+        let kind = if self.is_window {
+            quote! { Window }
+        } else if self.is_table_function {
+            quote! { Table }
+        } else if user_fn.async_ {
+            quote! { AsyncScalar }
+        } else {
+            quote! { Scalar }
         };
-        let ffi_wrapper = match self.is_table_function {
-            true => quote! { table_wrapper },
-            false => quote! { scalar_wrapper },
+        // window functions and async functions are not exported through FFI
+        let exported = !self.is_window && !user_fn.async_;
+        let ffi_function = exported.then(|| {
+            let ffi_wrapper = match self.is_table_function {
+                true => quote! { table_wrapper },
+                false => quote! { scalar_wrapper },
+            };
+            // scalar functions can also be called through a cursor,
+            // and table functions through a session
+            let cursor_function = match self.is_table_function {
+                false => quote! {
+                    #[export_name = #cursor_export_name]
+                    unsafe extern "C" fn #cursor_ffi_name(out: *mut arrow_udf::ffi::CSlice) {
+                        arrow_udf::ffi::scalar_cursor_open(#eval_name, out)
+                    }
+                },
+                true => quote! {
+                    #[export_name = #session_export_name]
+                    unsafe extern "C" fn #session_ffi_name(ptr: *const u8, len: usize, out: *mut arrow_udf::ffi::CSlice) -> i32 {
+                        arrow_udf::ffi::table_session_open(#eval_name, ptr, len, out)
+                    }
+                },
+            };
+            quote! {
+                /// The symbol of the function exported through FFI.
+                #[allow(dead_code)]
+                const #export_name_const: &str = #export_name;
+
+                #[export_name = #export_name]
+                unsafe extern "C" fn #ffi_name(ptr: *const u8, len: usize, out: *mut arrow_udf::ffi::CSlice) -> i32 {
+                    arrow_udf::ffi::#ffi_wrapper(#eval_name, ptr, len, out)
+                }
+
+                #cursor_function
+            }
+        });
+
+        let sig_export_name = match exported {
+            true => quote! { Some(#export_name.into()) },
+            false => quote! { None },
         };
 
         Ok(quote! {
             #eval_function
 
-            #[export_name = #export_name]
-            unsafe extern "C" fn #ffi_name(ptr: *const u8, len: usize, out: *mut arrow_udf::ffi::CSlice) -> i32 {
-                arrow_udf::ffi::#ffi_wrapper(#eval_name, ptr, len, out)
-            }
+            #ffi_function
 
             #[cfg(feature = "global_registry")]
             #[::arrow_udf::codegen::linkme::distributed_slice(::arrow_udf::sig::SIGNATURES)]
@@ -96,6 +236,10 @@ impl FunctionAttr {
                     arg_types: args.into(),
                     variadic: #variadic,
                     return_type: #ret,
+                    volatile: #volatile,
+                    rows_per_input: #rows_per_input,
+                    type_infer: #type_infer,
+                    export_name: #sig_export_name,
                     function: FunctionKind::#kind(#eval_name),
                 }
             }
@@ -108,9 +252,48 @@ impl FunctionAttr {
         user_fn: &UserFunctionAttr,
         eval_fn_name: &Ident,
     ) -> Result<TokenStream2> {
+        // these functions receive the whole input and are called without the extra arguments
+        if (user_fn.context.is_some() || self.init.is_some()) && (self.batches || self.is_window) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`init` and `&Context` are not supported for functions with `batches` or window functions",
+            ));
+        }
+        if user_fn.session && (self.batches || self.is_window) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`&SessionContext` is not supported for functions with `batches` or window functions",
+            ));
+        }
+        if user_fn.async_ && (self.is_table_function || self.is_window || self.batch_fn.is_some()) {
+            return Err(Error::new(
+                Span::call_site(),
+                "async functions are only supported for scalar functions without `batch_fn`",
+            ));
+        }
+        if self.batches {
+            return self.generate_batches_function(user_fn, eval_fn_name);
+        }
+        if self.is_window {
+            return self.generate_window_function(user_fn, eval_fn_name);
+        }
+        if self.columnar {
+            return self.generate_columnar_function(user_fn, eval_fn_name);
+        }
         let variadic = matches!(self.args.last(), Some(t) if t == "...");
         let num_args = self.args.len() - if variadic { 1 } else { 0 };
         let user_fn_name = format_ident!("{}", user_fn.name);
+        if !self.defaults.is_empty() && self.batch_fn.is_some() {
+            return Err(Error::new(
+                Span::call_site(),
+                "customized batch function is not supported for arguments with default values",
+            ));
+        }
+        let defaults = self
+            .defaults
+            .iter()
+            .map(|d| syn::parse_str::<syn::Expr>(d))
+            .collect::<Result<Vec<_>>>()?;
 
         let children_indices = (0..num_args).collect_vec();
 
@@ -123,32 +306,169 @@ impl FunctionAttr {
         }
         let inputs = idents("i", &children_indices);
         let arrays = idents("a", &children_indices);
-        let arg_arrays = children_indices
+        // `any` arguments are numbered in order, which is the index of their arrays in `AnyListBuilder`
+        let any_arrays = children_indices
             .iter()
-            .map(|i| format_ident!("{}", types::array_type(&self.args[*i])));
+            .filter(|i| self.args[**i] == "any")
+            .map(|i| &arrays[*i])
+            .collect_vec();
+        let read_inputs = children_indices.iter().map(|i| {
+            let (input, array) = (&inputs[*i], &arrays[*i]);
+            match any_arrays.iter().position(|a| *a == array) {
+                Some(source) => quote! {
+                    let #input = (!#array.is_null(i)).then(|| ::arrow_udf::types::AnyValue::new(#array, #source, i));
+                },
+                None => quote! {
+                    let #input = unsafe { (!#array.is_null(i)).then(|| #array.value_unchecked(i)) };
+                },
+            }
+        }).collect_vec();
         let ret_array_type = format_ident!("{}", types::array_type(&self.ret));
-        let ret_data_type = field(&self.name, &self.ret);
+        let ret_data_type = self.return_field(user_fn);
+        let nullable = self.output_nullable(user_fn);
 
         let variadic_args = variadic.then(|| quote! { variadic_row, });
-        let context = user_fn.context.then(|| quote! { &self.context, });
+        // the trailing columns after the fixed arguments, which may be empty
+        let let_variadic_row = variadic.then(|| {
+            quote! {
+                let variadic_row = ::arrow_udf::types::VariadicRow::new(&input.columns()[#num_args..], i);
+            }
+        });
+        let context = user_fn.context.as_ref().map(|_| quote! { &*CONTEXT, });
+        // the session variables are read from the input schema once per call
+        let session = user_fn.session.then(|| quote! { &session, });
+        let let_session = user_fn.session.then(|| {
+            quote! {
+                let session = ::arrow_udf::session::SessionContext::from_metadata(input.schema().metadata());
+            }
+        });
+        // the context is initialized once and shared by all calls
+        let static_context = match (&self.init, &user_fn.context) {
+            (Some(init), Some(ty)) => {
+                let init = format_ident!("{}", init);
+                let ty: TokenStream2 = ty.parse().unwrap();
+                Some(quote! {
+                    static CONTEXT: once_cell::sync::Lazy<#ty> = once_cell::sync::Lazy::new(#init);
+                })
+            }
+            (Some(_), None) => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`init` is specified but the function has no `&Context` argument",
+                ));
+            }
+            (None, Some(_)) => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "functions with a `&Context` argument must specify `init`",
+                ));
+            }
+            (None, None) => None,
+        };
         let writer = user_fn.write.then(|| quote! { builder, });
         let await_ = user_fn.async_.then(|| quote! { .await });
+        if self.keys_sorted && types::map_key_value(&self.ret).is_none() {
+            return Err(Error::new(
+                Span::call_site(),
+                "`keys_sorted` can only be used for functions that return `map`",
+            ));
+        }
+        if self.called_on_null_input && self.strict {
+            return Err(Error::new(
+                Span::call_site(),
+                "`called_on_null_input` and `strict` cannot be used together",
+            ));
+        }
+        if self.called_on_null_input && !user_fn.args_option.iter().take(num_args).all(|b| *b) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`called_on_null_input` requires all arguments to be `Option`",
+            ));
+        }
         // transform inputs for array arguments
         // e.g. for `int[]`, transform `ArrayRef` -> `&[T]`
         let transformed_inputs = inputs
             .iter()
             .zip(&self.args)
-            .map(|(input, ty)| transform_input(input, ty));
+            .zip(user_fn.args_option.iter())
+            .map(|((input, ty), opt)| {
+                let mut input = transform_input(input, ty);
+                if types::decimal128_precision_scale(ty).is_some() {
+                    // the value may not fit in `rust_decimal::Decimal`, which is a row error
+                    input = quote! {
+                        match #input {
+                            Ok(v) => v,
+                            Err(e) => break 'row Err(e.to_string()),
+                        }
+                    };
+                }
+                if self.utc && is_timestamp(ty) {
+                    input = quote! { #input.and_utc() };
+                }
+                // strict functions only see non-null values, wrap them for `Option` arguments
+                if self.strict && *opt {
+                    quote! { Some(#input) }
+                } else {
+                    input
+                }
+            });
         // call the user defined function
         let mut output = quote! { #user_fn_name(
             #(#transformed_inputs,)*
+            #(#defaults,)*
             #variadic_args
             #context
+            #session
             #writer
         ) #await_ };
+        // the row index of table functions, or the number of rows if `no_row_index` is set
+        let append_index = match self.no_row_index {
+            true => quote! { num_rows += 1; },
+            false => quote! { index_builder.append_value(i as i32); },
+        };
         // handle error if the function returns `Result`
         // wrap a `Some` if the function doesn't return `Option`
-        output = if self.is_table_function {
+        output = if self.has_fallible_conversion() {
+            // errors of the function and of the conversions are collected as `Result<Option<T>, String>`
+            let result = match user_fn.return_type_kind {
+                ReturnTypeKind::T => quote! { Ok(Some(#output)) },
+                ReturnTypeKind::Option => quote! { Ok(#output) },
+                ReturnTypeKind::Result => quote! { #output.map(Some).map_err(|e| e.to_string()) },
+                ReturnTypeKind::ResultOption => quote! { #output.map_err(|e| e.to_string()) },
+            };
+            // the label is only used to break out of the call on conversion errors of arguments
+            let result = match self
+                .args
+                .iter()
+                .any(|ty| types::decimal128_precision_scale(ty).is_some())
+            {
+                true => quote! { 'row: { #result } },
+                false => result,
+            };
+            if self.is_table_function {
+                quote! {{
+                    let r: ::std::result::Result<_, String> = #result;
+                    match r {
+                        Ok(x) => x,
+                        Err(e) => {
+                            #append_index
+                            builder.append_null();
+                            error_builder.append_value(e);
+                            None
+                        }
+                    }
+                }}
+            } else {
+                let convert_output = self.gen_convert_output();
+                quote! {{
+                    let r: ::std::result::Result<_, String> = #result;
+                    match r #convert_output {
+                        Ok(x) => { error_builder.append_null(); x },
+                        Err(e) => { error_builder.append_value(e); None }
+                    }
+                }}
+            }
+        } else if self.is_table_function {
             match user_fn.return_type_kind {
                 ReturnTypeKind::T => quote! { Some(#output) },
                 ReturnTypeKind::Option => output,
@@ -156,7 +476,7 @@ impl FunctionAttr {
                     quote! { match #output {
                         Ok(x) => Some(x),
                         Err(e) => {
-                            index_builder.append_value(i as i32);
+                            #append_index
                             builder.append_null();
                             error_builder.append_value(e.to_string());
                             None
@@ -167,7 +487,7 @@ impl FunctionAttr {
                     quote! { match #output {
                         Ok(x) => x,
                         Err(e) => {
-                            index_builder.append_value(i as i32);
+                            #append_index
                             builder.append_null();
                             error_builder.append_value(e.to_string());
                             None
@@ -195,17 +515,18 @@ impl FunctionAttr {
         };
         // if user function accepts non-option arguments, we assume the function
         // returns null on null input, so we need to unwrap the inputs before calling.
+        // strict functions always return null on null input.
         let some_inputs = inputs
             .iter()
             .zip(user_fn.args_option.iter())
             .map(|(input, opt)| {
-                if *opt {
+                if *opt && !self.strict {
                     quote! { #input }
                 } else {
                     quote! { Some(#input) }
                 }
             });
-        if !self.is_table_function && user_fn.has_error() {
+        if !self.is_table_function && self.has_error(user_fn) {
             output = quote! {
                 match (#(#inputs,)*) {
                     (#(#some_inputs,)*) => #output,
@@ -223,71 +544,167 @@ impl FunctionAttr {
 
         let eval = if self.is_table_function {
             let builder = builder(&self.ret);
-            let append_output = gen_append(&self.ret);
-            let error_append_null = user_fn
-                .has_error()
+            let append_output = self.gen_append_output();
+            let error_append_null = self
+                .has_error(user_fn)
                 .then(|| quote! { error_builder.append_null(); });
-            let element = match user_fn.iterator_item_kind.clone().unwrap() {
-                ReturnTypeKind::T => quote! {{ #error_append_null; Some(v) }},
-                ReturnTypeKind::Option => quote! {{ #error_append_null; v }},
-                ReturnTypeKind::Result => {
-                    quote! { match v {
-                        Ok(x) => { error_builder.append_null(); Some(x) },
-                        Err(e) => { error_builder.append_value(e.to_string()); None }
-                    } }
-                }
-                ReturnTypeKind::ResultOption => {
-                    quote! { match v {
+            let element = if let Some(convert_output) = self.gen_convert_output() {
+                let result = match user_fn.iterator_item_kind.clone().unwrap() {
+                    ReturnTypeKind::T => quote! { Ok(Some(v)) },
+                    ReturnTypeKind::Option => quote! { Ok(v) },
+                    ReturnTypeKind::Result => quote! { v.map(Some).map_err(|e| e.to_string()) },
+                    ReturnTypeKind::ResultOption => quote! { v.map_err(|e| e.to_string()) },
+                };
+                quote! {{
+                    let r: ::std::result::Result<_, String> = #result;
+                    match r #convert_output {
                         Ok(x) => { error_builder.append_null(); x },
-                        Err(e) => { error_builder.append_value(e.to_string()); None }
-                    } }
+                        Err(e) => { error_builder.append_value(e); None }
+                    }
+                }}
+            } else {
+                match user_fn.iterator_item_kind.clone().unwrap() {
+                    ReturnTypeKind::T => quote! {{ #error_append_null; Some(v) }},
+                    ReturnTypeKind::Option => quote! {{ #error_append_null; v }},
+                    ReturnTypeKind::Result => {
+                        quote! { match v {
+                            Ok(x) => { error_builder.append_null(); Some(x) },
+                            Err(e) => { error_builder.append_value(e.to_string()); None }
+                        } }
+                    }
+                    ReturnTypeKind::ResultOption => {
+                        quote! { match v {
+                            Ok(x) => { error_builder.append_null(); x },
+                            Err(e) => { error_builder.append_value(e.to_string()); None }
+                        } }
+                    }
                 }
             };
 
-            let error_field = user_fn.has_error().then(|| {
-                quote! { Field::new("error", DataType::Utf8, true), }
+            let error_field = self.has_error(user_fn).then(|| {
+                let name = self.error_column.as_deref().unwrap_or("error");
+                quote! { Field::new(#name, DataType::Utf8, true), }
             });
-            let let_error_builder = user_fn.has_error().then(|| {
-                quote! { let mut error_builder = StringBuilder::with_capacity(input.num_rows(), input.num_rows() * 16); }
+            let let_error_builder = self.has_error(user_fn).then(|| {
+                quote! { let mut error_builder = ::arrow_udf::codegen::ErrorBuilder::with_capacity(input.num_rows()); }
             });
-            let error_array = user_fn.has_error().then(|| {
+            let error_array = self.has_error(user_fn).then(|| {
                 quote! { Arc::new(error_builder.finish()) }
             });
+            let finish = self.gen_finish();
+            let no_row_index = self.no_row_index;
+            let (row_field, let_index_builder, num_rows, index_array, reset_num_rows) =
+                match no_row_index {
+                    true => (
+                        quote! {},
+                        quote! { let mut num_rows = 0usize; },
+                        quote! { num_rows },
+                        quote! {},
+                        quote! { num_rows = 0; },
+                    ),
+                    false => (
+                        quote! { Field::new("row", DataType::Int32, true), },
+                        quote! { let mut index_builder = Int32Builder::with_capacity(input.num_rows()); },
+                        quote! { index_builder.len() },
+                        quote! { Arc::new(index_builder.finish()), },
+                        quote! {},
+                    ),
+                };
             let yield_batch = quote! {
-                let index_array = Arc::new(index_builder.finish());
-                let value_array = Arc::new(builder.finish());
-                yield_!(RecordBatch::try_new(SCHEMA.clone(), vec![index_array, value_array, #error_array]).unwrap());
+                let value_array = Arc::new(#finish);
+                yield_!(RecordBatch::try_new(SCHEMA.clone(), vec![#index_array value_array, #error_array]));
+                #reset_num_rows
             };
-            quote! {{
-                static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
-                    Arc::new(Schema::new(vec![
-                        Field::new("row", DataType::Int32, true),
-                        #ret_data_type,
-                        #error_field
-                    ]))
-                });
-                let mut index_builder = Int32Builder::with_capacity(input.num_rows());
-                let mut builder = #builder;
-                let builder = &mut builder;
-                #let_error_builder
-                for i in 0..input.num_rows() {
-                    #(let #inputs = unsafe { (!#arrays.is_null(i)).then(|| #arrays.value_unchecked(i)) };)*
-                    let Some(iter) = (#output) else {
-                        continue;
-                    };
-                    for v in iter {
-                        index_builder.append_value(i as i32);
-                        let v = #element;
-                        #append_output
-                        if index_builder.len() == BATCH_SIZE {
-                            #yield_batch
+            if let Some(max) = self.max_rows_per_input {
+                // build each batch from as many input rows as fit, without a generator
+                let name = &self.name;
+                let let_index_builder = match no_row_index {
+                    true => quote! { let mut num_rows = 0usize; },
+                    false => {
+                        quote! { let mut index_builder = Int32Builder::with_capacity(BATCH_SIZE); }
+                    }
+                };
+                quote! {{
+                    static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                        Arc::new(Schema::new(vec![
+                            #row_field
+                            #ret_data_type,
+                            #error_field
+                        ]))
+                    });
+                    let mut next_row = 0;
+                    // the error of a row is yielded after the batch of the rows before it
+                    let mut pending_error = None;
+                    std::iter::from_fn(move || {
+                        if let Some(err) = pending_error.take() {
+                            return Some(Err(err));
+                        }
+                        #let_index_builder
+                        let mut builder = #builder;
+                        let builder = &mut builder;
+                        #let_error_builder
+                        'rows: while next_row < input.num_rows() && #num_rows + #max <= BATCH_SIZE {
+                            let i = next_row;
+                            next_row += 1;
+                            #(#read_inputs)*
+                            #let_variadic_row
+                            let Some(iter) = (#output) else {
+                                continue;
+                            };
+                            for (n, v) in iter.into_iter().enumerate() {
+                                if n == #max {
+                                    // end the iterator after the error
+                                    next_row = input.num_rows();
+                                    pending_error = Some(::arrow_udf::codegen::arrow_schema::ArrowError::ComputeError(format!(
+                                        "table function `{}` produced more than {} rows for input row {}", #name, #max, i
+                                    )));
+                                    break 'rows;
+                                }
+                                #append_index
+                                let v = #element;
+                                #append_output
+                            }
+                        }
+                        if #num_rows == 0 {
+                            return pending_error.take().map(Err);
+                        }
+                        let value_array = Arc::new(#finish);
+                        Some(RecordBatch::try_new(SCHEMA.clone(), vec![#index_array value_array, #error_array]))
+                    })
+                }}
+            } else {
+                quote! {{
+                    static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                        Arc::new(Schema::new(vec![
+                            #row_field
+                            #ret_data_type,
+                            #error_field
+                        ]))
+                    });
+                    #let_index_builder
+                    let mut builder = #builder;
+                    let builder = &mut builder;
+                    #let_error_builder
+                    for i in 0..input.num_rows() {
+                        #(#read_inputs)*
+                        #let_variadic_row
+                        let Some(iter) = (#output) else {
+                            continue;
+                        };
+                        for v in iter {
+                            #append_index
+                            let v = #element;
+                            #append_output
+                            if #num_rows == BATCH_SIZE {
+                                #yield_batch
+                            }
                         }
                     }
-                }
-                if index_builder.len() > 0 {
-                    #yield_batch
-                }
-            }}
+                    if #num_rows > 0 {
+                        #yield_batch
+                    }
+                }}
+            }
         } else if let Some(batch_fn) = &self.batch_fn {
             if variadic {
                 return Err(Error::new(
@@ -301,11 +718,85 @@ impl FunctionAttr {
                 let c = #fn_name(#(#arrays),*);
                 let array = Arc::new(c);
             }
+        } else if self.ret == "any" {
+            if user_fn.write {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "`&mut Write` can not be used for functions returning `any`",
+                ));
+            }
+            // the values are copied from the `any` arguments, whose type is known only at runtime
+            let generic_loop = quote! {
+                let any_data = [#(#any_arrays.to_data()),*];
+                let mut builder = ::arrow_udf::types::AnyBuilder::new(&any_data, input.num_rows())?;
+                for i in 0..input.num_rows() {
+                    #(#read_inputs)*
+                    #let_variadic_row
+                    let v = #output;
+                    builder.append_option(v);
+                }
+                let array = builder.finish();
+            };
+            if self.args.len() == 1
+                && user_fn.is_pure()
+                && user_fn.generic > 0
+                && !variadic
+                && self.defaults.is_empty()
+            {
+                // SIMD optimization for primitive types:
+                // the generic function is instantiated for the native type of each primitive type,
+                // and with `AnyValue` for other types.
+                let arms = [
+                    "Int8", "Int16", "Int32", "Int64", "UInt8", "UInt16", "UInt32", "UInt64",
+                    "Float16", "Float32", "Float64",
+                ]
+                .map(|ty| {
+                    let data_type = format_ident!("{ty}");
+                    let primitive_type = format_ident!("{ty}Type");
+                    quote! {
+                        DataType::#data_type => {
+                            let a0 = arrow_array::cast::AsArray::as_primitive::<arrow_array::types::#primitive_type>(a0);
+                            let c: PrimitiveArray<arrow_array::types::#primitive_type> = arrow_arith::arity::unary(a0, #user_fn_name);
+                            Arc::new(c)
+                        }
+                    }
+                });
+                quote! {
+                    let array: ArrayRef = match a0.data_type() {
+                        #(#arms)*
+                        _ => {
+                            #generic_loop
+                            array
+                        }
+                    };
+                }
+            } else {
+                generic_loop
+            }
+        } else if self.ret == "boolean"
+            && self.args.iter().all(|ty| ty == "boolean")
+            && matches!(self.args.len(), 1 | 2)
+            && user_fn.is_pure()
+            && !variadic
+            && self.defaults.is_empty()
+        {
+            // bitwise operations for boolean types
+            match self.args.len() {
+                1 => quote! {
+                    let c = ::arrow_udf::codegen::boolean_unary(a0, #user_fn_name);
+                    let array = Arc::new(c);
+                },
+                _ => quote! {
+                    let c = ::arrow_udf::codegen::boolean_binary(a0, a1, #user_fn_name)?;
+                    let array = Arc::new(c);
+                },
+            }
         } else if types::is_primitive(&self.ret)
             && self.args.iter().all(|ty| types::is_primitive(ty))
             && self.args.len() <= 2
             && user_fn.is_pure()
             && !variadic
+            && self.defaults.is_empty()
         {
             // SIMD optimization for primitive types
             match self.args.len() {
@@ -327,13 +818,25 @@ impl FunctionAttr {
             }
         } else {
             // no optimization
-            let builder = builder(&self.ret);
+            let (let_any_data, builder) = if self.ret == "any[]" {
+                // the element type is the data type of the `any` arguments, known only at runtime
+                (
+                    Some(quote! { let any_data = [#(#any_arrays.to_data()),*]; }),
+                    quote! { ::arrow_udf::types::AnyListBuilder::new(&any_data, input.num_rows())? },
+                )
+            } else {
+                (None, builder(&self.ret))
+            };
             // append the `output` to the `builder`
             let append_output = if user_fn.write {
-                if self.ret != "string" && self.ret != "binary" {
+                // json and decimal values are also written to a `StringBuilder`
+                if !matches!(
+                    self.ret.as_str(),
+                    "string" | "binary" | "json" | "json_text" | "decimal"
+                ) {
                     return Err(Error::new(
                         Span::call_site(),
-                        "`&mut Write` can only be used for functions that return `string` or `binary`",
+                        "`&mut Write` can only be used for functions that return `string`, `binary`, `json`, `json_text` or `decimal`",
                     ));
                 }
                 quote! {{
@@ -344,20 +847,23 @@ impl FunctionAttr {
                     }
                 }}
             } else {
-                let append = gen_append(&self.ret);
+                let append = self.gen_append_output();
                 quote! {{
                     let v = #output;
                     #append
                 }}
             };
+            let finish = self.gen_finish();
             quote! {
+                #let_any_data
                 let mut builder = #builder;
                 let builder = &mut builder;
                 for i in 0..input.num_rows() {
-                    #(let #inputs = unsafe { (!#arrays.is_null(i)).then(|| #arrays.value_unchecked(i)) };)*
+                    #(#read_inputs)*
+                    #let_variadic_row
                     #append_output
                 }
-                let array = Arc::new(builder.finish());
+                let array = Arc::new(#finish);
             }
         };
 
@@ -366,35 +872,65 @@ impl FunctionAttr {
                 #eval
             }
         } else {
-            let error_field = user_fn.has_error().then(|| {
-                quote! { Field::new("error", DataType::Utf8, true), }
+            let error_field = self.has_error(user_fn).then(|| {
+                let name = self.error_column.as_deref().unwrap_or("error");
+                quote! { Field::new(#name, DataType::Utf8, true), }
             });
-            let let_error_builder = user_fn.has_error().then(|| {
-                quote! { let mut error_builder = StringBuilder::with_capacity(input.num_rows(), input.num_rows() * 16); }
+            let let_error_builder = self.has_error(user_fn).then(|| {
+                quote! { let mut error_builder = ::arrow_udf::codegen::ErrorBuilder::with_capacity(input.num_rows()); }
             });
-            let error_array = user_fn.has_error().then(|| {
+            let error_array = self.has_error(user_fn).then(|| {
                 quote! { Arc::new(error_builder.finish()) }
             });
+            let schema = if self.ret == "any[]" || self.ret == "any" {
+                // the (element) type depends on the input, so the schema can not be static
+                let name = self.output_name.as_ref().unwrap_or(&self.name);
+                quote! {
+                    Arc::new(Schema::new(vec![
+                        Field::new(#name, array.data_type().clone(), #nullable),
+                        #error_field
+                    ]))
+                }
+            } else {
+                quote! {{
+                    static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                        Arc::new(Schema::new(vec![#ret_data_type, #error_field]))
+                    });
+                    SCHEMA.clone()
+                }}
+            };
             quote! {
                 #let_error_builder
                 #eval
 
-                static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
-                    Arc::new(Schema::new(vec![#ret_data_type, #error_field]))
-                });
-                Ok(RecordBatch::try_new(SCHEMA.clone(), vec![array, #error_array]).unwrap())
+                Ok(RecordBatch::try_new(#schema, vec![array, #error_array])?)
             }
         };
 
-        // downcast input arrays
-        let downcast_arrays = quote! {
-            #(
-                let #arrays: &#arg_arrays = input.column(#children_indices).as_any().downcast_ref()
-                    .ok_or_else(|| ::arrow_udf::codegen::arrow_schema::ArrowError::CastError(
-                        format!("expect {} for the {}-th argument", stringify!(#arg_arrays), #children_indices)
-                    ))?;
-            )*
-        };
+        let downcast_arrays = self.gen_downcast_arrays(num_args, self.batch_fn.is_none());
+
+        // for pure unary functions on dictionary-encoded input,
+        // evaluate the function on the dictionary values only and keep the keys.
+        let dictionary_fast_path = (!self.is_table_function
+            && !variadic
+            && num_args == 1
+            && !self.volatile
+            && user_fn.is_pure()
+            && !self.has_fallible_conversion())
+        .then(|| {
+            quote! {
+                if let Some(dict) = ::arrow_udf::codegen::arrow_array::cast::AsArray::as_any_dictionary_opt(input.column(0)) {
+                    let field = input.schema().field(0).clone().with_data_type(dict.values().data_type().clone());
+                    let values = ::arrow_udf::codegen::arrow_array::RecordBatch::try_new(
+                        ::std::sync::Arc::new(::arrow_udf::codegen::arrow_schema::Schema::new(vec![field])),
+                        vec![dict.values().clone()],
+                    )?;
+                    let output = #eval_fn_name(&values)?;
+                    let array = ::arrow_udf::codegen::take_dictionary_values(dict, output.column(0))?;
+                    return ::arrow_udf::codegen::arrow_array::RecordBatch::try_new(output.schema(), vec![array]);
+                }
+            }
+        });
 
         // the function body
         let body = quote! {
@@ -412,19 +948,69 @@ impl FunctionAttr {
             use ::arrow_udf::codegen::rust_decimal;
             use ::arrow_udf::codegen::serde_json;
 
+            #static_context
+            #let_session
             #eval_and_return
         };
 
+        // table functions with `max_rows_per_input` return a plain iterator instead of a generator
+        let make_iterator = match self.max_rows_per_input {
+            Some(_) => quote! { Ok(Box::new({ #body })) },
+            None => quote! {
+                use ::arrow_udf::codegen::genawaiter::{rc::gen, yield_};
+                Ok(Box::new(gen!({ #body }).into_iter()))
+            },
+        };
+        // evaluate the function on a new input, whose output of table functions is collected
+        // because the iterator can not borrow the new input
+        let reevaluate = if self.is_table_function {
+            quote! {
+                let batches = #eval_fn_name(&input)?.collect::<Vec<_>>();
+                return Ok(Box::new(batches.into_iter()));
+            }
+        } else {
+            quote! { return #eval_fn_name(&input) #await_; }
+        };
+        let decode_run_end_encoded = quote! {
+            // decode run-end encoded input and evaluate the function on plain arrays
+            if let Some(input) = ::arrow_udf::codegen::decode_run_end_encoded(input)? {
+                #reevaluate
+            }
+        };
+        let rescale_decimals = self.gen_rescale_decimals(&reevaluate);
+        let prelude = quote! {
+            #decode_run_end_encoded
+            #rescale_decimals
+            #dictionary_fast_path
+            #downcast_arrays
+            #body
+        };
         Ok(if self.is_table_function {
             quote! {
                 fn #eval_fn_name<'a>(input: &'a ::arrow_udf::codegen::arrow_array::RecordBatch)
-                    -> ::arrow_udf::Result<Box<dyn Iterator<Item = ::arrow_udf::codegen::arrow_array::RecordBatch> + 'a>>
+                    -> ::arrow_udf::Result<Box<dyn Iterator<Item = ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>> + 'a>>
                 {
                     const BATCH_SIZE: usize = 1024;
-                    use ::arrow_udf::codegen::genawaiter::{rc::gen, yield_};
                     use ::arrow_udf::codegen::arrow_array::array::*;
+                    #decode_run_end_encoded
+                    #rescale_decimals
                     #downcast_arrays
-                    Ok(Box::new(gen!({ #body }).into_iter()))
+                    #make_iterator
+                }
+            }
+        } else if user_fn.async_ {
+            // the future is boxed to be stored in the registry and to allow recursive calls
+            let async_fn_name = format_ident!("{}_async", eval_fn_name);
+            quote! {
+                fn #eval_fn_name<'a>(input: &'a ::arrow_udf::codegen::arrow_array::RecordBatch)
+                    -> ::arrow_udf::BoxFuture<'a, ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>>
+                {
+                    async fn #async_fn_name(input: &::arrow_udf::codegen::arrow_array::RecordBatch)
+                        -> ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>
+                    {
+                        #prelude
+                    }
+                    Box::pin(#async_fn_name(input))
                 }
             }
         } else {
@@ -432,12 +1018,588 @@ impl FunctionAttr {
                 fn #eval_fn_name(input: &::arrow_udf::codegen::arrow_array::RecordBatch)
                     -> ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>
                 {
-                    #downcast_arrays
-                    #body
+                    #prelude
+                }
+            }
+        })
+    }
+
+    /// Generate code to rescale `decimal128` and `decimal256` input columns to the expected scale
+    /// and evaluate the function on the rescaled input.
+    ///
+    /// `reevaluate` is the code to evaluate the function on `input` and return.
+    fn gen_rescale_decimals(&self, reevaluate: &TokenStream2) -> TokenStream2 {
+        let decimals = self
+            .args
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ty)| {
+                let (precision, scale) = types::decimal128_precision_scale(ty)
+                    .or_else(|| types::decimal256_precision_scale(ty))?;
+                Some(quote! { (#i, #precision, #scale) })
+            })
+            .collect::<Vec<_>>();
+        if decimals.is_empty() {
+            return quote! {};
+        }
+        let round = self.round_decimal;
+        quote! {
+            // rescale decimal input to the expected scale
+            if let Some(input) = ::arrow_udf::codegen::rescale_decimal_columns(input, &[#(#decimals),*], #round)? {
+                #reevaluate
+            }
+        }
+    }
+
+    /// Generate code to downcast the first `num_args` input columns to `a0`, `a1`, etc.
+    ///
+    /// If `view` is set, `string` and `binary` arguments also accept view arrays.
+    fn gen_downcast_arrays(&self, num_args: usize, view: bool) -> TokenStream2 {
+        let check_arg_metadata = self.gen_check_arg_metadata();
+        let downcasts = (0..num_args).map(|i| {
+            let array = format_ident!("a{i}");
+            if self.args[i] == "any" {
+                return quote! { let #array = input.column(#i); };
+            }
+            // string and binary arguments also accept view arrays if `view` is set
+            let view_array = match self.args[i].as_str() {
+                "string" if view => {
+                    Some((quote! { StringArrayOrView }, "StringArray or StringViewArray"))
+                }
+                "binary" if view => {
+                    Some((quote! { BinaryArrayOrView }, "BinaryArray or BinaryViewArray"))
+                }
+                _ => None,
+            };
+            if let Some((view_array, expected)) = view_array {
+                return quote! {
+                    let #array = ::arrow_udf::codegen::#view_array::try_new(input.column(#i).as_ref())
+                        .ok_or_else(|| ::arrow_udf::codegen::arrow_schema::ArrowError::CastError(
+                            format!("expect {} for the {}-th argument", #expected, #i)
+                        ))?;
+                };
+            }
+            let arg_array = format_ident!("{}", types::array_type(&self.args[i]));
+            // the values of decimal128 and decimal256 are read as is, so the scale must be the same.
+            // scalar and table functions have rescaled the input before.
+            let check_scale = types::decimal128_precision_scale(&self.args[i])
+                .or_else(|| types::decimal256_precision_scale(&self.args[i]))
+                .map(|(_, scale)| {
+                    quote! {
+                        if #array.scale() != #scale {
+                            return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                                "expect scale {} for the {}-th argument, got {}", #scale, #i, #array.scale()
+                            )));
+                        }
+                    }
+                });
+            // the values of fixed size binary are converted to arrays, so the width must be the same
+            let width = fixed_size_binary_width(&self.args[i]);
+            let check_width = width.map(|width| {
+                quote! {
+                    if #array.value_length() != #width {
+                        return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                            "expect width {} for the {}-th argument, got {}", #width, #i, #array.value_length()
+                        )));
+                    }
                 }
+            });
+            // the keys and values of maps are downcast for each row, so their types are checked here
+            let check_map_entries = types::map_key_value(&self.args[i]).map(|(key, value)| {
+                let key_array = format_ident!("{}", types::array_type(key));
+                let check_values = (value != "any").then(|| {
+                    let value_array = format_ident!("{}", types::array_type(value));
+                    quote! {
+                        if #array.entries().column(1).as_any().downcast_ref::<#value_array>().is_none() {
+                            return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                                "expect {} values for the {}-th argument", stringify!(#value_array), #i
+                            )));
+                        }
+                    }
+                });
+                quote! {
+                    if #array.entries().column(0).as_any().downcast_ref::<#key_array>().is_none() {
+                        return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                            "expect {} keys for the {}-th argument", stringify!(#key_array), #i
+                        )));
+                    }
+                    #check_values
+                }
+            });
+            // the values of maps are converted for each row, so their width and range are checked here
+            let check_map_values = types::map_key_value(&self.args[i]).and_then(|(_, value)| {
+                if let Some(width) = fixed_size_binary_width(value) {
+                    Some(quote! {
+                        if let Some(values) = #array.entries().column(1).as_any().downcast_ref::<FixedSizeBinaryArray>() {
+                            if values.value_length() != #width {
+                                return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                                    "expect width {} for the values of the {}-th argument, got {}", #width, #i, values.value_length()
+                                )));
+                            }
+                        }
+                    })
+                } else if let Some((_, scale)) = types::decimal128_precision_scale(value) {
+                    let unsigned_scale = scale as u32;
+                    Some(quote! {
+                        if let Some(values) = #array.entries().column(1).as_any().downcast_ref::<Decimal128Array>() {
+                            if values.scale() != #scale {
+                                return Err(::arrow_udf::codegen::arrow_schema::ArrowError::CastError(format!(
+                                    "expect scale {} for the values of the {}-th argument, got {}", #scale, #i, values.scale()
+                                )));
+                            }
+                            let offsets = #array.value_offsets();
+                            for j in offsets[0] as usize..offsets[offsets.len() - 1] as usize {
+                                if values.is_valid(j) {
+                                    ::arrow_udf::codegen::decimal128_to_decimal(values.value(j), #unsigned_scale)?;
+                                }
+                            }
+                        }
+                    })
+                } else {
+                    None
+                }
+            });
+            quote! {
+                let #array: &#arg_array = input.column(#i).as_any().downcast_ref()
+                    .ok_or_else(|| ::arrow_udf::codegen::arrow_schema::ArrowError::CastError(
+                        format!("expect {} for the {}-th argument", stringify!(#arg_array), #i)
+                    ))?;
+                #check_scale
+                #check_width
+                #check_map_entries
+                #check_map_values
+            }
+        });
+        quote! {
+            #check_arg_metadata
+            #(#downcasts)*
+        }
+    }
+
+    /// Generate code to check the field metadata of arguments specified by `arg_metadata`.
+    fn gen_check_arg_metadata(&self) -> TokenStream2 {
+        let checks = self.arg_metadata.iter().map(|(index, key, value)| {
+            quote! {
+                match input.schema().field(#index).metadata().get(#key) {
+                    Some(v) if v == #value => {}
+                    actual => return Err(::arrow_udf::codegen::arrow_schema::ArrowError::InvalidArgumentError(format!(
+                        "expect metadata {:?} = {:?} for the {}-th argument, got {:?}",
+                        #key, #value, #index, actual,
+                    ))),
+                }
+            }
+        });
+        quote! { #(#checks)* }
+    }
+
+    /// Generate a table function whose user function returns record batches directly.
+    ///
+    /// Each yielded batch must match the declared schema `(row int32, return_type)`.
+    /// Field names and metadata are replaced by the declared ones. If the number or types of the
+    /// columns are different, the iterator yields a `SchemaError`.
+    fn generate_batches_function(
+        &self,
+        user_fn: &UserFunctionAttr,
+        eval_fn_name: &Ident,
+    ) -> Result<TokenStream2> {
+        if !self.is_table_function {
+            return Err(Error::new(
+                Span::call_site(),
+                "`batches` can only be used for table functions",
+            ));
+        }
+        let name = &self.name;
+        let user_fn_name = format_ident!("{}", user_fn.name);
+        let ret_data_type = self.return_field(user_fn);
+        let row_field = (!self.no_row_index).then(|| {
+            quote! { Field::new("row", DataType::Int32, true), }
+        });
+        let batches = match user_fn.return_type_kind {
+            ReturnTypeKind::T => quote! { #user_fn_name(input) },
+            ReturnTypeKind::Result => quote! {
+                #user_fn_name(input).map_err(|e| ::arrow_udf::Error::ComputeError(e.to_string()))?
+            },
+            _ => {
+                return Err(Error::new(
+                    user_fn.return_type_span,
+                    "functions with `batches` must return `impl IntoIterator<Item = RecordBatch>` or `Result` of it",
+                ))
+            }
+        };
+        Ok(quote! {
+            fn #eval_fn_name<'a>(input: &'a ::arrow_udf::codegen::arrow_array::RecordBatch)
+                -> ::arrow_udf::Result<Box<dyn Iterator<Item = ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>> + 'a>>
+            {
+                use ::std::sync::Arc;
+                use ::arrow_udf::codegen::arrow_array::RecordBatch;
+                use ::arrow_udf::codegen::arrow_schema::{self, Schema, SchemaRef, Field, DataType, IntervalUnit, TimeUnit};
+                use ::arrow_udf::codegen::once_cell;
+
+                static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                    Arc::new(Schema::new(vec![
+                        #row_field
+                        #ret_data_type,
+                    ]))
+                });
+                let batches = #batches;
+                Ok(Box::new(batches.into_iter().map(|batch| {
+                    if batch.schema() == *SCHEMA {
+                        return Ok(batch);
+                    }
+                    RecordBatch::try_new(SCHEMA.clone(), batch.columns().to_vec()).map_err(|e| {
+                        arrow_schema::ArrowError::SchemaError(format!(
+                            "table function `{}` yielded a batch that does not match the declared schema: {}", #name, e
+                        ))
+                    })
+                })))
+            }
+        })
+    }
+
+    /// Generate a scalar function whose user function takes the whole argument arrays.
+    ///
+    /// The user function returns an `ArrayRef` (or a `Result` of it) with one value for each row.
+    /// Errors are returned from the eval function instead of being written to an error column.
+    fn generate_columnar_function(
+        &self,
+        user_fn: &UserFunctionAttr,
+        eval_fn_name: &Ident,
+    ) -> Result<TokenStream2> {
+        let error = |msg: &str| Err(Error::new(Span::call_site(), msg));
+        if self.is_table_function {
+            return error("`columnar` can only be used for scalar functions");
+        }
+        if self.batch_fn.is_some() {
+            return error("`columnar` and `batch_fn` cannot be used together");
+        }
+        if !self.defaults.is_empty() {
+            return error("`columnar` is not supported for arguments with default values");
+        }
+        if self.ret == "any[]" {
+            return error("`columnar` is not supported for functions returning `anyarray`");
+        }
+        if user_fn.async_ || user_fn.write || user_fn.context.is_some() || user_fn.session {
+            return error(
+                "`columnar` functions can not be async or take `&Context`, `&SessionContext` or `&mut impl Write`",
+            );
+        }
+        let variadic = matches!(self.args.last(), Some(t) if t == "...");
+        let num_args = self.args.len() - if variadic { 1 } else { 0 };
+        let name = &self.name;
+        let user_fn_name = format_ident!("{}", user_fn.name);
+        let arrays = (0..num_args).map(|i| format_ident!("a{i}"));
+        // the trailing columns after the fixed arguments are passed as `&[ArrayRef]`
+        let variadic_arrays = variadic.then(|| quote! { &input.columns()[#num_args..], });
+        let output = quote! { #user_fn_name(#(#arrays,)* #variadic_arrays) };
+        let output = match user_fn.return_type_kind {
+            ReturnTypeKind::T => output,
+            ReturnTypeKind::Result => quote! {
+                #output.map_err(|e| ::arrow_udf::Error::ComputeError(e.to_string()))?
+            },
+            _ => {
+                return Err(Error::new(
+                    user_fn.return_type_span,
+                    "functions with `columnar` must return `ArrayRef` or `Result<ArrayRef>`",
+                ))
+            }
+        };
+        let downcast_arrays = self.gen_downcast_arrays(num_args, false);
+        let rescale_decimals = self.gen_rescale_decimals(&quote! { return #eval_fn_name(&input); });
+        let schema = if self.ret == "any" {
+            // the return type depends on the input, so the schema can not be static
+            let name = self.output_name.as_ref().unwrap_or(&self.name);
+            quote! {
+                let schema = Arc::new(Schema::new(vec![Field::new(#name, array.data_type().clone(), true)]));
+            }
+        } else {
+            let ret_data_type = self.return_field(user_fn);
+            quote! {
+                static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                    Arc::new(Schema::new(vec![#ret_data_type]))
+                });
+                if array.data_type() != SCHEMA.field(0).data_type() {
+                    return Err(::arrow_udf::Error::ComputeError(format!(
+                        "function `{}` returned an array of {}, expected {}", #name, array.data_type(), SCHEMA.field(0).data_type()
+                    )));
+                }
+                let schema = SCHEMA.clone();
+            }
+        };
+        Ok(quote! {
+            fn #eval_fn_name(input: &::arrow_udf::codegen::arrow_array::RecordBatch)
+                -> ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch>
+            {
+                use ::std::sync::Arc;
+                use ::arrow_udf::codegen::arrow_array::RecordBatch;
+                use ::arrow_udf::codegen::arrow_array::array::*;
+                use ::arrow_udf::codegen::arrow_schema::{self, Schema, SchemaRef, Field, DataType, IntervalUnit, TimeUnit};
+                use ::arrow_udf::codegen::once_cell;
+
+                // decode run-end encoded input and evaluate the function on plain arrays
+                if let Some(input) = ::arrow_udf::codegen::decode_run_end_encoded(input)? {
+                    return #eval_fn_name(&input);
+                }
+                #rescale_decimals
+                #downcast_arrays
+                let array: ArrayRef = #output;
+                if array.len() != input.num_rows() {
+                    return Err(::arrow_udf::Error::ComputeError(format!(
+                        "function `{}` returned {} rows, expected {}", #name, array.len(), input.num_rows()
+                    )));
+                }
+                #schema
+                RecordBatch::try_new(schema, vec![array])
+            }
+        })
+    }
+
+    /// Generate a window function.
+    ///
+    /// The user function is called for each row of the partition with the argument arrays,
+    /// the index of the row and its frame.
+    fn generate_window_function(
+        &self,
+        user_fn: &UserFunctionAttr,
+        eval_fn_name: &Ident,
+    ) -> Result<TokenStream2> {
+        if self.is_table_function {
+            return Err(Error::new(
+                Span::call_site(),
+                "window functions can not return `setof`",
+            ));
+        }
+        if matches!(self.args.last(), Some(t) if t == "...") {
+            return Err(Error::new(
+                Span::call_site(),
+                "variadic arguments are not supported for window functions",
+            ));
+        }
+        let user_fn_name = format_ident!("{}", user_fn.name);
+        let downcast_arrays = self.gen_downcast_arrays(self.args.len(), false);
+        let arrays = (0..self.args.len())
+            .map(|i| format_ident!("a{i}"))
+            .collect_vec();
+        let ret_data_type = self.return_field(user_fn);
+        let builder = builder(&self.ret);
+        let append = self.gen_append_output();
+        let finish = self.gen_finish();
+
+        let output = quote! { #user_fn_name(#(#arrays,)* i, frame.clone()) };
+        let output = if let Some(convert_output) = self.gen_convert_output() {
+            let result = match user_fn.return_type_kind {
+                ReturnTypeKind::T => quote! { Ok(Some(#output)) },
+                ReturnTypeKind::Option => quote! { Ok(#output) },
+                ReturnTypeKind::Result => quote! { #output.map(Some).map_err(|e| e.to_string()) },
+                ReturnTypeKind::ResultOption => quote! { #output.map_err(|e| e.to_string()) },
+            };
+            quote! {{
+                let r: ::std::result::Result<_, String> = #result;
+                match r #convert_output {
+                    Ok(x) => { error_builder.append_null(); x },
+                    Err(e) => { error_builder.append_value(e); None }
+                }
+            }}
+        } else {
+            match user_fn.return_type_kind {
+                ReturnTypeKind::T => quote! { Some(#output) },
+                ReturnTypeKind::Option => output,
+                ReturnTypeKind::Result => quote! {
+                    match #output {
+                        Ok(x) => { error_builder.append_null(); Some(x) },
+                        Err(e) => { error_builder.append_value(e.to_string()); None }
+                    }
+                },
+                ReturnTypeKind::ResultOption => quote! {
+                    match #output {
+                        Ok(x) => { error_builder.append_null(); x },
+                        Err(e) => { error_builder.append_value(e.to_string()); None }
+                    }
+                },
+            }
+        };
+        let error_field = self.has_error(user_fn).then(|| {
+            let name = self.error_column.as_deref().unwrap_or("error");
+            quote! { Field::new(#name, DataType::Utf8, true), }
+        });
+        let let_error_builder = self.has_error(user_fn).then(|| {
+            quote! { let mut error_builder = ::arrow_udf::codegen::ErrorBuilder::with_capacity(input.num_rows()); }
+        });
+        let error_array = self.has_error(user_fn).then(|| {
+            quote! { Arc::new(error_builder.finish()) }
+        });
+
+        Ok(quote! {
+            fn #eval_fn_name(
+                input: &::arrow_udf::codegen::arrow_array::RecordBatch,
+                frames: &[::std::ops::Range<usize>],
+            ) -> ::arrow_udf::Result<::arrow_udf::codegen::arrow_array::RecordBatch> {
+                use ::std::sync::Arc;
+                use ::arrow_udf::Error;
+                use ::arrow_udf::codegen::arrow_array;
+                use ::arrow_udf::codegen::arrow_array::RecordBatch;
+                use ::arrow_udf::codegen::arrow_array::array::*;
+                use ::arrow_udf::codegen::arrow_array::builder::*;
+                use ::arrow_udf::codegen::arrow_schema::{self, Schema, SchemaRef, Field, DataType, IntervalUnit, TimeUnit};
+                use ::arrow_udf::codegen::once_cell;
+
+                if frames.len() != input.num_rows() {
+                    return Err(Error::InvalidArgumentError(format!(
+                        "expect {} frames, got {}", input.num_rows(), frames.len()
+                    )));
+                }
+                #downcast_arrays
+                #let_error_builder
+                let mut builder = #builder;
+                let builder = &mut builder;
+                for (i, frame) in frames.iter().enumerate() {
+                    let v = #output;
+                    #append
+                }
+                let array = Arc::new(#finish);
+
+                static SCHEMA: once_cell::sync::Lazy<SchemaRef> = once_cell::sync::Lazy::new(|| {
+                    Arc::new(Schema::new(vec![#ret_data_type, #error_field]))
+                });
+                RecordBatch::try_new(SCHEMA.clone(), vec![array, #error_array])
+            }
+        })
+    }
+
+    /// Returns true if the arguments or the return value may fail to convert between arrow values
+    /// and Rust types. Such errors are reported in the error column like errors of the function.
+    fn has_fallible_conversion(&self) -> bool {
+        if self.columnar || self.batches || self.batch_fn.is_some() {
+            return false;
+        }
+        // window functions take the argument arrays
+        let args = !self.is_window
+            && self
+                .args
+                .iter()
+                .any(|ty| types::decimal128_precision_scale(ty).is_some());
+        args || self.gen_convert_output().is_some()
+    }
+
+    /// Returns true if the output has an error column.
+    fn has_error(&self, user_fn: &UserFunctionAttr) -> bool {
+        user_fn.has_error() || self.has_fallible_conversion()
+    }
+
+    /// Generate code to convert the `Result<Option<T>, String>` of the return value to the arrow
+    /// value, or `None` if the conversion can not fail.
+    fn gen_convert_output(&self) -> Option<TokenStream2> {
+        let convert = gen_convert_value(&self.ret)?;
+        Some(quote! {
+            .and_then(|v| v.map(|v| #convert).transpose())
+        })
+    }
+
+    /// Generate code to append the `v: Option<T>` returned by the function to the `builder`.
+    ///
+    /// The value has been converted if there is a fallible conversion.
+    fn gen_append_output(&self) -> TokenStream2 {
+        gen_append(&self.ret)
+    }
+
+    /// Returns the index of the first `any` or `map<K, any>` argument, and whether it is a map.
+    fn any_arg(&self) -> Option<(usize, bool)> {
+        self.args.iter().enumerate().find_map(|(i, ty)| {
+            if ty == "any" {
+                Some((i, false))
+            } else if types::map_key_value(ty).is_some_and(|(_, v)| v == "any") {
+                Some((i, true))
+            } else {
+                None
             }
         })
     }
+
+    /// Check that `any` and `anyarray` are only used where they are supported.
+    ///
+    /// `any` can only be used for arguments of scalar functions, and `any` or `anyarray` for their
+    /// return value if there is at least one `any` argument. `columnar` functions can also take
+    /// `map<K, any>` arguments, and the return type is inferred from the first `any` or
+    /// `map<K, any>` argument.
+    fn check_any_types(&self) -> Result<()> {
+        let has_any_arg = self.args.iter().any(|ty| ty == "any");
+        let has_any_map = self
+            .args
+            .iter()
+            .any(|ty| types::map_key_value(ty).is_some_and(|(_, v)| v == "any"));
+        let error = |msg: &str| Err(Error::new(Span::call_site(), msg));
+        if !has_any_arg && !has_any_map && self.ret != "any[]" && self.ret != "any" {
+            return Ok(());
+        }
+        if self.is_table_function || self.is_window || self.batches {
+            return error("`any` and `anyarray` are only supported for scalar functions");
+        }
+        if self.batch_fn.is_some() {
+            return error("customized batch function is not supported for `any` arguments");
+        }
+        if self.args.iter().any(|ty| ty == "any[]") {
+            return error("`anyarray` arguments are not supported yet");
+        }
+        if has_any_map && !self.columnar {
+            return error("`map<K, any>` arguments are only supported for `columnar` functions");
+        }
+        if self.ret == "any" && self.any_arg().is_none() {
+            return error(
+                "functions returning `any` must have at least one `any` or `map<K, any>` argument",
+            );
+        }
+        if self.ret == "any[]" && !has_any_arg {
+            return error("functions returning `anyarray` must have at least one `any` argument");
+        }
+        Ok(())
+    }
+
+    /// Returns the `Field` of the return value.
+    fn return_field(&self, user_fn: &UserFunctionAttr) -> TokenStream2 {
+        let name = self.output_name.as_ref().unwrap_or(&self.name);
+        let field = match types::map_key_value(&self.ret) {
+            Some((key, value)) if self.keys_sorted => {
+                let data_type = map_data_type(key, value, true);
+                quote! { arrow_schema::Field::new(#name, #data_type, true) }
+            }
+            _ => field(name, &self.ret),
+        };
+        match self.output_nullable(user_fn) {
+            true => field,
+            false => quote! { #field.with_nullable(false) },
+        }
+    }
+
+    /// Returns whether the output column is nullable.
+    ///
+    /// Unless `nullable` is specified, only scalar functions that return a plain `T` and are
+    /// called on null input never return null.
+    fn output_nullable(&self, user_fn: &UserFunctionAttr) -> bool {
+        if let Some(nullable) = self.nullable {
+            return nullable;
+        }
+        let num_args = self.args.iter().filter(|t| *t != "...").count();
+        let never_null = !self.is_table_function
+            && !self.is_window
+            && !self.columnar
+            && self.batch_fn.is_none()
+            && !self.strict
+            && user_fn.return_type_kind == ReturnTypeKind::T
+            && user_fn.args_option.iter().take(num_args).all(|b| *b)
+            && !self.has_error(user_fn);
+        !never_null
+    }
+
+    /// Generate code to finish the `builder` of the return value.
+    fn gen_finish(&self) -> TokenStream2 {
+        if self.keys_sorted {
+            quote! {{
+                let (field, offsets, entries, nulls, _) = builder.finish().into_parts();
+                MapArray::new(field, offsets, entries, nulls, true)
+            }}
+        } else {
+            quote! { builder.finish() }
+        }
+    }
 }
 
 /// Returns a `Field` from type name.
@@ -445,20 +1607,38 @@ pub fn field(name: &str, ty: &str) -> TokenStream2 {
     let data_type = if let Some(ty) = ty.strip_suffix("[]") {
         let inner = field("item", ty);
         quote! { arrow_schema::DataType::List(Arc::new(#inner)) }
+    } else if let Some((key, value)) = types::map_key_value(ty) {
+        map_data_type(key, value, false)
     } else if let Some(s) = ty.strip_prefix("struct ") {
         let struct_type = format_ident!("{}", s);
         quote! { arrow_schema::DataType::Struct(#struct_type::fields()) }
+    } else if let Some((precision, scale)) = types::decimal128_precision_scale(ty) {
+        quote! { arrow_schema::DataType::Decimal128(#precision, #scale) }
+    } else if let Some((precision, scale)) = types::decimal256_precision_scale(ty) {
+        quote! { arrow_schema::DataType::Decimal256(#precision, #scale) }
+    } else if let Some(width) = types::fixed_size_binary_width(ty) {
+        quote! { arrow_schema::DataType::FixedSizeBinary(#width) }
     } else {
         let variant: TokenStream2 = types::data_type(ty).parse().unwrap();
         quote! { arrow_schema::DataType::#variant }
     };
     let with_metadata = match ty {
-        "json" => {
+        "json" | "json_text" => {
             quote! { .with_metadata([("ARROW:extension:name".into(), "arrowudf.json".into())].into()) }
         }
         "decimal" => {
             quote! { .with_metadata([("ARROW:extension:name".into(), "arrowudf.decimal".into())].into()) }
         }
+        "uuid" => {
+            quote! { .with_metadata([("ARROW:extension:name".into(), "arrow.uuid".into())].into()) }
+        }
+        "geometry" | "largegeometry" => {
+            quote! { .with_metadata([("ARROW:extension:name".into(), "geoarrow.wkb".into())].into()) }
+        }
+        // a placeholder that matches any type in the function registry
+        "any" => {
+            quote! { .with_metadata([("ARROW:extension:name".into(), "arrowudf.any".into())].into()) }
+        }
         _ => quote! {},
     };
     quote! {
@@ -466,8 +1646,50 @@ pub fn field(name: &str, ty: &str) -> TokenStream2 {
     }
 }
 
+/// Returns the width of `fixedsizebinary(n)` and `uuid`, which are stored in `FixedSizeBinary` arrays.
+fn fixed_size_binary_width(ty: &str) -> Option<i32> {
+    match ty {
+        "uuid" => Some(16),
+        ty => types::fixed_size_binary_width(ty),
+    }
+}
+
+/// Returns true if the type is `decimal128(p,s)` or `decimal256(p,s)`, whose input is rescaled.
+fn is_scaled_decimal(ty: &str) -> bool {
+    types::decimal128_precision_scale(ty).is_some()
+        || types::decimal256_precision_scale(ty).is_some()
+}
+
+/// Returns true if the type is a timestamp of any precision.
+fn is_timestamp(ty: &str) -> bool {
+    matches!(ty, "timestamp" | "timestamp(ns)")
+}
+
+/// Returns a map `DataType` from key and value type names.
+///
+/// The field names are consistent with `MapBuilder`.
+fn map_data_type(key: &str, value: &str, keys_sorted: bool) -> TokenStream2 {
+    let key = field("keys", key);
+    let value = field("values", value);
+    quote! {
+        arrow_schema::DataType::Map(
+            Arc::new(arrow_schema::Field::new(
+                "entries",
+                arrow_schema::DataType::Struct(vec![#key.with_nullable(false), #value].into()),
+                false,
+            )),
+            #keys_sorted,
+        )
+    }
+}
+
 /// Generate a builder for the given type.
 fn builder(ty: &str) -> TokenStream2 {
+    if let Some((key, value)) = types::map_key_value(ty) {
+        let key_builder = builder(key);
+        let value_builder = builder(value);
+        return quote! { MapBuilder::new(None, #key_builder, #value_builder) };
+    }
     match ty {
         // `NullBuilder::with_capacity` is deprecated since v52.0, use `NullBuilder::new` instead.
         "null" => quote! { NullBuilder::new() },
@@ -475,10 +1697,35 @@ fn builder(ty: &str) -> TokenStream2 {
         "binary" => quote! { BinaryBuilder::with_capacity(input.num_rows(), 1024) },
         "largestring" => quote! { LargeStringBuilder::with_capacity(input.num_rows(), 1024) },
         "largebinary" => quote! { LargeBinaryBuilder::with_capacity(input.num_rows(), 1024) },
+        "geometry" => quote! { BinaryBuilder::with_capacity(input.num_rows(), 1024) },
+        "largegeometry" => quote! { LargeBinaryBuilder::with_capacity(input.num_rows(), 1024) },
         "decimal" => {
             quote! { StringBuilder::with_capacity(input.num_rows(), input.num_rows() * 8) }
         }
-        "json" => quote! { StringBuilder::with_capacity(input.num_rows(), input.num_rows() * 8) },
+        "json" | "json_text" => {
+            quote! { StringBuilder::with_capacity(input.num_rows(), input.num_rows() * 8) }
+        }
+        s if s.starts_with("decimal128(") => {
+            let (precision, scale) = types::decimal128_precision_scale(s).unwrap();
+            quote! {
+                Decimal128Builder::with_capacity(input.num_rows())
+                    .with_precision_and_scale(#precision, #scale)
+                    .expect("valid precision and scale")
+            }
+        }
+        s if s.starts_with("decimal256(") => {
+            let (precision, scale) = types::decimal256_precision_scale(s).unwrap();
+            quote! {
+                Decimal256Builder::with_capacity(input.num_rows())
+                    .with_precision_and_scale(#precision, #scale)
+                    .expect("valid precision and scale")
+            }
+        }
+        s if s.starts_with("fixedsizebinary(") => {
+            let width = types::fixed_size_binary_width(s).unwrap();
+            quote! { FixedSizeBinaryBuilder::with_capacity(input.num_rows(), #width) }
+        }
+        "uuid" => quote! { FixedSizeBinaryBuilder::with_capacity(input.num_rows(), 16) },
         s if s.ends_with("[]") => {
             let values_builder = builder(ty.strip_suffix("[]").unwrap());
             quote! { ListBuilder::<Box<dyn ArrayBuilder>>::with_capacity(Box::new(#values_builder), input.num_rows()) }
@@ -505,6 +1752,49 @@ pub fn builder_type(ty: &str) -> TokenStream2 {
     }
 }
 
+/// Generate code to convert the `v: T` of a type with a fallible conversion to a
+/// `Result<_, String>` of the value to append, or `None` if the conversion can not fail.
+///
+/// `timestamp(ns)` and `decimal128` values are converted to integers, and lists and maps of them
+/// are collected into vectors of the converted values.
+fn gen_convert_value(ty: &str) -> Option<TokenStream2> {
+    if ty == "timestamp(ns)" {
+        Some(quote! {
+            ::arrow_udf::codegen::TimestampValue::to_nanos(v)
+                .ok_or_else(|| "timestamp out of range for nanoseconds".to_string())
+        })
+    } else if let Some((precision, scale)) = types::decimal128_precision_scale(ty) {
+        let scale = scale as u32;
+        Some(quote! {
+            ::arrow_udf::codegen::decimal_to_decimal128(v, #precision, #scale).map_err(|e| e.to_string())
+        })
+    } else if let Some(elem) = ty.strip_suffix("[]") {
+        let convert = gen_convert_value(elem)?;
+        Some(quote! {
+            v.into_iter().map(|v| #convert).collect::<::std::result::Result<Vec<_>, String>>()
+        })
+    } else if let Some((key, value)) = types::map_key_value(ty) {
+        let convert_key = gen_convert_value(key);
+        let convert_value = gen_convert_value(value);
+        if convert_key.is_none() && convert_value.is_none() {
+            return None;
+        }
+        let convert_key = convert_key.unwrap_or_else(|| quote! { Ok::<_, String>(v) });
+        let convert_value = convert_value.unwrap_or_else(|| quote! { Ok::<_, String>(v) });
+        Some(quote! {
+            v.into_iter()
+                .map(|(k, v)| -> ::std::result::Result<_, String> {
+                    let k = { let v = k; #convert_key }?;
+                    let v = v.map(|v| #convert_value).transpose()?;
+                    Ok((k, v))
+                })
+                .collect::<::std::result::Result<Vec<_>, String>>()
+        })
+    } else {
+        None
+    }
+}
+
 /// Generate code to append the `v: Option<T>` to the `builder`.
 fn gen_append(ty: &str) -> TokenStream2 {
     let append_value = gen_append_value(ty);
@@ -519,14 +1809,54 @@ fn gen_append(ty: &str) -> TokenStream2 {
 
 /// Generate code to append the `v: T` to the `builder: &mut Builder`.
 pub fn gen_append_value(ty: &str) -> TokenStream2 {
+    if ty == "any[]" {
+        // builder: AnyListBuilder
+        return quote! { builder.append_value(v) };
+    }
     if let Some(inner_ty) = ty.strip_suffix("[]") {
         let value_builder_type = builder_type(inner_ty);
+        if types::is_primitive(inner_ty)
+            || matches!(
+                inner_ty,
+                "string" | "binary" | "largestring" | "largebinary"
+            )
+        {
+            return quote! {{
+                // builder.values() is Box<dyn ArrayBuilder>
+                let value_builder = builder.values().as_any_mut().downcast_mut::<#value_builder_type>().expect("downcast list value builder");
+                value_builder.extend(v.into_iter().map(Some));
+                builder.append(true);
+            }};
+        }
+        // nested lists and structs can not be extended, append the elements one by one
+        let append_elem = gen_append_value(inner_ty);
         quote! {{
-            // builder.values() is Box<dyn ArrayBuilder>
-            let value_builder = builder.values().as_any_mut().downcast_mut::<#value_builder_type>().expect("downcast list value builder");
-            value_builder.extend(v.into_iter().map(Some));
+            {
+                let builder = builder.values().as_any_mut().downcast_mut::<#value_builder_type>().expect("downcast list value builder");
+                for v in v {
+                    #append_elem;
+                }
+            }
             builder.append(true);
         }}
+    } else if let Some((key, value)) = types::map_key_value(ty) {
+        let append_key = gen_append_value(key);
+        let append_value = gen_append(value);
+        quote! {{
+            // builder: MapBuilder
+            for (k, v) in v {
+                {
+                    let builder = builder.keys();
+                    let v = k;
+                    #append_key;
+                }
+                {
+                    let builder = builder.values();
+                    #append_value;
+                }
+            }
+            builder.append(true).expect("append map");
+        }}
     } else if ty.starts_with("struct ") {
         quote! {{
             v.append_to(builder);
@@ -540,12 +1870,23 @@ pub fn gen_append_value(ty: &str) -> TokenStream2 {
         }}
     } else if ty == "decimal" {
         quote! { builder.append_value(v.to_string()) }
+    } else if types::decimal128_precision_scale(ty).is_some() || ty == "timestamp(ns)" {
+        // the values have been converted by `gen_convert_value`
+        quote! { builder.append_value(v) }
+    } else if let Some(width) = types::fixed_size_binary_width(ty) {
+        let width = width as usize;
+        quote! { builder.append_value({
+            let v: [u8; #width] = v;
+            v
+        }).expect("fixed size binary") }
+    } else if ty == "uuid" {
+        quote! { builder.append_value(::arrow_udf::codegen::uuid::Uuid::into_bytes(v)).expect("uuid") }
     } else if ty == "date32" {
         quote! { builder.append_value(arrow_array::types::Date32Type::from_naive_date(v)) }
     } else if ty == "time64" {
         quote! { builder.append_value(arrow_array::temporal_conversions::time_to_time64us(v)) }
     } else if ty == "timestamp" {
-        quote! { builder.append_value(v.and_utc().timestamp_micros()) }
+        quote! { builder.append_value(::arrow_udf::codegen::TimestampValue::to_micros(v)) }
     } else if ty == "interval" {
         quote! { builder.append_value({
             let v: arrow_udf::types::Interval = v.into();
@@ -563,6 +1904,8 @@ pub fn gen_append_null(ty: &str) -> TokenStream2 {
     if let Some(s) = ty.strip_prefix("struct ") {
         let struct_type = format_ident!("{}", s);
         quote! { #struct_type::append_null(builder) }
+    } else if ty.starts_with("map<") {
+        quote! { builder.append(false).expect("append map") }
     } else {
         quote! { builder.append_null() }
     }
@@ -575,9 +1918,16 @@ pub fn gen_append_null(ty: &str) -> TokenStream2 {
 /// | `date32`        | `i32`            | `chrono::NaiveDate`              |
 /// | `time64`        | `i64`            | `chrono::NaiveTime`              |
 /// | `timestamp`     | `i64`            | `chrono::NaiveDateTime`          |
+/// | `timestamp`     | `i64`            | `chrono::DateTime<Utc>` (`utc`)  |
+/// | `timestamp(ns)` | `i64`            | `chrono::NaiveDateTime`          |
 /// | `interval`      | `i128`           | `arrow_udf::types::Interval`     |
 /// | `decimal`       | `&str`           | `rust_decimal::Decimal`          |
+/// | `decimal128`    | `i128`           | `rust_decimal::Decimal`          |
+/// | `decimal256`    | `i256`           | `i256`                           |
+/// | `fixedsizebinary(n)` | `&[u8]`     | `&[u8; n]`                       |
+/// | `uuid`          | `&[u8]`          | `uuid::Uuid`                     |
 /// | `json`          | `&str`           | `serde_json::Value`              |
+/// | `json_text`     | `&str`           | `&str`                           |
 /// | `int8[]`        | `ArrayRef`       | `&[i8]`                          |
 /// | `int16[]`       | `ArrayRef`       | `&[i16]`                         |
 /// | `int32[]`       | `ArrayRef`       | `&[i32]`                         |
@@ -592,15 +1942,50 @@ pub fn gen_append_null(ty: &str) -> TokenStream2 {
 /// | `binary[]`      | `ArrayRef`       | `arrow::array::BinaryArray`      |
 /// | `largestring[]` | `ArrayRef`       | `arrow::array::LargeStringArray` |
 /// | `largebinary[]` | `ArrayRef`       | `arrow::array::LargeBinaryArray` |
+/// | `map<K,V>`      | `StructArray`    | `impl Iterator<Item = (K, Option<V>)>` |
 fn transform_input(input: &Ident, ty: &str) -> TokenStream2 {
+    if let Some((key, value)) = types::map_key_value(ty) {
+        let key_array = format_ident!("{}", types::array_type(key));
+        let value_array = format_ident!("{}", types::array_type(value));
+        let transform_key = transform_input(&format_ident!("k"), key);
+        let transform_value = match transform_input(&format_ident!("v"), value) {
+            t if t.to_string() == "v" => quote! { v },
+            t if types::decimal128_precision_scale(value).is_some() => {
+                quote! { v.map(|v| #t.expect("the values are checked")) }
+            }
+            t => quote! { v.map(|v| #t) },
+        };
+        return quote! {{
+            // the entries of a map are stored in a struct array of (keys, values)
+            let keys: &#key_array = #input.column(0).as_any().downcast_ref().expect("the keys are checked");
+            let values: &#value_array = #input.column(1).as_any().downcast_ref().expect("the values are checked");
+            (0..#input.len()).map(move |j| {
+                let k = keys.value(j);
+                let v = values.is_valid(j).then(|| values.value(j));
+                (#transform_key, #transform_value)
+            })
+        }};
+    }
     if ty == "decimal" {
         return quote! { #input.parse::<rust_decimal::Decimal>().expect("invalid decimal") };
+    } else if let Some((_, scale)) = types::decimal128_precision_scale(ty) {
+        let scale = scale as u32;
+        return quote! { ::arrow_udf::codegen::decimal128_to_decimal(#input, #scale) };
+    } else if let Some(width) = types::fixed_size_binary_width(ty) {
+        let width = width as usize;
+        // the width has been checked when downcasting the array
+        return quote! { <&[u8; #width]>::try_from(#input).expect("the width is checked") };
+    } else if ty == "uuid" {
+        // the width has been checked when downcasting the array
+        return quote! { ::arrow_udf::codegen::uuid::Uuid::from_slice(#input).expect("the width is checked") };
     } else if ty == "date32" {
         return quote! { arrow_array::types::Date32Type::to_naive_date(#input) };
     } else if ty == "time64" {
         return quote! { arrow_array::temporal_conversions::as_time::<arrow_array::types::Time64MicrosecondType>(#input).expect("invalid time") };
     } else if ty == "timestamp" {
         return quote! { arrow_array::temporal_conversions::as_datetime::<arrow_array::types::TimestampMicrosecondType>(#input).expect("invalid timestamp") };
+    } else if ty == "timestamp(ns)" {
+        return quote! { arrow_array::temporal_conversions::as_datetime::<arrow_array::types::TimestampNanosecondType>(#input).expect("invalid timestamp") };
     } else if ty == "interval" {
         return quote! {{
             let (months, days, nanos) = arrow_array::types::IntervalMonthDayNanoType::to_parts(#input);
@@ -619,7 +2004,7 @@ fn transform_input(input: &Ident, ty: &str) -> TokenStream2 {
             return quote! {
                 #input.as_any().downcast_ref::<arrow_array::StringArray>().expect("string array")
             };
-        } else if elem_type == "binary" {
+        } else if elem_type == "binary" || elem_type == "geometry" {
             return quote! {
                 #input.as_any().downcast_ref::<arrow_array::BinaryArray>().expect("binary array")
             };
@@ -627,7 +2012,7 @@ fn transform_input(input: &Ident, ty: &str) -> TokenStream2 {
             return quote! {
                 #input.as_any().downcast_ref::<arrow_array::LargeStringArray>().expect("large string array")
             };
-        } else if elem_type == "largebinary" {
+        } else if elem_type == "largebinary" || elem_type == "largegeometry" {
             return quote! {
                 #input.as_any().downcast_ref::<arrow_array::LargeBinaryArray>().expect("large binary array")
             };
@@ -62,8 +62,13 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 ///     - [Return Value](#return-value)
 ///     - [Optimization](#optimization)
 ///     - [Functions Returning Strings](#functions-returning-strings)
+///     - [Context](#context)
+///     - [Async Functions](#async-functions)
+///     - [Argument Metadata](#argument-metadata)
 /// - [Table Function](#table-function)
 /// - [Registration and Invocation](#registration-and-invocation)
+///     - [Type Inference](#type-inference)
+///     - [Export Symbols](#export-symbols)
 /// - [Appendix: Type Matrix](#appendix-type-matrix)
 ///
 /// The following example demonstrates a simple usage:
@@ -89,6 +94,9 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// `arg_types` is a comma-separated list of argument types. The allowed data types are listed in
 /// in the `name` column of the appendix's [type matrix]. Wildcards or `auto` can also be used, as
 /// explained below. If the function is variadic, the last argument can be denoted as `...`.
+/// The trailing columns are then passed to the Rust function as an [`arrow_udf::types::VariadicRow`]
+/// after the fixed arguments. Nulls in the trailing columns are not checked, and the row is empty
+/// if there are no trailing columns.
 ///
 /// When `setof` appears before the return type, this indicates that the function is a set-returning
 /// function (table function), meaning it can return multiple values instead of just one. For more
@@ -96,6 +104,20 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 ///
 /// If no return type is specified, the function returns `null`.
 ///
+/// Trailing arguments can have a default value, which is a Rust expression of the argument type
+/// of the Rust function. A function is then defined for each number of arguments, and the omitted
+/// arguments are filled with their default values. For example, the following registers both
+/// `round(decimal)` and `round(decimal, int32)`:
+///
+/// ```ignore
+/// #[function("round(decimal, int = 0) -> decimal")]
+/// fn round(x: Decimal, scale: i32) -> Decimal {
+///     x.round_dp(scale.max(0) as u32)
+/// }
+/// ```
+///
+/// Default values can not contain commas, and can not be used with variadic arguments.
+///
 /// ## Multiple Function Definitions
 ///
 /// Multiple `#[function]` macros can be applied to a single generic Rust function to define
@@ -126,6 +148,24 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// fn add(x: Option<i32>, y: i32) -> i32 {...}
 /// ```
 ///
+/// The null handling can also be declared explicitly, like `CALLED ON NULL INPUT` and `STRICT` in
+/// SQL:
+///
+/// - `called_on_null_input`: The function is called even if arguments are null. All arguments of
+///   the Rust function must be `Option`.
+/// - `strict` (or `returns_null_on_null_input`): The function returns null if any argument is null,
+///   without calling the Rust function. `Option` arguments will always receive `Some`.
+///
+/// ```ignore
+/// #[function("coalesce(int, int) -> int", called_on_null_input)]
+/// fn coalesce(x: Option<i32>, y: Option<i32>) -> Option<i32> {
+///     x.or(y)
+/// }
+///
+/// #[function("add(int, int) -> int", strict)]
+/// fn add(x: Option<i32>, y: Option<i32>) -> i32 {...}
+/// ```
+///
 /// ## Return Value
 ///
 /// Similarly, the return value type can be one of the following:
@@ -135,6 +175,38 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// - `Result<T>`: Indicates that an error may occur, but a null value will not be returned.
 /// - `Result<Option<T>>`: Indicates that a null value may be returned, and an error may also occur.
 ///
+/// The output column is named after the function by default. Use the `output_name` attribute to
+/// give it a different name:
+///
+/// ```ignore
+/// #[function("add(int, int) -> int", output_name = "result")]
+/// fn add(x: i32, y: i32) -> i32 {
+///     x + y
+/// }
+/// ```
+///
+/// The output column is nullable unless the function never returns null, that is, it returns a
+/// plain `T` and all arguments are `Option` without `strict`. Use the `nullable` attribute to
+/// declare it explicitly. If a non-nullable function returns null, the evaluation fails.
+///
+/// ```ignore
+/// #[function("len(string) -> int", nullable = false)]
+/// fn len(s: Option<&str>) -> Option<i32> {
+///     Some(s.map_or(0, |s| s.len() as i32))
+/// }
+/// ```
+///
+/// If the function may return errors, the errors are returned in an extra column named `error`.
+/// Use the `error_column` attribute to give it a different name:
+///
+/// ```ignore
+/// #[function("div(int, int) -> int", error_column = "__udf_error")]
+/// fn div(x: i32, y: i32) -> Result<i32, &'static str> {...}
+/// ```
+///
+/// Hosts usually look up the error column by name, so the same name should be used by all functions
+/// in a registry.
+///
 /// ## Optimization
 ///
 /// When all input and output types of the function are *primitive type* (int2, int4, int8, float4, float8)
@@ -143,6 +215,47 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 ///
 /// Therefore, try to avoid returning `Option` and `Result` whenever possible.
 ///
+/// Unary and binary functions over `boolean` without Option or Result are evaluated with bitwise
+/// operations on the packed bits. The function is only called once for each combination of
+/// input values, so it must be deterministic.
+///
+/// Similarly, for non-volatile unary functions without Option or Result, if the input is
+/// dictionary-encoded, the function is only evaluated on the dictionary values, and the results
+/// are taken by the keys into a plain array of the return type. This is much cheaper for
+/// low-cardinality input.
+///
+/// `RunEndEncoded` input of scalar and table functions is accepted for arguments of any type, but
+/// it is decoded to a plain array before evaluation, which copies each value once per row.
+/// The output is a plain array with the same number of rows. The output batches of table functions
+/// are computed at once in this case.
+///
+/// ## Columnar Functions
+///
+/// With the `columnar` attribute, the user function takes the whole argument arrays and returns
+/// the output array, which is useful for kernels that are not element-wise or are faster on
+/// whole arrays. The arguments are the typed arrays of the argument types, and variadic arguments
+/// are passed as `&[ArrayRef]`:
+///
+/// ```ignore
+/// #[function("minus_mean(float64) -> float64", columnar)]
+/// fn minus_mean(x: &Float64Array) -> ArrayRef {
+///     // ...
+/// }
+///
+/// #[function("sum_columns(int32, ...) -> int32", columnar)]
+/// fn sum_columns(first: &Int32Array, rest: &[ArrayRef]) -> Result<ArrayRef, String> {
+///     // ...
+/// }
+/// ```
+///
+/// The returned array must have the same number of rows as the input and the declared return type,
+/// otherwise the call fails. An `Err` fails the whole call rather than producing an error column.
+///
+/// Unlike `batch_fn`, which replaces the evaluation of an element-wise function with an
+/// alternative implementation returning a concrete array type, a `columnar` function is the only
+/// implementation. It supports variadic arguments and `Result`, and can return any `ArrayRef`.
+/// Arguments with default values, `&Context` and writer arguments are not supported.
+///
 /// ## Functions Returning Strings
 ///
 /// For functions that return string types, you can also use the writer style function signature to
@@ -179,6 +292,91 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// }
 /// ```
 ///
+/// The writer style can also be used for functions that return `json` or `decimal`, whose values
+/// are stored as strings. The written text is not validated.
+///
+/// ```ignore
+/// #[function("to_json_array(int[]) -> json")]
+/// fn to_json_array(v: &[i32], writer: &mut impl Write) {
+///     write!(writer, "{v:?}").unwrap();
+/// }
+/// ```
+///
+/// ## Context
+///
+/// Functions that need expensive one-time setup (e.g. compiling a regex) can take a `&Context`
+/// argument after the SQL arguments. The context is built by the function specified in the `init`
+/// attribute, which is called once before the first call. The context is stored in a static
+/// variable, so the type must be `Send + Sync`. The generated functions remain free functions
+/// with the usual signature, so the host does not need to pass the context. Both scalar and table
+/// functions can take a context, but functions with `batches` and window functions can not.
+///
+/// ```ignore
+/// struct Context {
+///     regex: Regex,
+/// }
+///
+/// fn build_context() -> Context {
+///     Context { regex: Regex::new("^[a-z]+$").unwrap() }
+/// }
+///
+/// #[function("is_lowercase(string) -> boolean", init = "build_context")]
+/// fn is_lowercase(s: &str, ctx: &Context) -> bool {
+///     ctx.regex.is_match(s)
+/// }
+/// ```
+///
+/// ## Async Functions
+///
+/// Scalar functions can be `async`, e.g. to call an external service:
+///
+/// ```ignore
+/// #[function("get_user_name(int64) -> string")]
+/// async fn get_user_name(id: i64) -> Result<String> {
+///     // ...
+/// }
+/// ```
+///
+/// The rows are evaluated one by one, awaiting each call before the next. The generated function
+/// has the type of `arrow_udf::AsyncScalarFunction`, which returns a boxed future that is `Send`,
+/// so the future returned by the function must be `Send` too. It is registered as
+/// `FunctionKind::AsyncScalar` in the global function registry, and the host awaits it on its own
+/// runtime. Async functions are not exported through FFI.
+///
+/// Table functions, window functions, `columnar` and `batch_fn` can not be async.
+///
+/// ## Session Variables
+///
+/// Functions that depend on session variables like the timezone can take a `&SessionContext`
+/// argument after the SQL arguments (and after `&Context` if any). The host attaches the
+/// variables to the schema metadata of the input batch with `SessionContext::attach`, and they
+/// are read once per call.
+///
+/// ```ignore
+/// use arrow_udf::session::SessionContext;
+///
+/// #[function("current_timezone() -> string")]
+/// fn current_timezone(session: &SessionContext) -> Option<String> {
+///     session.timezone.clone()
+/// }
+/// ```
+///
+/// ## Argument Metadata
+///
+/// Arguments can be required to carry specific field metadata, e.g. a unit tag, with the
+/// `arg_metadata = "<index>:<key>=<value>"` attribute. The attribute can be specified multiple
+/// times. When the function is called, the metadata of each input field is checked, and an
+/// `InvalidArgumentError` is returned if a key is missing or has a different value. The metadata
+/// of omitted arguments with default values is not checked, and variadic arguments can not be
+/// checked.
+///
+/// ```ignore
+/// #[function("meters_to_feet(float64) -> float64", arg_metadata = "0:unit=meter")]
+/// fn meters_to_feet(x: f64) -> f64 {
+///     x * 3.28084
+/// }
+/// ```
+///
 /// # Table Function
 ///
 /// A table function is a special kind of function that can return multiple values instead of just
@@ -200,6 +398,61 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// - `Result<impl Iterator<Item = T>>`
 /// - `Result<impl Iterator<Item = Result<Option<T>>>>`
 ///
+/// A table function without arguments emits its rows once per input row, and the `row` column
+/// refers to the index of the input row. To call it once, pass a batch with no columns and a row
+/// count of 1, e.g. created by `RecordBatch::try_new_with_options`.
+///
+/// By default, the output of a table function starts with a `row` column (`int32`) which refers to
+/// the index of the input row. Use the `no_row_index` attribute to omit it, so that the output only
+/// has the value column (and the error column if any):
+///
+/// ```ignore
+/// #[function("generate_series(int32, int32) -> setof int32", no_row_index)]
+/// fn generate_series(start: i32, stop: i32) -> impl Iterator<Item = i32> {
+///     start..=stop
+/// }
+/// ```
+///
+/// Note that the output rows can then no longer be correlated to the input rows, especially when
+/// the output is split into multiple batches.
+///
+/// For full control over the output, a table function can build the record batches by itself with
+/// the `batches` attribute. The Rust function receives the whole input batch and returns
+/// `impl IntoIterator<Item = RecordBatch>` (or a `Result` of it). Each batch must have the columns
+/// `row` (the index of the input row, `int32`, omitted with `no_row_index`) and the return value:
+///
+/// ```ignore
+/// #[function("generate_series(int32) -> setof int32", batches)]
+/// fn generate_series(input: &RecordBatch) -> impl Iterator<Item = RecordBatch> + '_ {
+///     ...
+/// }
+/// ```
+///
+/// Field names and metadata of the yielded batches are replaced by the declared ones. If the
+/// number or types of the columns don't match the signature, the iterator yields a `SchemaError`.
+///
+/// The planner can be given an estimate of how many rows a table function produces for each input
+/// row with the `rows_per_input` attribute. It is stored in the function signature and defaults to 1.
+///
+/// ```ignore
+/// #[function("generate_series(int32, int32) -> setof int32", rows_per_input = 10)]
+/// fn generate_series(start: i32, stop: i32) -> impl Iterator<Item = i32> {
+///     start..=stop
+/// }
+/// ```
+///
+/// If a table function produces at most a few rows for each input row, declare the bound with the
+/// `max_rows_per_input` attribute. The output is then built by a plain iterator instead of a
+/// generator, which is cheaper for small outputs. If the function produces more rows than declared
+/// for an input row, the iterator yields the rows built so far, then a `ComputeError`, and ends.
+///
+/// ```ignore
+/// #[function("split_part(string) -> setof string", max_rows_per_input = 4)]
+/// fn split_part(s: &str) -> impl Iterator<Item = &str> {
+///     s.splitn(4, ',')
+/// }
+/// ```
+///
 /// # Registration and Invocation
 ///
 /// Every function defined by `#[function]` is automatically registered in the global function registry.
@@ -213,6 +466,43 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// let sig = REGISTRY.get("add", &[Int32, Int32], &Int32).unwrap();
 /// ```
 ///
+/// ## Type Inference
+///
+/// The `type_infer` attribute specifies a function of type
+/// `fn(&[Field]) -> Result<Field, ArrowError>` to infer the return type from the argument types
+/// of a call. It is called by `FunctionSignature::infer_return_type`. For variadic functions, the
+/// argument types include all variadic arguments, so `args.len()` is the number of arguments of
+/// the call. The function is still evaluated with the declared return type, so the caller should
+/// cast the output if the inferred type is different.
+///
+/// ```ignore
+/// #[function("greatest(...) -> int64", type_infer = "greatest_type")]
+/// fn greatest(args: VariadicRow<'_>) -> Option<i64> {
+///     // ...
+/// }
+///
+/// fn greatest_type(args: &[Field]) -> Result<Field, ArrowError> {
+///     // the widest type of all arguments
+/// }
+/// ```
+///
+/// ## Export Symbols
+///
+/// Scalar and table functions are exported through FFI with the symbol `arrowudf_<base64>`, where
+/// `<base64>` encodes the normalized signature, e.g. `gcd(int32,int32)->int32`. To look up the
+/// symbol of a function in a loaded library, use `FunctionSignature::export_name`, or the constant
+/// `<NAME>_EXPORT_NAME` generated next to the function, where `<NAME>` is the uppercase name of
+/// its `_eval` function without the suffix:
+///
+/// ```ignore
+/// #[function("gcd(int, int) -> int")]
+/// fn gcd(a: i32, b: i32) -> i32 {
+///     // ...
+/// }
+///
+/// assert_eq!(GCD_INT32_INT32_INT32_EXPORT_NAME, "arrowudf_Z2NkKGludDMyLGludDMyKS0$aW50MzI");
+/// ```
+///
 /// # Appendix: Type Matrix
 ///
 /// ## Base Types
@@ -224,15 +514,75 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// | `int16`              | `smallint`         | `i16`                          | `i16`                          |
 /// | `int32`              | `int`              | `i32`                          | `i32`                          |
 /// | `int64`              | `bigint`           | `i64`                          | `i64`                          |
+/// | `float16`            | `half`, `float2`   | [`half::f16`]                  | [`half::f16`]                  |
 /// | `float32`            | `real`             | `f32`                          | `f32`                          |
 /// | `float32`            | `double precision` | `f64`                          | `f64`                          |
 /// | `date32`             | `date`             | [`chrono::NaiveDate`]          | [`chrono::NaiveDate`]          |
 /// | `time64`             | `time`             | [`chrono::NaiveTime`]          | [`chrono::NaiveTime`]          |
-/// | `timestamp`          |                    | [`chrono::NaiveDateTime`]      | [`chrono::NaiveDateTime`]      |
+/// | `timestamp`          | `timestamp(us)`    | [`chrono::NaiveDateTime`]      | [`chrono::NaiveDateTime`], [`chrono::DateTime<Utc>`] |
+/// | `timestamp(ns)`      |                    | [`chrono::NaiveDateTime`]      | [`chrono::NaiveDateTime`], [`chrono::DateTime<Utc>`] |
 /// | `timestamptz`        |                    | not supported yet              | not supported yet              |
 /// | `interval`           |                    | [`arrow_udf::types::Interval`] | [`arrow_udf::types::Interval`] |
+/// | `decimal128(p,s)`    |                    | [`rust_decimal::Decimal`]      | [`rust_decimal::Decimal`]      |
+/// | `decimal256(p,s)`    |                    | [`arrow_udf::types::i256`]     | [`arrow_udf::types::i256`]     |
 /// | `string`             | `varchar`          | `&str`                         | `impl AsRef<str>`, e.g. `String`, `Box<str>`, `&str`     |
 /// | `binary`             | `bytea`            | `&[u8]`                        | `impl AsRef<[u8]>`, e.g. `Vec<u8>`, `Box<[u8]>`, `&[u8]` |
+/// | `fixedsizebinary(n)` | `bytea(n)`         | `&[u8; n]`                     | `[u8; n]`                      |
+/// | `uuid`               |                    | `uuid::Uuid`                   | `uuid::Uuid`                   |
+///
+/// `decimal128(p,s)` values are stored as is in a `Decimal128(p, s)` array, without the string
+/// conversion of `decimal`. The precision is limited to 28 by `rust_decimal`. Returned values are
+/// rescaled to `s`, rounding if needed. Argument values that do not fit in `rust_decimal::Decimal`
+/// and returned values that do not fit in the precision `p` are reported as errors of the row, so
+/// these functions always have an error column.
+///
+/// `decimal256(p,s)` supports a precision up to 76, beyond the range of `rust_decimal`.
+/// Its values are passed as the unscaled `i256` integers stored in the `Decimal256(p, s)` array,
+/// i.e. the decimal value multiplied by `10^s`. Returned values must be unscaled by the `s` of
+/// the return type.
+///
+/// Arguments of scalar and table functions with a different scale are rescaled to `s` before calling the
+/// function, and an error is returned if a value does not fit in the precision `p` or would lose
+/// digits. Add the `round_decimal` attribute to round such values half away from zero instead:
+///
+/// ```ignore
+/// #[function("add(decimal128(10,2), decimal128(10,2)) -> decimal128(11,2)", round_decimal)]
+/// fn add(x: Decimal, y: Decimal) -> Decimal {
+///     x + y
+/// }
+/// ```
+///
+/// Window functions reject decimal arguments with a different scale.
+///
+/// `fixedsizebinary(n)` values are stored in a `FixedSizeBinary(n)` array. The length of the
+/// returned array is checked at compile time, e.g. a function returning `fixedsizebinary(16)` must
+/// return `[u8; 16]`.
+///
+/// `uuid` values are stored in a `FixedSizeBinary(16)` array with the `arrow.uuid` extension type.
+///
+/// `string` and `binary` arguments also accept `Utf8View` and `BinaryView` arrays, which are read
+/// without conversion. The return value is always built as a `Utf8` or `Binary` array.
+/// Customized batch functions and nested types such as `string[]` only accept the non-view arrays.
+///
+/// `timestamp` arguments are passed as `NaiveDateTime` by default. Use the `utc` attribute to get
+/// `DateTime<Utc>` instead, which has the same value but can not be mixed up with local times.
+/// Both types can be returned as `timestamp`:
+///
+/// ```ignore
+/// #[function("format_utc(timestamp) -> string", utc)]
+/// fn format_utc(t: DateTime<Utc>) -> String {
+///     t.to_rfc3339()
+/// }
+/// ```
+///
+/// `timestamp` values are stored with microsecond precision. Use `timestamp(ns)` for nanosecond
+/// precision, which is stored in a `Timestamp(Nanosecond, None)` array and limited to the years
+/// 1677 to 2262. Returned timestamps out of this range are reported as errors of the row, so these
+/// functions always have an error column.
+///
+/// Returned intervals are stored as is, so they never overflow. To compute an interval from `i128`
+/// nanoseconds, use `Interval::checked_from_nanos` and return an error if it is `None`, or
+/// `Interval::saturating_from_nanos` to saturate at the bounds of `i64`.
 ///
 /// ## Extension Types
 ///
@@ -242,6 +592,21 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// | ----------- | ------------------- | ------------------------------ | ------------------------------ |
 /// | `decimal`   | `arrowudf.decimal`  | [`rust_decimal::Decimal`]      | [`rust_decimal::Decimal`]      |
 /// | `json`      | `arrowudf.json`     | [`serde_json::Value`]          | [`serde_json::Value`]          |
+/// | `json_text` | `arrowudf.json`     | `&str`                         | `impl AsRef<str>`              |
+/// | `geometry`  | `geoarrow.wkb`      | `&[u8]`                        | `impl AsRef<[u8]>`             |
+///
+/// `geometry` values are stored as WKB in a `Binary` array. Use `largegeometry` for `LargeBinary`.
+///
+/// `json_text` is the same extension type as `json`, but the values are passed and returned as
+/// the raw JSON text without parsing or validation. This avoids deserializing every row for
+/// functions that only scan or pass through the text. Returned strings must be valid JSON.
+///
+/// ```ignore
+/// #[function("json_is_array(json_text) -> boolean")]
+/// fn json_is_array(s: &str) -> bool {
+///     s.trim_start().starts_with('[')
+/// }
+/// ```
 ///
 /// ## Array Types
 ///
@@ -264,13 +629,79 @@ pub fn struct_type(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// | SQL type              | Rust type as argument     | Rust type as return value      |
 /// | --------------------- | ------------------------- | ------------------------------ |
 /// | `struct<..>`          | `UserDefinedStruct`       | `UserDefinedStruct`            |
+/// | `map<K, V>`           | `impl Iterator<Item = (K, Option<V>)>` | `impl IntoIterator<Item = (K, Option<V>)>` |
+///
+/// If the keys of the returned map are sorted, add the `keys_sorted` attribute to set the flag in
+/// the map type:
+///
+/// ```ignore
+/// #[function("to_map(string[], string[]) -> map<string, string>", keys_sorted)]
+/// ```
+///
+/// ## Polymorphic Types
+///
+/// | SQL type              | Rust type as argument     | Rust type as return value      |
+/// | --------------------- | ------------------------- | ------------------------------ |
+/// | `any`                 | [`arrow_udf::types::AnyValue`] | [`arrow_udf::types::AnyValue`], or `ArrayRef` for `columnar` functions |
+/// | `anyarray`            | not supported yet         | `impl IntoIterator<Item = AnyValue>` or `Item = Option<AnyValue>` |
+///
+/// Scalar functions can take `any` arguments of any type. A function returning `anyarray` must
+/// have at least one `any` argument. The element type of the returned list is not known at compile
+/// time: the generated function reads it from the data type of the `any` input arrays, which must
+/// be the same for all `any` arguments, and the elements are copied from these arrays.
+///
+/// ```ignore
+/// #[function("array_fill(any, int) -> anyarray")]
+/// fn array_fill(x: AnyValue<'_>, n: i32) -> impl Iterator<Item = AnyValue<'_>> {
+///     std::iter::repeat(x).take(n.max(0) as usize)
+/// }
+/// ```
+///
+/// A function returning `any` must also have at least one `any` argument. The returned values are
+/// copied from the `any` arguments, so the return type is the type of the first `any` argument:
+///
+/// ```ignore
+/// #[function("coalesce(any, any) -> any")]
+/// fn coalesce<'a>(x: Option<AnyValue<'a>>, y: Option<AnyValue<'a>>) -> Option<AnyValue<'a>> {
+///     x.or(y)
+/// }
+/// ```
+///
+/// Since the type is unknown at compile time, these functions are evaluated row by row. The
+/// exception is a pure function of a single `any` argument that is generic over its type, like a
+/// passthrough function. It is instantiated for the native type of each integer and float type,
+/// and the data type of the input is dispatched at runtime to evaluate primitive arrays with SIMD.
+/// Other types fall back to the row-by-row loop with `AnyValue`:
+///
+/// ```ignore
+/// #[function("passthrough(any) -> any")]
+/// fn passthrough<T>(x: T) -> T {
+///     x
+/// }
+/// ```
+///
+/// `columnar` functions can also take `map<K, any>` arguments as `&MapArray` and return `any` as
+/// `ArrayRef`. The return type is the type of the first `any` argument, or the value type of the
+/// first `map<K, any>` argument. It is inferred by `FunctionSignature::infer_return_type` unless
+/// `type_infer` is specified. For example, a lookup function returning null for missing keys:
+///
+/// ```ignore
+/// #[function("map_get(map<varchar, any>, varchar) -> any", columnar)]
+/// fn map_get(map: &MapArray, key: &StringArray) -> ArrayRef {
+///     // take the value of `key` from each map, or null if not found
+/// }
+/// ```
 ///
 /// [type matrix]: #appendix-type-matrix
+/// [`half::f16`]: https://docs.rs/half/2.4.1/half/struct.f16.html
 /// [`rust_decimal::Decimal`]: https://docs.rs/rust_decimal/1.33.1/rust_decimal/struct.Decimal.html
 /// [`chrono::NaiveDate`]: https://docs.rs/chrono/0.4.31/chrono/naive/struct.NaiveDate.html
 /// [`chrono::NaiveTime`]: https://docs.rs/chrono/0.4.31/chrono/naive/struct.NaiveTime.html
 /// [`chrono::NaiveDateTime`]: https://docs.rs/chrono/0.4.31/chrono/naive/struct.NaiveDateTime.html
+/// [`chrono::DateTime<Utc>`]: https://docs.rs/chrono/0.4.31/chrono/struct.DateTime.html
 /// [`arrow_udf::types::Interval`]: https://docs.rs/arrow_udf/0.1.0/arrow_udf/types/struct.Interval.html
+/// [`arrow_udf::types::VariadicRow`]: https://docs.rs/arrow_udf/latest/arrow_udf/types/struct.VariadicRow.html
+/// [`arrow_udf::types::AnyValue`]: https://docs.rs/arrow_udf/latest/arrow_udf/types/struct.AnyValue.html
 /// [`serde_json::Value`]: https://docs.rs/serde_json/1.0.108/serde_json/enum.Value.html
 /// [`&StringArray`]: https://docs.rs/arrow/50.0.0/arrow/array/type.StringArray.html
 /// [`&BinaryArray`]: https://docs.rs/arrow/50.0.0/arrow/array/type.BinaryArray.html
@@ -294,16 +725,67 @@ pub fn function(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// Defining a window function on Arrow arrays.
+///
+/// A window function sees an ordered partition and produces one output for each input row.
+/// The signature is the same as [`macro@function`], except that `setof` and variadic arguments are
+/// not allowed.
+///
+/// The Rust function receives the argument arrays of the whole partition, followed by the index
+/// of the current row and the range of rows in its window frame:
+///
+/// ```ignore
+/// #[window("row_number() -> int64")]
+/// fn row_number(row: usize, _frame: Range<usize>) -> i64 {
+///     row as i64 + 1
+/// }
+///
+/// #[window("lag(int32) -> int32")]
+/// fn lag(values: &Int32Array, row: usize, _frame: Range<usize>) -> Option<i32> {
+///     let prev = row.checked_sub(1)?;
+///     values.is_valid(prev).then(|| values.value(prev))
+/// }
+/// ```
+///
+/// The generated function has the type of `arrow_udf::WindowFunction`, and is registered as
+/// `FunctionKind::Window` in the global function registry. Window functions are not exported
+/// through FFI.
+#[proc_macro_attribute]
+pub fn window(attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn inner(attr: TokenStream, item: TokenStream) -> Result<TokenStream2> {
+        let mut fn_attr: FunctionAttr = syn::parse(attr)?;
+        fn_attr.is_window = true;
+        let user_fn: UserFunctionAttr = syn::parse(item.clone())?;
+
+        let mut tokens: TokenStream2 = item.into();
+        for attr in fn_attr.expand() {
+            tokens.extend(attr.generate_function_descriptor(&user_fn)?);
+        }
+        Ok(tokens)
+    }
+    match inner(attr, item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 struct FunctionAttr {
     /// Function name
     name: String,
     /// Input argument types
     args: Vec<String>,
+    /// Default values of the trailing arguments, as Rust expressions.
+    ///
+    /// After [`FunctionAttr::expand`], these are the values of the trailing arguments of the user
+    /// function that are omitted from `args`.
+    defaults: Vec<String>,
     /// Return type
     ret: String,
     /// Whether it is a table function
     is_table_function: bool,
+    /// Whether it is a window function
+    is_window: bool,
     /// Whether it is an append-only aggregate function
     append_only: bool,
     /// Optional function for batch evaluation.
@@ -320,9 +802,47 @@ struct FunctionAttr {
     generic: Option<String>,
     /// Whether the function is volatile.
     volatile: bool,
+    /// Function to initialize the context.
+    /// It is called once before the first call of the function.
+    init: Option<String>,
     /// Generated batch function name.
     /// If not specified, the macro will not generate batch function.
     output: Option<String>,
+    /// Whether the function is called on null input.
+    /// All arguments of the user function must be `Option`.
+    called_on_null_input: bool,
+    /// Whether the function returns null if any argument is null,
+    /// even if the argument of the user function is `Option`.
+    strict: bool,
+    /// Whether the table function returns record batches directly.
+    batches: bool,
+    /// Whether the user function takes the whole argument arrays.
+    columnar: bool,
+    /// Whether the keys of the returned map are sorted.
+    keys_sorted: bool,
+    /// Estimated number of output rows per input row for table function.
+    /// If not specified, it will be 1.
+    rows_per_input: Option<usize>,
+    /// Maximum number of output rows per input row for table function.
+    /// If specified, the output is built without a generator.
+    max_rows_per_input: Option<usize>,
+    /// Expected field metadata of arguments: `(index, key, value)`.
+    arg_metadata: Vec<(usize, String, String)>,
+    /// Name of the output column.
+    /// If not specified, it will be the function name.
+    output_name: Option<String>,
+    /// Whether the output column is nullable.
+    /// If not specified, it is inferred from the user function.
+    nullable: Option<bool>,
+    /// Name of the error column.
+    /// If not specified, it will be `error`.
+    error_column: Option<String>,
+    /// Whether the table function omits the `row` column.
+    no_row_index: bool,
+    /// Whether `timestamp` arguments are passed as `DateTime<Utc>`.
+    utc: bool,
+    /// Whether decimal arguments are rounded when rescaled to a smaller scale.
+    round_decimal: bool,
 }
 
 /// Attributes from function signature `fn(..)`
@@ -333,8 +853,10 @@ struct UserFunctionAttr {
     name: String,
     /// Whether the function is async.
     async_: bool,
-    /// Whether contains argument `&Context`.
-    context: bool,
+    /// If contains argument `&Context`, then `Some(Context)`.
+    context: Option<String>,
+    /// Whether contains argument `&SessionContext`.
+    session: bool,
     /// Whether contains argument `&mut impl Write`.
     write: bool,
     /// Whether the last argument type is `retract: bool`.
@@ -376,8 +898,8 @@ impl FunctionAttr {
         format!("{}_{}_{}", self.name, self.args.join("_"), self.ret)
             .replace("[]", "array")
             .replace("...", "variadic")
-            .replace(['<', ' ', ',', ':'], "_")
-            .replace('>', "")
+            .replace(['<', ' ', ',', ':', '('], "_")
+            .replace(['>', ')'], "")
             .replace("__", "_")
     }
 
@@ -398,7 +920,8 @@ impl UserFunctionAttr {
     fn is_pure(&self) -> bool {
         !self.async_
             && !self.write
-            && !self.context
+            && self.context.is_none()
+            && !self.session
             && self.args_option.iter().all(|b| !b)
             && self.return_type_kind == ReturnTypeKind::T
     }
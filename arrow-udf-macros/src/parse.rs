@@ -35,22 +35,63 @@ impl Parse for FunctionAttr {
         let (name, args) = name_args
             .split_once('(')
             .ok_or_else(|| Error::new_spanned(&sig, "expected '('"))?;
-        let args = args.trim_start().trim_end_matches([')', ' ']);
+        // only strip the closing parenthesis of arguments, not those of types like `decimal128(p,s)`
+        let args = args.trim_end();
+        let args = args.strip_suffix(')').unwrap_or(args).trim();
         let (is_table_function, ret) = match ret.trim_start() {
             s if s.starts_with("setof") => (true, &s[5..]), // -> setof
             s if s.starts_with('>') => (true, &s[1..]),     // ->>
             _ => (false, ret),
         };
         parsed.name = name.trim().to_string();
-        parsed.args = if args.is_empty() {
-            vec![]
-        } else {
-            args.split(',')
-                .map(|s| types::normalize_type(s.trim()))
-                .collect()
-        };
+        if !args.is_empty() {
+            for arg in types::split_types(args) {
+                let ty = match arg.split_once('=') {
+                    Some((ty, default)) => {
+                        parsed.defaults.push(default.trim().to_string());
+                        ty
+                    }
+                    None if !parsed.defaults.is_empty() => {
+                        return Err(Error::new_spanned(
+                            &sig,
+                            "arguments with default values must be at the end",
+                        ));
+                    }
+                    None => arg,
+                };
+                parsed.args.push(types::normalize_type(ty.trim()));
+            }
+        }
+        if !parsed.defaults.is_empty() && parsed.args.last().is_some_and(|t| t == "...") {
+            return Err(Error::new_spanned(
+                &sig,
+                "default values can not be used with variadic arguments",
+            ));
+        }
         parsed.ret = types::normalize_type(ret.trim());
         parsed.is_table_function = is_table_function;
+        for ty in parsed.args.iter().chain([&parsed.ret]) {
+            let elem = ty.trim_end_matches("[]");
+            if elem.starts_with("decimal128") && types::decimal128_precision_scale(elem).is_none() {
+                return Err(Error::new_spanned(
+                    &sig,
+                    format!("invalid type `{ty}`: expect `decimal128(p,s)` with precision in 1..=28 and scale in 0..=p"),
+                ));
+            }
+            if elem.starts_with("fixedsizebinary") && types::fixed_size_binary_width(elem).is_none()
+            {
+                return Err(Error::new_spanned(
+                    &sig,
+                    format!("invalid type `{ty}`: expect `fixedsizebinary(n)` with a positive `n`"),
+                ));
+            }
+            if elem.starts_with("decimal256") && types::decimal256_precision_scale(elem).is_none() {
+                return Err(Error::new_spanned(
+                    &sig,
+                    format!("invalid type `{ty}`: expect `decimal256(p,s)` with precision in 1..=76 and scale in 0..=p"),
+                ));
+            }
+        }
 
         if input.parse::<Token![,]>().is_err() {
             return Ok(parsed);
@@ -78,10 +119,93 @@ impl Parse for FunctionAttr {
                 parsed.type_infer = Some(get_value()?);
             } else if meta.path().is_ident("generic") {
                 parsed.generic = Some(get_value()?);
+            } else if meta.path().is_ident("init") {
+                parsed.init = Some(get_value()?);
             } else if meta.path().is_ident("output") {
                 parsed.output = Some(get_value()?);
+            } else if meta.path().is_ident("output_name") {
+                parsed.output_name = Some(get_value()?);
+            } else if meta.path().is_ident("error_column") {
+                parsed.error_column = Some(get_value()?);
+            } else if meta.path().is_ident("arg_metadata") {
+                let value = get_value()?;
+                let invalid = || {
+                    Error::new(
+                        meta.span(),
+                        "expected `arg_metadata = \"<index>:<key>=<value>\"`",
+                    )
+                };
+                let (index, kv) = value.split_once(':').ok_or_else(invalid)?;
+                let (key, value) = kv.split_once('=').ok_or_else(invalid)?;
+                let index = index.trim().parse().map_err(|_| invalid())?;
+                if index >= parsed.args.len() {
+                    return Err(Error::new(
+                        meta.span(),
+                        format!(
+                            "argument index {index} is out of range, the function has {} arguments",
+                            parsed.args.len()
+                        ),
+                    ));
+                }
+                if parsed.args[index] == "..." {
+                    return Err(Error::new(
+                        meta.span(),
+                        "`arg_metadata` can not be used for variadic arguments",
+                    ));
+                }
+                parsed
+                    .arg_metadata
+                    .push((index, key.trim().to_string(), value.trim().to_string()));
+            } else if meta.path().is_ident("max_rows_per_input") {
+                let kv = meta.require_name_value()?;
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit),
+                    ..
+                }) = &kv.value
+                else {
+                    return Err(Error::new(kv.value.span(), "expected integer literal"));
+                };
+                parsed.max_rows_per_input = Some(lit.base10_parse()?);
+            } else if meta.path().is_ident("rows_per_input") {
+                let kv = meta.require_name_value()?;
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit),
+                    ..
+                }) = &kv.value
+                else {
+                    return Err(Error::new(kv.value.span(), "expected integer literal"));
+                };
+                parsed.rows_per_input = Some(lit.base10_parse()?);
+            } else if meta.path().is_ident("nullable") {
+                let kv = meta.require_name_value()?;
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Bool(lit),
+                    ..
+                }) = &kv.value
+                else {
+                    return Err(Error::new(kv.value.span(), "expected boolean literal"));
+                };
+                parsed.nullable = Some(lit.value);
             } else if meta.path().is_ident("volatile") {
                 parsed.volatile = true;
+            } else if meta.path().is_ident("called_on_null_input") {
+                parsed.called_on_null_input = true;
+            } else if meta.path().is_ident("strict")
+                || meta.path().is_ident("returns_null_on_null_input")
+            {
+                parsed.strict = true;
+            } else if meta.path().is_ident("batches") {
+                parsed.batches = true;
+            } else if meta.path().is_ident("columnar") {
+                parsed.columnar = true;
+            } else if meta.path().is_ident("no_row_index") {
+                parsed.no_row_index = true;
+            } else if meta.path().is_ident("utc") {
+                parsed.utc = true;
+            } else if meta.path().is_ident("round_decimal") {
+                parsed.round_decimal = true;
+            } else if meta.path().is_ident("keys_sorted") {
+                parsed.keys_sorted = true;
             } else if meta.path().is_ident("append_only") {
                 parsed.append_only = true;
             } else {
@@ -121,14 +245,15 @@ impl From<&syn::Signature> for UserFunctionAttr {
             name: sig.ident.to_string(),
             async_: sig.asyncness.is_some(),
             write: sig.inputs.iter().any(arg_is_write),
-            context: sig.inputs.iter().any(arg_is_context),
+            context: sig.inputs.iter().find_map(context_arg),
+            session: sig.inputs.iter().any(arg_is_session),
             retract: last_arg_is_retract(sig),
             args_option: sig.inputs.iter().map(arg_is_option).collect(),
             first_mut_ref_arg: first_mut_ref_arg(sig),
             return_type_kind,
             iterator_item_kind,
             core_return_type,
-            generic: sig.generics.params.len(),
+            generic: sig.generics.type_params().count(),
             return_type_span: sig.output.span(),
         }
     }
@@ -154,21 +279,39 @@ fn arg_is_write(arg: &syn::FnArg) -> bool {
     seg.ident == "Write"
 }
 
-/// Check if the argument is `&Context`.
-fn arg_is_context(arg: &syn::FnArg) -> bool {
+/// Returns `T` if the argument is `&T` where `T` is named `Context`.
+fn context_arg(arg: &syn::FnArg) -> Option<String> {
     let syn::FnArg::Typed(arg) = arg else {
-        return false;
+        return None;
     };
     let syn::Type::Reference(syn::TypeReference { elem, .. }) = arg.ty.as_ref() else {
-        return false;
+        return None;
     };
     let syn::Type::Path(path) = elem.as_ref() else {
+        return None;
+    };
+    let seg = path.path.segments.last()?;
+    if seg.ident != "Context" {
+        return None;
+    }
+    Some(elem.to_token_stream().to_string())
+}
+
+/// Check if the argument is `&T` where `T` is named `SessionContext`.
+fn arg_is_session(arg: &syn::FnArg) -> bool {
+    let syn::FnArg::Typed(arg) = arg else {
         return false;
     };
-    let Some(seg) = path.path.segments.last() else {
+    let syn::Type::Reference(syn::TypeReference { elem, .. }) = arg.ty.as_ref() else {
+        return false;
+    };
+    let syn::Type::Path(path) = elem.as_ref() else {
         return false;
     };
-    seg.ident == "Context"
+    path.path
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "SessionContext")
 }
 
 /// Check if the last argument is `retract: bool`.
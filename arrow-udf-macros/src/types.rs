@@ -17,6 +17,7 @@
 //  name    primitive   rust type       array prefix            data type
 const TYPE_MATRIX: &str = "
     null        _       ()              Null                    Null
+    any         _       AnyValue        Any                     Null
     boolean     _       bool            Boolean                 Boolean
     int8        y       i8              Int8                    Int8
     int16       y       i16             Int16                   Int16
@@ -26,20 +27,30 @@ const TYPE_MATRIX: &str = "
     uint16      y       u16             UInt16                  UInt16
     uint32      y       u32             UInt32                  UInt32
     uint64      y       u64             UInt64                  UInt64
+    float16     y       f16             Float16                 Float16
     float32     y       f32             Float32                 Float32
     float64     y       f64             Float64                 Float64
     date32      _       NaiveDate       Date32                  Date32
     time64      _       NaiveTime       Time64Microsecond       Time64(TimeUnit::Microsecond)
     timestamp   _       NaiveDateTime   TimestampMicrosecond    Timestamp(TimeUnit::Microsecond,None)
+    timestamp(ns) _     NaiveDateTime   TimestampNanosecond     Timestamp(TimeUnit::Nanosecond,None)
     interval    _       Interval        IntervalMonthDayNano    Interval(IntervalUnit::MonthDayNano)
     decimal     _       Decimal         String                  Utf8
+    decimal128  _       Decimal         Decimal128              Decimal128
+    decimal256  _       _               Decimal256              Decimal256
     json        _       Value           String                  Utf8
+    json_text   _       _               String                  Utf8
     string      _       String,str      String                  Utf8
     binary      _       Vec<u8>,[u8]    Binary                  Binary
     largestring _       String,str      LargeString             LargeUtf8
     largebinary _       Vec<u8>,[u8]    LargeBinary             LargeBinary
+    fixedsizebinary _   _               FixedSizeBinary         FixedSizeBinary
+    uuid        _       Uuid            FixedSizeBinary         FixedSizeBinary(16)
+    geometry    _       Vec<u8>,[u8]    Binary                  Binary
+    largegeometry _     Vec<u8>,[u8]    LargeBinary             LargeBinary
     array       _       _               List                    List
     struct      _       _               Struct                  Struct
+    map         _       _               Map                     Map
 ";
 
 /// Maps a data type to its corresponding data type name.
@@ -89,6 +100,14 @@ fn lookup_matrix(mut ty: &str, idx: usize) -> &str {
         ty = "array";
     } else if ty.starts_with("struct") {
         ty = "struct";
+    } else if ty.starts_with("map<") {
+        ty = "map";
+    } else if ty.starts_with("decimal128(") {
+        ty = "decimal128";
+    } else if ty.starts_with("decimal256(") {
+        ty = "decimal256";
+    } else if ty.starts_with("fixedsizebinary(") {
+        ty = "fixedsizebinary";
     }
     let s = TYPE_MATRIX.trim().lines().find_map(|line| {
         let mut parts = line.split_whitespace();
@@ -108,6 +127,9 @@ fn lookup_matrix(mut ty: &str, idx: usize) -> &str {
 /// "int" => "int32"
 /// "int[]" => "int32[]"
 /// "struct  Key" => "struct Key"
+/// "map<varchar, int>" => "map<string,int32>"
+/// "decimal128(10, 2)" => "decimal128(10,2)"
+/// "bytea(16)" => "fixedsizebinary(16)"
 /// ```
 pub fn normalize_type(ty: &str) -> String {
     if let Some(t) = ty.strip_suffix("[]") {
@@ -116,11 +138,39 @@ pub fn normalize_type(ty: &str) -> String {
     if let Some(s) = ty.strip_prefix("struct ") {
         return format!("struct {}", s.trim());
     }
+    if let Some(s) = ty.strip_prefix("map<").and_then(|s| s.strip_suffix('>')) {
+        let kv = split_types(s)
+            .into_iter()
+            .map(|t| normalize_type(t.trim()))
+            .collect::<Vec<_>>();
+        return format!("map<{}>", kv.join(","));
+    }
+    if ty.starts_with("decimal128(") || ty.starts_with("decimal256(") {
+        return ty.split_whitespace().collect();
+    }
+    if let Some(s) = ty.strip_prefix("bytea(") {
+        return format!(
+            "fixedsizebinary({}",
+            s.split_whitespace().collect::<String>()
+        );
+    }
+    if ty.starts_with("fixedsizebinary(") {
+        return ty.split_whitespace().collect();
+    }
+    if ty.starts_with("timestamp(") {
+        let ty: String = ty.split_whitespace().collect();
+        // microseconds are the default precision
+        return match ty.as_str() {
+            "timestamp(us)" => "timestamp".into(),
+            _ => ty,
+        };
+    }
     match ty {
         "bool" => "boolean",
         "smallint" => "int16",
         "int" | "integer" => "int32",
         "bigint" => "int64",
+        "float2" | "half" => "float16",
         "real" => "float32",
         "double precision" => "float64",
         "numeric" => "decimal",
@@ -129,11 +179,108 @@ pub fn normalize_type(ty: &str) -> String {
         "jsonb" => "json",
         "date" => "date32",
         "time" => "time64",
+        "anyarray" => "any[]",
         _ => ty,
     }
     .to_string()
 }
 
+/// Returns the key and value types of a map type.
+///
+/// # Examples
+/// ```text
+/// "map<string,int32>" => Some(("string", "int32"))
+/// "int32" => None
+/// ```
+pub fn map_key_value(ty: &str) -> Option<(&str, &str)> {
+    let s = ty.strip_prefix("map<")?.strip_suffix('>')?;
+    match split_types(s).as_slice() {
+        [key, value] => Some((*key, *value)),
+        _ => None,
+    }
+}
+
+/// Returns the precision and scale of a `decimal128(p,s)` type.
+///
+/// Returns `None` if the type is malformed, or the precision is out of the 28 digits supported by
+/// `rust_decimal`, or the scale is out of `0..=p`.
+///
+/// # Examples
+/// ```text
+/// "decimal128(10,2)" => Some((10, 2))
+/// "decimal128(38,2)" => None
+/// "int32" => None
+/// ```
+pub fn decimal128_precision_scale(ty: &str) -> Option<(u8, i8)> {
+    precision_scale(ty.strip_prefix("decimal128(")?, 28)
+}
+
+/// Returns the precision and scale of a `decimal256(p,s)` type.
+///
+/// Returns `None` if the type is malformed, or the precision is out of `1..=76`,
+/// or the scale is out of `0..=p`.
+///
+/// # Examples
+/// ```text
+/// "decimal256(40,2)" => Some((40, 2))
+/// "decimal256(77,2)" => None
+/// ```
+pub fn decimal256_precision_scale(ty: &str) -> Option<(u8, i8)> {
+    precision_scale(ty.strip_prefix("decimal256(")?, 76)
+}
+
+/// Returns the number of bytes of a `fixedsizebinary(n)` type.
+///
+/// Returns `None` if the type is malformed or `n` is not positive.
+///
+/// # Examples
+/// ```text
+/// "fixedsizebinary(16)" => Some(16)
+/// "fixedsizebinary(0)" => None
+/// ```
+pub fn fixed_size_binary_width(ty: &str) -> Option<i32> {
+    let n: i32 = ty
+        .strip_prefix("fixedsizebinary(")?
+        .strip_suffix(')')?
+        .trim()
+        .parse()
+        .ok()?;
+    (n > 0).then_some(n)
+}
+
+/// Parses `p,s)` with precision in `1..=max_precision` and scale in `0..=p`.
+fn precision_scale(s: &str, max_precision: u8) -> Option<(u8, i8)> {
+    let (p, s) = s.strip_suffix(')')?.split_once(',')?;
+    let (p, s): (u8, i8) = (p.trim().parse().ok()?, s.trim().parse().ok()?);
+    ((1..=max_precision).contains(&p) && (0..=p as i8).contains(&s)).then_some((p, s))
+}
+
+/// Splits a comma-separated list of types, ignoring the commas inside `<>` and `()`.
+///
+/// # Examples
+/// ```text
+/// "int, map<string, int>" => ["int", " map<string, int>"]
+/// "decimal128(10,2), int" => ["decimal128(10,2)", " int"]
+/// ```
+pub fn split_types(s: &str) -> Vec<&str> {
+    let mut types = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                types.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    types.push(&s[start..]);
+    types
+}
+
 /// Expands a type wildcard string into a list of concrete types.
 pub fn expand_type_wildcard(ty: &str) -> Vec<&str> {
     match ty {
@@ -141,7 +288,20 @@ pub fn expand_type_wildcard(ty: &str) -> Vec<&str> {
             .trim()
             .lines()
             .map(|l| l.split_whitespace().next().unwrap())
-            .filter(|l| *l != "any" && *l != "null")
+            .filter(|l| {
+                !matches!(
+                    *l,
+                    "any"
+                        | "null"
+                        | "map"
+                        | "decimal128"
+                        | "decimal256"
+                        | "fixedsizebinary"
+                        | "uuid"
+                        | "json_text"
+                        | "timestamp(ns)"
+                )
+            })
             .collect(),
         "int*" => vec!["int8", "int16", "int32", "int64"],
         "uint*" => vec!["uint8", "uint16", "uint32", "uint64"],
@@ -160,6 +320,7 @@ mod tests {
         assert_eq!(normalize_type("smallint"), "int16");
         assert_eq!(normalize_type("int"), "int32");
         assert_eq!(normalize_type("bigint"), "int64");
+        assert_eq!(normalize_type("half"), "float16");
         assert_eq!(normalize_type("real"), "float32");
         assert_eq!(normalize_type("double precision"), "float64");
         assert_eq!(normalize_type("numeric"), "decimal");
@@ -167,6 +328,70 @@ mod tests {
         assert_eq!(normalize_type("character varying"), "string");
         assert_eq!(normalize_type("jsonb"), "json");
         assert_eq!(normalize_type("int[]"), "int32[]");
+        assert_eq!(normalize_type("anyarray"), "any[]");
+        assert_eq!(normalize_type("timestamp( ns )"), "timestamp(ns)");
+        assert_eq!(normalize_type("timestamp(us)"), "timestamp");
         assert_eq!(normalize_type("struct   Key"), "struct Key");
+        assert_eq!(normalize_type("map<varchar, int>"), "map<string,int32>");
+        assert_eq!(normalize_type("decimal128(10, 2)"), "decimal128(10,2)");
+        assert_eq!(normalize_type("decimal256(40, 2)"), "decimal256(40,2)");
+        assert_eq!(normalize_type("bytea( 16 )"), "fixedsizebinary(16)");
+        assert_eq!(normalize_type("fixedsizebinary(16)"), "fixedsizebinary(16)");
+        assert_eq!(
+            normalize_type("map<varchar, map<int, bigint>>"),
+            "map<string,map<int32,int64>>"
+        );
+    }
+
+    #[test]
+    fn test_map_key_value() {
+        assert_eq!(
+            map_key_value("map<string,int32>"),
+            Some(("string", "int32"))
+        );
+        assert_eq!(
+            map_key_value("map<string,map<int32,int64>>"),
+            Some(("string", "map<int32,int64>"))
+        );
+        assert_eq!(map_key_value("int32"), None);
+    }
+
+    #[test]
+    fn test_decimal128_precision_scale() {
+        assert_eq!(
+            decimal128_precision_scale("decimal128(10,2)"),
+            Some((10, 2))
+        );
+        assert_eq!(
+            decimal128_precision_scale("decimal128(28,28)"),
+            Some((28, 28))
+        );
+        assert_eq!(decimal128_precision_scale("decimal128(38,2)"), None);
+        assert_eq!(decimal128_precision_scale("decimal128(10,11)"), None);
+        assert_eq!(decimal128_precision_scale("decimal128(10)"), None);
+        assert_eq!(decimal128_precision_scale("decimal128"), None);
+        assert_eq!(decimal128_precision_scale("int32"), None);
+        assert_eq!(
+            decimal256_precision_scale("decimal256(76,10)"),
+            Some((76, 10))
+        );
+        assert_eq!(decimal256_precision_scale("decimal256(77,2)"), None);
+        assert_eq!(decimal256_precision_scale("decimal128(10,2)"), None);
+    }
+
+    #[test]
+    fn test_fixed_size_binary_width() {
+        assert_eq!(fixed_size_binary_width("fixedsizebinary(16)"), Some(16));
+        assert_eq!(fixed_size_binary_width("fixedsizebinary(0)"), None);
+        assert_eq!(fixed_size_binary_width("fixedsizebinary"), None);
+        assert_eq!(fixed_size_binary_width("binary"), None);
+    }
+
+    #[test]
+    fn test_split_types() {
+        assert_eq!(
+            split_types("decimal128(10,2), map<int, int>"),
+            ["decimal128(10,2)", " map<int, int>"]
+        );
     }
 }
@@ -0,0 +1,120 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This example shows how to generate `CREATE FUNCTION` statements from the function catalog.
+//!
+//! A crate defining functions writes its catalog to a file with `sig::write_catalog`, e.g. from a
+//! test or a small binary. A host linking the crate can then read the file in its build script
+//! and generate SQL DDL without depending on the functions.
+//!
+//! Run with `cargo run --example ddl --features global_registry [-- <catalog path>]`.
+
+use arrow_udf::function;
+use arrow_udf::sig::{catalog_json, write_catalog};
+use serde_json::Value;
+
+#[function("gcd(int, int) -> int")]
+fn gcd(mut a: i32, mut b: i32) -> i32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[function("split_words(string) ->> string")]
+fn split_words(s: &str) -> impl Iterator<Item = &str> {
+    s.split_whitespace()
+}
+
+fn main() {
+    // the crate defining functions writes the catalog
+    if let Some(path) = std::env::args().nth(1) {
+        write_catalog(&path).unwrap();
+    }
+
+    // the build script of the host reads the catalog, here from memory
+    let catalog: Value = serde_json::from_str(&catalog_json()).unwrap();
+    for function in catalog.as_array().unwrap() {
+        match create_function(function) {
+            Some(sql) => println!("{sql}"),
+            None => println!("-- skipped function {}", function["name"]),
+        }
+    }
+}
+
+/// Returns the `CREATE FUNCTION` statement of a function in the catalog.
+///
+/// Returns `None` for window functions, variadic functions and types without a SQL equivalent.
+fn create_function(function: &Value) -> Option<String> {
+    if function["variadic"] == true {
+        return None;
+    }
+    let args = function["arg_types"]
+        .as_array()?
+        .iter()
+        .map(sql_type)
+        .collect::<Option<Vec<_>>>()?;
+    let return_type = sql_type(&function["return_type"])?;
+    let returns = match function["kind"].as_str()? {
+        "scalar" => return_type,
+        "table" => format!("SETOF {return_type}"),
+        _ => return None,
+    };
+    let volatility = if function["volatile"] == true {
+        "VOLATILE"
+    } else {
+        "IMMUTABLE"
+    };
+    Some(format!(
+        "CREATE FUNCTION {}({}) RETURNS {returns} {volatility} LANGUAGE rust AS '{}';",
+        function["name"].as_str()?,
+        args.join(", "),
+        function["export_name"].as_str()?,
+    ))
+}
+
+/// Returns the SQL type of a type in the catalog.
+fn sql_type(ty: &Value) -> Option<String> {
+    if let Some(item) = ty.get("list") {
+        return Some(format!("{}[]", sql_type(item)?));
+    }
+    if let Some(fields) = ty.get("struct") {
+        let fields = fields
+            .as_array()?
+            .iter()
+            .map(|f| Some(format!("{} {}", f["name"].as_str()?, sql_type(&f["type"])?)))
+            .collect::<Option<Vec<_>>>()?;
+        return Some(format!("struct<{}>", fields.join(", ")));
+    }
+    if let Some(extension) = ty.get("extension") {
+        return match extension.as_str()? {
+            "arrowudf.json" => Some("jsonb".into()),
+            "arrowudf.decimal" => Some("decimal".into()),
+            _ => None,
+        };
+    }
+    let ty = match ty.as_str()? {
+        "Boolean" => "boolean",
+        "Int16" => "smallint",
+        "Int32" => "int",
+        "Int64" => "bigint",
+        "Float32" => "real",
+        "Float64" => "double precision",
+        "Date32" => "date",
+        "Utf8" | "LargeUtf8" => "varchar",
+        "Binary" | "LargeBinary" => "bytea",
+        _ => return None,
+    };
+    Some(ty.into())
+}
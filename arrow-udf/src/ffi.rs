@@ -14,9 +14,14 @@
 
 //! FFI interfaces.
 
+use std::sync::Arc;
+
 use crate::{Error, ScalarFunction, TableFunction};
-use arrow_array::RecordBatch;
-use arrow_ipc::{reader::FileReader, writer::FileWriter};
+use arrow_array::{cast::AsArray, RecordBatch};
+use arrow_buffer::Buffer;
+use arrow_ipc::reader::{FileReader, StreamDecoder};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{Field, Schema};
 
 /// A symbol indicating the ABI version.
 ///
@@ -26,12 +31,15 @@ use arrow_ipc::{reader::FileReader, writer::FileWriter};
 ///
 /// # Changelog
 ///
+/// - 3.3: Add `record_batch_iterator_try_next` for table functions that fail mid-stream.
+/// - 3.2: Add sessions for table functions with shared dictionaries.
+/// - 3.1: Add cursors for scalar functions.
 /// - 3.0: Change type names in signatures.
 /// - 2.0: Add user defined struct type.
 /// - 1.0: Initial version.
 #[no_mangle]
 #[used]
-pub static ARROWUDF_VERSION_3_0: () = ();
+pub static ARROWUDF_VERSION_3_3: () = ();
 
 /// Allocate memory.
 ///
@@ -125,12 +133,223 @@ fn call_scalar(function: ScalarFunction, input_bytes: &[u8]) -> Result<Box<[u8]>
     Ok(buf.into())
 }
 
+/// An opaque cursor for calling a scalar function on multiple record batches.
+///
+/// The lifecycle of a cursor is:
+///
+/// 1. Open a cursor for a function with the exported symbol `arrowudfcursor_<base64>`,
+///    which calls [`scalar_cursor_open`]. The symbol is the same as `arrowudf_<base64>` except
+///    for the prefix.
+/// 2. Evaluate input batches with [`scalar_cursor_push`]. It can be called multiple times.
+/// 3. Take the output batches of all pushed inputs with [`scalar_cursor_pull`].
+///    Steps 2 and 3 can be repeated.
+/// 4. Close the cursor with [`scalar_cursor_close`].
+pub struct ScalarCursor {
+    function: ScalarFunction,
+    /// The output batches that have not been pulled.
+    outputs: Vec<RecordBatch>,
+}
+
+impl ScalarCursor {
+    /// Evaluate all record batches in the IPC buffer.
+    ///
+    /// If any batch fails, no output of the buffer is kept.
+    fn push(&mut self, input_bytes: &[u8]) -> Result<(), Error> {
+        let reader = FileReader::try_new(std::io::Cursor::new(input_bytes), None)?;
+        let mut outputs = vec![];
+        for input_batch in reader {
+            outputs.push((self.function)(&input_batch?)?);
+        }
+        self.outputs.append(&mut outputs);
+        Ok(())
+    }
+
+    /// Write all pending output batches to an IPC buffer.
+    fn pull(&mut self) -> Result<Option<Box<[u8]>>, Error> {
+        let Some(first) = self.outputs.first() else {
+            return Ok(None);
+        };
+        let mut buf = vec![];
+        let mut writer = FileWriter::try_new(&mut buf, &first.schema())?;
+        for batch in self.outputs.drain(..) {
+            writer.write(&batch)?;
+        }
+        writer.finish()?;
+        drop(writer);
+        Ok(Some(buf.into()))
+    }
+}
+
+/// Open a cursor for calling a scalar function on multiple record batches.
+///
+/// The cursor is written to `out_slice`. It must be closed by [`scalar_cursor_close`].
+///
+/// # Safety
+///
+/// `out_slice` must point to a valid buffer.
+pub unsafe fn scalar_cursor_open(function: ScalarFunction, out_slice: *mut CSlice) {
+    let cursor = Box::new(ScalarCursor {
+        function,
+        outputs: vec![],
+    });
+    out_slice.write(CSlice {
+        ptr: Box::into_raw(cursor) as *const u8,
+        len: std::mem::size_of::<ScalarCursor>(),
+    });
+}
+
+/// Evaluate the input record batches of a cursor.
+///
+/// The input IPC buffer pointed to by `ptr` and `len` may contain multiple record batches.
+/// Their outputs are kept in the cursor until [`scalar_cursor_pull`] is called.
+///
+/// The return value is 0 on success, -1 on error.
+/// If successful, an empty slice is written to `out`.
+/// If failed, the error message is written to `out`, and no output of the input is kept.
+///
+/// # Safety
+///
+/// `cursor` must be a valid cursor. `ptr`, `len`, `out` must point to a valid buffer.
+#[no_mangle]
+pub unsafe extern "C" fn scalar_cursor_push(
+    cursor: *mut ScalarCursor,
+    ptr: *const u8,
+    len: usize,
+    out: *mut CSlice,
+) -> i32 {
+    let cursor = cursor.as_mut().expect("null pointer");
+    let input = std::slice::from_raw_parts(ptr, len);
+    match cursor.push(input) {
+        Ok(()) => {
+            out.write(CSlice {
+                ptr: std::ptr::null(),
+                len: 0,
+            });
+            0
+        }
+        Err(err) => {
+            let msg = err.to_string().into_boxed_str();
+            out.write(CSlice {
+                ptr: msg.as_ptr(),
+                len: msg.len(),
+            });
+            std::mem::forget(msg);
+            -1
+        }
+    }
+}
+
+/// Take the output record batches of all inputs pushed to a cursor.
+///
+/// The output batches are written to a single IPC buffer pointed to by `out`, in the order of the
+/// inputs. If there is no pending output, an empty slice with a null pointer is written.
+/// The caller is responsible for deallocating the output buffer.
+///
+/// The return value is 0 on success, -1 on error.
+/// If failed, the error message is written to `out`.
+///
+/// # Safety
+///
+/// `cursor` must be a valid cursor. `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn scalar_cursor_pull(cursor: *mut ScalarCursor, out: *mut CSlice) -> i32 {
+    let cursor = cursor.as_mut().expect("null pointer");
+    match cursor.pull() {
+        Ok(Some(data)) => {
+            out.write(CSlice {
+                ptr: data.as_ptr(),
+                len: data.len(),
+            });
+            std::mem::forget(data);
+            0
+        }
+        Ok(None) => {
+            out.write(CSlice {
+                ptr: std::ptr::null(),
+                len: 0,
+            });
+            0
+        }
+        Err(err) => {
+            let msg = err.to_string().into_boxed_str();
+            out.write(CSlice {
+                ptr: msg.as_ptr(),
+                len: msg.len(),
+            });
+            std::mem::forget(msg);
+            -1
+        }
+    }
+}
+
+/// Close a cursor. Pending outputs are dropped.
+///
+/// # Safety
+///
+/// `cursor` must be a valid cursor.
+#[no_mangle]
+pub unsafe extern "C" fn scalar_cursor_close(cursor: *mut ScalarCursor) {
+    drop(Box::from_raw(cursor));
+}
+
 /// An opaque type for iterating over record batches.
 pub struct RecordBatchIter {
     /// The input record batch is borrowed by `iter`. Its lifetime must be longer than `iter`.
     _input: Box<RecordBatch>,
     /// This iterator borrows `input`.
-    iter: Box<dyn Iterator<Item = RecordBatch>>,
+    iter: Box<dyn Iterator<Item = Result<RecordBatch, Error>>>,
+    /// The number of batches that have been returned.
+    batches: usize,
+    /// Whether the iterator has ended or failed.
+    done: bool,
+}
+
+impl RecordBatchIter {
+    /// Write the next record batch to an IPC buffer.
+    ///
+    /// If the function fails, its error is returned and the iterator ends. If it panics, the error
+    /// contains the number of returned batches.
+    fn try_next(&mut self) -> Result<Option<Box<[u8]>>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+        let next = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.iter.next()));
+        let batch = match next {
+            Ok(Some(Ok(batch))) => batch,
+            Ok(Some(Err(err))) => {
+                self.done = true;
+                return Err(err);
+            }
+            Ok(None) => {
+                self.done = true;
+                return Ok(None);
+            }
+            Err(payload) => {
+                self.done = true;
+                let msg = if let Some(s) = payload.downcast_ref::<&str>() {
+                    s.to_string()
+                } else if let Some(s) = payload.downcast_ref::<String>() {
+                    s.clone()
+                } else {
+                    "unknown panic".to_string()
+                };
+                return Err(Error::ExternalError(
+                    format!(
+                        "table function panicked after {} batches: {msg}",
+                        self.batches
+                    )
+                    .into(),
+                ));
+            }
+        };
+        self.batches += 1;
+        let mut buf = vec![];
+        let mut writer = FileWriter::try_new(&mut buf, &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        drop(writer);
+        Ok(Some(buf.into()))
+    }
 }
 
 /// A wrapper for calling table functions from C.
@@ -179,6 +398,14 @@ fn call_table(function: TableFunction, input_bytes: &[u8]) -> Result<Box<RecordB
         .next()
         .ok_or_else(|| Error::IpcError("no record batch".into()))??;
 
+    iter_table(function, input_batch)
+}
+
+/// Call a table function on the input batch and keep the input alive with the iterator.
+fn iter_table(
+    function: TableFunction,
+    input_batch: RecordBatch,
+) -> Result<Box<RecordBatchIter>, Error> {
     let input = Box::new(input_batch);
     // SAFETY: The lifetime of `input` is longer than `iter`.
     let input_ref: &RecordBatch = unsafe { std::mem::transmute(input.as_ref()) };
@@ -186,38 +413,249 @@ fn call_table(function: TableFunction, input_bytes: &[u8]) -> Result<Box<RecordB
     Ok(Box::new(RecordBatchIter {
         _input: input,
         iter,
+        batches: 0,
+        done: false,
     }))
 }
 
+/// An opaque session for calling a table function on multiple record batches that share
+/// dictionaries.
+///
+/// Unlike [`table_wrapper`], the inputs of a session are messages of a single Arrow IPC stream.
+/// The session keeps the dictionaries it has decoded, so later record batches can reference them
+/// by dictionary ID without resending them. The lifecycle of a session is:
+///
+/// 1. Open a session for a table function with the exported symbol `arrowudfsession_<base64>`,
+///    which calls [`table_session_open`]. The preamble contains the schema message, optionally
+///    followed by the dictionary batches shared by all calls. The symbol is the same as
+///    `arrowudf_<base64>` except for the prefix.
+/// 2. Call the function with [`table_session_call`]. The input contains exactly one record batch,
+///    optionally preceded by dictionary batches that replace or extend (delta) the kept ones.
+///    If the preamble has no dictionary, each call sends its own dictionaries as usual.
+/// 3. Close the session with [`table_session_close`].
+///
+/// Dictionary-encoded columns are decoded to their value type before calling the function.
+pub struct TableSession {
+    function: TableFunction,
+    decoder: StreamDecoder,
+}
+
+impl TableSession {
+    /// Decode the messages in the buffer, returning the record batch if any.
+    ///
+    /// Returns an error if the buffer contains more than one record batch or an incomplete message.
+    fn decode(&mut self, bytes: &[u8]) -> Result<Option<RecordBatch>, Error> {
+        let mut buffer = Buffer::from(bytes);
+        let batch = self.decoder.decode(&mut buffer)?;
+        if !buffer.is_empty() {
+            return Err(Error::IpcError(match batch {
+                Some(_) => "expect only one record batch".into(),
+                None => "incomplete message".into(),
+            }));
+        }
+        Ok(batch)
+    }
+
+    /// Call the function on the record batch in the buffer.
+    fn call(&mut self, input_bytes: &[u8]) -> Result<Box<RecordBatchIter>, Error> {
+        let input_batch = self
+            .decode(input_bytes)?
+            .ok_or_else(|| Error::IpcError("no record batch".into()))?;
+        iter_table(self.function, decode_dictionaries(&input_batch)?)
+    }
+}
+
+/// Replace dictionary-encoded columns with their values.
+fn decode_dictionaries(batch: &RecordBatch) -> Result<RecordBatch, Error> {
+    if !batch
+        .columns()
+        .iter()
+        .any(|c| c.as_any_dictionary_opt().is_some())
+    {
+        return Ok(batch.clone());
+    }
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let Some(dict) = column.as_any_dictionary_opt() else {
+            fields.push(field.clone());
+            columns.push(column.clone());
+            continue;
+        };
+        let array = crate::codegen::take_dictionary_values(dict, dict.values())?;
+        let field = field
+            .as_ref()
+            .clone()
+            .with_data_type(array.data_type().clone());
+        fields.push(Arc::new(field));
+        columns.push(array);
+    }
+    let schema = Schema::new(fields).with_metadata(batch.schema().metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// Open a session for calling a table function on record batches that share dictionaries.
+///
+/// The preamble pointed to by `ptr` and `len` contains the schema message and optional
+/// dictionary batches of an IPC stream. See [`TableSession`].
+///
+/// The return value is 0 on success, -1 on error.
+/// If successful, the session is written to `out`. It must be closed by [`table_session_close`].
+/// If failed, the error message is written to `out`.
+///
+/// # Safety
+///
+/// `ptr`, `len`, `out` must point to a valid buffer.
+pub unsafe fn table_session_open(
+    function: TableFunction,
+    ptr: *const u8,
+    len: usize,
+    out: *mut CSlice,
+) -> i32 {
+    let preamble = std::slice::from_raw_parts(ptr, len);
+    let mut session = Box::new(TableSession {
+        function,
+        decoder: StreamDecoder::new(),
+    });
+    match session.decode(preamble) {
+        Ok(None) => {
+            out.write(CSlice {
+                ptr: Box::into_raw(session) as *const u8,
+                len: std::mem::size_of::<TableSession>(),
+            });
+            0
+        }
+        result => {
+            let msg = match result {
+                Err(err) => err.to_string(),
+                _ => "unexpected record batch in preamble".to_string(),
+            };
+            let msg = msg.into_boxed_str();
+            out.write(CSlice {
+                ptr: msg.as_ptr(),
+                len: msg.len(),
+            });
+            std::mem::forget(msg);
+            -1
+        }
+    }
+}
+
+/// Call the table function of a session.
+///
+/// The input IPC messages pointed to by `ptr` and `len` contain exactly one record batch,
+/// optionally preceded by dictionary batches.
+///
+/// The return value is 0 on success, -1 on error.
+/// If successful, the output iterator is written to `out`. See [`record_batch_iterator_next`].
+/// If failed, the error message is written to `out`.
+///
+/// # Safety
+///
+/// `session` must be a valid session. `ptr`, `len`, `out` must point to a valid buffer.
+#[no_mangle]
+pub unsafe extern "C" fn table_session_call(
+    session: *mut TableSession,
+    ptr: *const u8,
+    len: usize,
+    out: *mut CSlice,
+) -> i32 {
+    let session = session.as_mut().expect("null pointer");
+    let input = std::slice::from_raw_parts(ptr, len);
+    match session.call(input) {
+        Ok(iter) => {
+            out.write(CSlice {
+                ptr: Box::into_raw(iter) as *const u8,
+                len: std::mem::size_of::<RecordBatchIter>(),
+            });
+            0
+        }
+        Err(err) => {
+            let msg = err.to_string().into_boxed_str();
+            out.write(CSlice {
+                ptr: msg.as_ptr(),
+                len: msg.len(),
+            });
+            std::mem::forget(msg);
+            -1
+        }
+    }
+}
+
+/// Close a session. The kept dictionaries are dropped.
+///
+/// Output iterators of the session are not affected.
+///
+/// # Safety
+///
+/// `session` must be a valid session.
+#[no_mangle]
+pub unsafe extern "C" fn table_session_close(session: *mut TableSession) {
+    drop(Box::from_raw(session));
+}
+
 /// Get the next record batch from the iterator.
 ///
 /// The output record batch is written to the buffer pointed to by `out`.
 /// The caller is responsible for deallocating the output buffer.
 ///
+/// If the function fails, the error message is written to `out` in place of a batch and the iterator
+/// ends. Use [`record_batch_iterator_try_next`] to get the error instead.
+///
 /// # Safety
 ///
 /// `iter` and `out` must be valid pointers.
 #[no_mangle]
 pub unsafe extern "C" fn record_batch_iterator_next(iter: *mut RecordBatchIter, out: *mut CSlice) {
+    record_batch_iterator_try_next(iter, out);
+}
+
+/// Get the next record batch from the iterator, catching errors and panics of the function.
+///
+/// Batches are returned as soon as they are complete, so the batches before a failure are
+/// delivered to the caller. The return value is 0 on success, -1 on error.
+/// - If a batch is available, it is written to `out`.
+/// - If the stream ended normally, a null pointer is written to `out`.
+/// - If the function failed or panicked, the error message is written to `out`. The message of a
+///   panic contains the number of batches returned before the failure. The iterator has ended and
+///   returns null afterwards.
+///
+/// The caller is responsible for deallocating the output buffer.
+///
+/// # Safety
+///
+/// `iter` and `out` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn record_batch_iterator_try_next(
+    iter: *mut RecordBatchIter,
+    out: *mut CSlice,
+) -> i32 {
     let iter = iter.as_mut().expect("null pointer");
-    if let Some(batch) = iter.iter.next() {
-        let mut buf = vec![];
-        let mut writer = FileWriter::try_new(&mut buf, &batch.schema()).unwrap();
-        writer.write(&batch).unwrap();
-        writer.finish().unwrap();
-        drop(writer);
-        let buf = buf.into_boxed_slice();
-
-        out.write(CSlice {
-            ptr: buf.as_ptr(),
-            len: buf.len(),
-        });
-        std::mem::forget(buf);
-    } else {
-        out.write(CSlice {
-            ptr: std::ptr::null(),
-            len: 0,
-        });
+    match iter.try_next() {
+        Ok(Some(data)) => {
+            out.write(CSlice {
+                ptr: data.as_ptr(),
+                len: data.len(),
+            });
+            std::mem::forget(data);
+            0
+        }
+        Ok(None) => {
+            out.write(CSlice {
+                ptr: std::ptr::null(),
+                len: 0,
+            });
+            0
+        }
+        Err(err) => {
+            let msg = err.to_string().into_boxed_str();
+            out.write(CSlice {
+                ptr: msg.as_ptr(),
+                len: msg.len(),
+            });
+            std::mem::forget(msg);
+            -1
+        }
     }
 }
 
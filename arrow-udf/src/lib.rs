@@ -15,13 +15,29 @@
 #![doc = include_str!("../README.md")]
 
 use arrow_array::RecordBatch;
+/// The error type of Arrow UDF operations, which is `Send + Sync + 'static`.
+///
+/// Any error can be converted into it with [`ArrowError::from_external_error`], or
+/// `ExternalError(err.into())` for an `anyhow::Error`.
+///
+/// [`ArrowError::from_external_error`]: arrow_schema::ArrowError::from_external_error
 pub use arrow_schema::ArrowError as Error;
-pub use arrow_udf_macros::function;
+pub use arrow_udf_macros::{function, window};
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+
+// the errors of generated functions can be sent across threads, e.g. returned from async tasks
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<Error>();
+};
 
 /// A specialized `Result` type for Arrow UDF operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub mod ffi;
+pub mod session;
 #[cfg(feature = "global_registry")]
 pub mod sig;
 pub mod types;
@@ -29,9 +45,102 @@ pub mod types;
 /// A scalar function that operates on a record batch.
 pub type ScalarFunction = fn(input: &RecordBatch) -> Result<RecordBatch>;
 
+/// A boxed future that can be sent across threads.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An async scalar function that operates on a record batch.
+///
+/// The returned future must be awaited by the host, e.g. on its async runtime.
+pub type AsyncScalarFunction =
+    for<'a> fn(input: &'a RecordBatch) -> BoxFuture<'a, Result<RecordBatch>>;
+
 /// A table function that operates on a record batch and returns an iterator of record batches.
-pub type TableFunction =
-    for<'a> fn(input: &'a RecordBatch) -> Result<Box<dyn Iterator<Item = RecordBatch> + 'a>>;
+///
+/// An error yielded by the iterator means that the function failed while producing the output.
+/// The iterator should not be polled after it.
+pub type TableFunction = for<'a> fn(
+    input: &'a RecordBatch,
+) -> Result<Box<dyn Iterator<Item = Result<RecordBatch>> + 'a>>;
+
+/// A window function that operates on an ordered partition.
+///
+/// `frames[i]` is the range of rows in the window frame of the `i`-th row.
+/// It returns a record batch with one row for each input row.
+pub type WindowFunction =
+    fn(partition: &RecordBatch, frames: &[Range<usize>]) -> Result<RecordBatch>;
+
+/// Function pointer.
+#[derive(Clone, Copy)]
+pub enum FunctionKind {
+    Scalar(ScalarFunction),
+    AsyncScalar(AsyncScalarFunction),
+    Table(TableFunction),
+    Window(WindowFunction),
+}
+
+impl FunctionKind {
+    /// Returns the name of the function kind: `"scalar"`, `"async_scalar"`, `"table"` or `"window"`.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Scalar(_) => "scalar",
+            Self::AsyncScalar(_) => "async_scalar",
+            Self::Table(_) => "table",
+            Self::Window(_) => "window",
+        }
+    }
+
+    /// Check if the function is a scalar function.
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, Self::Scalar(_))
+    }
+
+    /// Check if the function is an async scalar function.
+    pub fn is_async_scalar(&self) -> bool {
+        matches!(self, Self::AsyncScalar(_))
+    }
+
+    /// Check if the function is a table function.
+    pub fn is_table(&self) -> bool {
+        matches!(self, Self::Table(_))
+    }
+
+    /// Check if the function is a window function.
+    pub fn is_window(&self) -> bool {
+        matches!(self, Self::Window(_))
+    }
+
+    /// Convert to a scalar function.
+    pub fn as_scalar(&self) -> Option<ScalarFunction> {
+        match self {
+            Self::Scalar(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Convert to an async scalar function.
+    pub fn as_async_scalar(&self) -> Option<AsyncScalarFunction> {
+        match self {
+            Self::AsyncScalar(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Convert to a table function.
+    pub fn as_table(&self) -> Option<TableFunction> {
+        match self {
+            Self::Table(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Convert to a window function.
+    pub fn as_window(&self) -> Option<WindowFunction> {
+        match self {
+            Self::Window(f) => Some(*f),
+            _ => None,
+        }
+    }
+}
 
 /// Internal APIs used by macros.
 #[doc(hidden)]
@@ -46,4 +155,420 @@ pub mod codegen {
     pub use once_cell;
     pub use rust_decimal;
     pub use serde_json;
+    pub use uuid;
+
+    use std::sync::Arc;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{
+        BinaryType, BinaryViewType, ByteArrayType, ByteViewType, Decimal128Type, Decimal256Type,
+        DecimalType, Int16Type, Int32Type, Int64Type, RunEndIndexType, StringViewType, Utf8Type,
+    };
+    use arrow_array::{
+        builder::StringBuilder, AnyDictionaryArray, Array, ArrayRef, ArrowNativeTypeOp,
+        BooleanArray, GenericByteArray, GenericByteViewArray, PrimitiveArray, RecordBatch,
+        RunArray, StringArray, UInt32Array,
+    };
+    use arrow_buffer::{ArrowNativeType, BooleanBuffer, NullBuffer};
+    use arrow_schema::{ArrowError, DataType, Field, Schema};
+
+    /// A builder of the error column that is only allocated on the first error.
+    ///
+    /// Until then, appending nulls only increases the length.
+    pub struct ErrorBuilder {
+        builder: Option<StringBuilder>,
+        capacity: usize,
+        len: usize,
+    }
+
+    impl ErrorBuilder {
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self {
+                builder: None,
+                capacity,
+                len: 0,
+            }
+        }
+
+        #[inline]
+        pub fn append_null(&mut self) {
+            match &mut self.builder {
+                Some(builder) => builder.append_null(),
+                None => self.len += 1,
+            }
+        }
+
+        #[cold]
+        pub fn append_value(&mut self, error: impl AsRef<str>) {
+            let (capacity, len) = (self.capacity, self.len);
+            let builder = self.builder.get_or_insert_with(|| {
+                let mut builder = StringBuilder::with_capacity(capacity.max(len + 1), 16);
+                for _ in 0..len {
+                    builder.append_null();
+                }
+                builder
+            });
+            builder.append_value(error);
+        }
+
+        /// Builds the error array and resets the builder.
+        pub fn finish(&mut self) -> StringArray {
+            let len = std::mem::take(&mut self.len);
+            match self.builder.take() {
+                Some(mut builder) => builder.finish(),
+                None => StringArray::new_null(len),
+            }
+        }
+    }
+
+    /// A timestamp returned by a user function.
+    ///
+    /// Both naive timestamps and UTC timestamps can be returned as `timestamp`.
+    pub trait TimestampValue {
+        /// Returns the number of microseconds since the Unix epoch.
+        fn to_micros(self) -> i64;
+
+        /// Returns the number of nanoseconds since the Unix epoch.
+        ///
+        /// Returns `None` if the timestamp is out of the range of `i64` nanoseconds, i.e. before
+        /// 1677 or after 2262.
+        fn to_nanos(self) -> Option<i64>;
+    }
+
+    impl TimestampValue for chrono::NaiveDateTime {
+        fn to_micros(self) -> i64 {
+            self.and_utc().timestamp_micros()
+        }
+
+        fn to_nanos(self) -> Option<i64> {
+            self.and_utc().to_nanos()
+        }
+    }
+
+    impl TimestampValue for chrono::DateTime<chrono::Utc> {
+        fn to_micros(self) -> i64 {
+            self.timestamp_micros()
+        }
+
+        fn to_nanos(self) -> Option<i64> {
+            self.timestamp_nanos_opt()
+        }
+    }
+
+    /// Expands the `values` computed for the dictionary values of `dict` to each row of `dict`.
+    ///
+    /// Rows with a null key are null.
+    pub fn take_dictionary_values(
+        dict: &dyn AnyDictionaryArray,
+        values: &ArrayRef,
+    ) -> Result<ArrayRef, ArrowError> {
+        arrow_select::take::take(values.as_ref(), dict.keys(), None)
+    }
+
+    /// Applies a pure function to a boolean array with bitwise operations.
+    ///
+    /// The function is called once for each possible input instead of for each row.
+    pub fn boolean_unary(a: &BooleanArray, f: impl Fn(bool) -> bool) -> BooleanArray {
+        let len = a.len();
+        let values = match (f(false), f(true)) {
+            (false, false) => BooleanBuffer::new_unset(len),
+            (false, true) => a.values().clone(),
+            (true, false) => !a.values(),
+            (true, true) => BooleanBuffer::new_set(len),
+        };
+        BooleanArray::new(values, a.nulls().cloned())
+    }
+
+    /// Applies a pure function to two boolean arrays with bitwise operations.
+    ///
+    /// The function is called once for each possible input instead of for each row.
+    pub fn boolean_binary(
+        a: &BooleanArray,
+        b: &BooleanArray,
+        f: impl Fn(bool, bool) -> bool,
+    ) -> Result<BooleanArray, ArrowError> {
+        if a.len() != b.len() {
+            return Err(ArrowError::ComputeError(
+                "Cannot perform binary operation on arrays of different length".to_string(),
+            ));
+        }
+        let select = |array: &BooleanArray, value: bool| match value {
+            true => array.values().clone(),
+            false => !array.values(),
+        };
+        // the union of the rows matching each input for which the function returns true
+        let mut values = BooleanBuffer::new_unset(a.len());
+        for x in [false, true] {
+            for y in [false, true] {
+                if f(x, y) {
+                    values = &values | &(&select(a, x) & &select(b, y));
+                }
+            }
+        }
+        Ok(BooleanArray::new(
+            values,
+            NullBuffer::union(a.nulls(), b.nulls()),
+        ))
+    }
+
+    /// Infers the return type of a function returning `any` from its `i`-th argument.
+    ///
+    /// The argument is declared as `map<K, any>` if `map` is set, and the return type is the value
+    /// type of the map. Otherwise it is declared as `any`, and the return type is its type.
+    pub fn infer_any_type(
+        name: &str,
+        args: &[Field],
+        i: usize,
+        map: bool,
+    ) -> Result<Field, ArrowError> {
+        let arg = args.get(i).ok_or_else(|| {
+            ArrowError::InvalidArgumentError(format!("missing the {i}-th argument"))
+        })?;
+        let data_type = match arg.data_type() {
+            DataType::Map(entries, _) if map => match entries.data_type() {
+                DataType::Struct(fields) if fields.len() == 2 => {
+                    return Ok(fields[1].as_ref().clone().with_name(name));
+                }
+                _ => None,
+            },
+            data_type if !map => Some(data_type.clone()),
+            _ => None,
+        };
+        match data_type {
+            Some(data_type) => Ok(Field::new(name, data_type, true)),
+            None => Err(ArrowError::InvalidArgumentError(format!(
+                "expect map for the {i}-th argument, got {}",
+                arg.data_type()
+            ))),
+        }
+    }
+
+    /// Decodes the `RunEndEncoded` columns of the input to plain arrays of their values.
+    ///
+    /// Returns `None` if there is no such column.
+    pub fn decode_run_end_encoded(input: &RecordBatch) -> Result<Option<RecordBatch>, ArrowError> {
+        let is_run_end_encoded =
+            |array: &ArrayRef| matches!(array.data_type(), DataType::RunEndEncoded(_, _));
+        if !input.columns().iter().any(is_run_end_encoded) {
+            return Ok(None);
+        }
+        let mut fields = Vec::with_capacity(input.num_columns());
+        let mut columns = Vec::with_capacity(input.num_columns());
+        for (field, column) in input.schema().fields().iter().zip(input.columns()) {
+            let column = match column.data_type() {
+                DataType::RunEndEncoded(run_ends, _) => match run_ends.data_type() {
+                    DataType::Int16 => decode_run_array(column.as_run::<Int16Type>())?,
+                    DataType::Int32 => decode_run_array(column.as_run::<Int32Type>())?,
+                    DataType::Int64 => decode_run_array(column.as_run::<Int64Type>())?,
+                    t => {
+                        return Err(ArrowError::InvalidArgumentError(format!(
+                            "invalid run end type: {t}"
+                        )))
+                    }
+                },
+                _ => column.clone(),
+            };
+            fields.push(
+                field
+                    .as_ref()
+                    .clone()
+                    .with_data_type(column.data_type().clone()),
+            );
+            columns.push(column);
+        }
+        let schema = Schema::new_with_metadata(fields, input.schema().metadata().clone());
+        RecordBatch::try_new(Arc::new(schema), columns).map(Some)
+    }
+
+    /// Rescales the decimal columns of the input to the scales expected by the function.
+    ///
+    /// `decimals` contains the `(index, precision, scale)` of each `decimal128` and `decimal256`
+    /// argument. If a value can not be represented in the expected scale, it is rounded half away
+    /// from zero when `round` is set, otherwise an error is returned.
+    ///
+    /// Returns `None` if all decimal columns already have the expected scale.
+    pub fn rescale_decimal_columns(
+        input: &RecordBatch,
+        decimals: &[(usize, u8, i8)],
+        round: bool,
+    ) -> Result<Option<RecordBatch>, ArrowError> {
+        let scale_of = |i: usize| match input.column(i).data_type() {
+            DataType::Decimal128(_, s) | DataType::Decimal256(_, s) => Some(*s),
+            _ => None,
+        };
+        if decimals
+            .iter()
+            .all(|&(i, _, scale)| scale_of(i).map_or(true, |s| s == scale))
+        {
+            return Ok(None);
+        }
+        let schema = input.schema();
+        let mut fields = schema.fields().to_vec();
+        let mut columns = input.columns().to_vec();
+        for &(i, precision, scale) in decimals {
+            let column = &columns[i];
+            let rescaled: ArrayRef = match column.data_type() {
+                DataType::Decimal128(_, s) if *s != scale => Arc::new(rescale_decimal(
+                    column.as_primitive::<Decimal128Type>(),
+                    i,
+                    precision,
+                    scale,
+                    round,
+                )?),
+                DataType::Decimal256(_, s) if *s != scale => Arc::new(rescale_decimal(
+                    column.as_primitive::<Decimal256Type>(),
+                    i,
+                    precision,
+                    scale,
+                    round,
+                )?),
+                _ => continue,
+            };
+            fields[i] = Arc::new(
+                fields[i]
+                    .as_ref()
+                    .clone()
+                    .with_data_type(rescaled.data_type().clone()),
+            );
+            columns[i] = rescaled;
+        }
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        RecordBatch::try_new(Arc::new(schema), columns).map(Some)
+    }
+
+    /// Rescales the `i`-th argument to `Decimal(precision, scale)`.
+    fn rescale_decimal<T: DecimalType>(
+        array: &PrimitiveArray<T>,
+        i: usize,
+        precision: u8,
+        scale: i8,
+        round: bool,
+    ) -> Result<PrimitiveArray<T>, ArrowError> {
+        let from = array.scale();
+        let ten = T::Native::usize_as(10);
+        let rescaled = if scale > from {
+            let factor = ten.pow_checked((scale - from) as u32)?;
+            array.try_unary::<_, T, _>(|v| v.mul_checked(factor))?
+        } else {
+            let factor = ten.pow_checked((from - scale) as u32)?;
+            let half = factor.div_wrapping(T::Native::usize_as(2));
+            array.try_unary::<_, T, _>(|v| {
+                let (quotient, remainder) = (v.div_wrapping(factor), v.mod_wrapping(factor));
+                if remainder.is_zero() {
+                    Ok(quotient)
+                } else if !round {
+                    Err(ArrowError::CastError(format!(
+                        "cannot rescale the {i}-th argument from scale {from} to {scale} without rounding"
+                    )))
+                } else if remainder >= half {
+                    Ok(quotient.add_wrapping(T::Native::ONE))
+                } else if remainder <= half.neg_wrapping() {
+                    Ok(quotient.sub_wrapping(T::Native::ONE))
+                } else {
+                    Ok(quotient)
+                }
+            })?
+        };
+        let rescaled = rescaled.with_precision_and_scale(precision, scale)?;
+        rescaled.validate_decimal_precision(precision)?;
+        Ok(rescaled)
+    }
+
+    /// Converts the value of a `decimal128(p,s)` argument to `rust_decimal::Decimal`.
+    ///
+    /// Returns an error if the value does not fit in the 96-bit mantissa of `Decimal`.
+    pub fn decimal128_to_decimal(
+        value: i128,
+        scale: u32,
+    ) -> Result<rust_decimal::Decimal, ArrowError> {
+        rust_decimal::Decimal::try_from_i128_with_scale(value, scale).map_err(|e| {
+            ArrowError::CastError(format!(
+                "cannot convert {value} with scale {scale} to decimal: {e}"
+            ))
+        })
+    }
+
+    /// Converts a `rust_decimal::Decimal` returned by a function to the value of `decimal128(p,s)`.
+    ///
+    /// The value is rounded to the scale. Returns an error if it does not fit in the precision.
+    pub fn decimal_to_decimal128(
+        mut value: rust_decimal::Decimal,
+        precision: u8,
+        scale: u32,
+    ) -> Result<i128, ArrowError> {
+        value.rescale(scale);
+        if value.scale() != scale {
+            return Err(ArrowError::CastError(format!(
+                "cannot represent decimal {value} with scale {scale}"
+            )));
+        }
+        let mantissa = value.mantissa();
+        Decimal128Type::validate_decimal_precision(mantissa, precision)?;
+        Ok(mantissa)
+    }
+
+    /// Repeats each value of a `RunArray` for the length of its run.
+    fn decode_run_array<R: RunEndIndexType>(array: &RunArray<R>) -> Result<ArrayRef, ArrowError> {
+        let run_ends = array.run_ends();
+        let (offset, len) = (run_ends.offset(), run_ends.len());
+        // the index of the value of each row
+        let mut indices = Vec::with_capacity(len);
+        if len > 0 {
+            for i in run_ends.get_start_physical_index()..=run_ends.get_end_physical_index() {
+                let end = (run_ends.values()[i].as_usize() - offset).min(len);
+                indices.resize(end, i as u32);
+            }
+        }
+        arrow_select::take::take(array.values().as_ref(), &UInt32Array::from(indices), None)
+    }
+
+    /// A string argument that is either a `StringArray` or a `StringViewArray`.
+    pub type StringArrayOrView<'a> = ByteArrayOrView<'a, Utf8Type, StringViewType>;
+
+    /// A binary argument that is either a `BinaryArray` or a `BinaryViewArray`.
+    pub type BinaryArrayOrView<'a> = ByteArrayOrView<'a, BinaryType, BinaryViewType>;
+
+    /// A byte array or its view variant, read row by row without converting the view.
+    pub enum ByteArrayOrView<'a, T: ByteArrayType, V: ByteViewType<Native = T::Native>> {
+        Array(&'a GenericByteArray<T>),
+        View(&'a GenericByteViewArray<V>),
+    }
+
+    impl<T: ByteArrayType, V: ByteViewType<Native = T::Native>> Clone for ByteArrayOrView<'_, T, V> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<T: ByteArrayType, V: ByteViewType<Native = T::Native>> Copy for ByteArrayOrView<'_, T, V> {}
+
+    impl<'a, T: ByteArrayType, V: ByteViewType<Native = T::Native>> ByteArrayOrView<'a, T, V> {
+        /// Downcasts the array, returning `None` if it is neither of the two types.
+        pub fn try_new(array: &'a dyn Array) -> Option<Self> {
+            let any = array.as_any();
+            if let Some(array) = any.downcast_ref() {
+                return Some(Self::Array(array));
+            }
+            any.downcast_ref().map(Self::View)
+        }
+
+        #[inline]
+        pub fn is_null(&self, i: usize) -> bool {
+            match self {
+                Self::Array(array) => array.is_null(i),
+                Self::View(array) => array.is_null(i),
+            }
+        }
+
+        /// # Safety
+        ///
+        /// `i` must be less than the length of the array.
+        #[inline]
+        pub unsafe fn value_unchecked(&self, i: usize) -> &'a T::Native {
+            match self {
+                Self::Array(array) => array.value_unchecked(i),
+                Self::View(array) => array.value_unchecked(i),
+            }
+        }
+    }
 }
@@ -0,0 +1,109 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Session variables passed from the host to functions.
+//!
+//! The host attaches the variables to the schema metadata of the input batch, so that they are
+//! also available through FFI. Functions that take a `&SessionContext` argument receive the
+//! variables of each call.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+
+use crate::Result;
+
+/// The metadata key of the session timezone.
+pub const TIMEZONE_KEY: &str = "arrowudf.session.timezone";
+/// The metadata key of the session locale.
+pub const LOCALE_KEY: &str = "arrowudf.session.locale";
+/// The metadata key of the session search path, a comma-separated list of schemas.
+pub const SEARCH_PATH_KEY: &str = "arrowudf.session.search_path";
+
+/// The session variables of a function call.
+///
+/// # Example
+///
+/// ```
+/// # use arrow_udf::session::SessionContext;
+/// # use arrow_array::RecordBatch;
+/// # use arrow_schema::Schema;
+/// let session = SessionContext {
+///     timezone: Some("Asia/Shanghai".into()),
+///     ..Default::default()
+/// };
+/// let input = session.attach(RecordBatch::new_empty(Schema::empty().into())).unwrap();
+/// assert_eq!(SessionContext::from_metadata(input.schema().metadata()), session);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionContext {
+    /// The timezone, e.g. `UTC` or `Asia/Shanghai`.
+    pub timezone: Option<String>,
+    /// The locale, e.g. `en_US`.
+    pub locale: Option<String>,
+    /// The schemas to search for unqualified names, in order.
+    pub search_path: Vec<String>,
+}
+
+impl SessionContext {
+    /// Read the session variables from schema metadata.
+    ///
+    /// Missing variables are left unset.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        Self {
+            timezone: metadata.get(TIMEZONE_KEY).cloned(),
+            locale: metadata.get(LOCALE_KEY).cloned(),
+            search_path: metadata
+                .get(SEARCH_PATH_KEY)
+                .map(|path| {
+                    path.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the session variables as schema metadata.
+    ///
+    /// Unset variables are omitted.
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        if let Some(timezone) = &self.timezone {
+            metadata.insert(TIMEZONE_KEY.to_string(), timezone.clone());
+        }
+        if let Some(locale) = &self.locale {
+            metadata.insert(LOCALE_KEY.to_string(), locale.clone());
+        }
+        if !self.search_path.is_empty() {
+            metadata.insert(SEARCH_PATH_KEY.to_string(), self.search_path.join(","));
+        }
+        metadata
+    }
+
+    /// Attach the session variables to the schema metadata of an input batch.
+    ///
+    /// Other metadata of the schema is kept, and previous session variables are replaced.
+    pub fn attach(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let schema = batch.schema();
+        let mut metadata = schema.metadata().clone();
+        metadata
+            .retain(|key, _| ![TIMEZONE_KEY, LOCALE_KEY, SEARCH_PATH_KEY].contains(&key.as_str()));
+        metadata.extend(self.to_metadata());
+        let schema = schema.as_ref().clone().with_metadata(metadata);
+        batch.with_schema(Arc::new(schema))
+    }
+}
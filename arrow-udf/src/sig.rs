@@ -31,9 +31,11 @@
 //! let sig = REGISTRY.get("add", &[int32.clone(), int32.clone()], &int32).unwrap();
 //! ```
 
-use super::{ScalarFunction, TableFunction};
-use arrow_schema::{Field, Fields};
+pub use super::FunctionKind;
+use arrow_schema::{DataType, Field, Fields};
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::path::Path;
 
 /// A function signature.
 pub struct FunctionSignature {
@@ -49,57 +51,62 @@ pub struct FunctionSignature {
     /// The return type.
     pub return_type: Field,
 
-    /// The function
-    pub function: FunctionKind,
-}
+    /// Whether the function is volatile, i.e. may return different results for the same input.
+    pub volatile: bool,
 
-/// Function pointer.
-pub enum FunctionKind {
-    Scalar(ScalarFunction),
-    Table(TableFunction),
-}
+    /// The estimated number of rows produced for each input row.
+    ///
+    /// This is always 1 for scalar functions.
+    pub rows_per_input: usize,
 
-impl FunctionKind {
-    /// Check if the function is a scalar function.
-    pub fn is_scalar(&self) -> bool {
-        matches!(self, Self::Scalar(_))
-    }
+    /// The function to infer the return type from the argument types of a call.
+    ///
+    /// For variadic functions, the argument types include the variadic arguments,
+    /// so their length is the number of arguments of the call.
+    pub type_infer: Option<fn(&[Field]) -> Result<Field, crate::Error>>,
 
-    /// Check if the function is a table function.
-    pub fn is_table(&self) -> bool {
-        matches!(self, Self::Table(_))
-    }
+    /// The symbol of the function exported through FFI, i.e. `arrowudf_<base64>`.
+    ///
+    /// This is `None` for window functions and async functions, which are not exported.
+    pub export_name: Option<String>,
 
-    /// Convert to a scalar function.
-    pub fn as_scalar(&self) -> Option<ScalarFunction> {
-        match self {
-            Self::Scalar(f) => Some(*f),
-            _ => None,
-        }
-    }
+    /// The function
+    pub function: FunctionKind,
+}
 
-    /// Convert to a table function.
-    pub fn as_table(&self) -> Option<TableFunction> {
-        match self {
-            Self::Table(f) => Some(*f),
-            _ => None,
+impl FunctionSignature {
+    /// Returns the return type of a call with the given argument types.
+    ///
+    /// All argument types are passed to the type inference function if there is one.
+    /// Otherwise the declared return type is returned.
+    pub fn infer_return_type(&self, arg_types: &[Field]) -> Result<Field, crate::Error> {
+        if arg_types.len() < self.arg_types.len()
+            || (!self.variadic && arg_types.len() > self.arg_types.len())
+        {
+            return Err(crate::Error::InvalidArgumentError(format!(
+                "function {} expects {}{} arguments, got {}",
+                self.name,
+                if self.variadic { "at least " } else { "" },
+                self.arg_types.len(),
+                arg_types.len()
+            )));
+        }
+        match self.type_infer {
+            Some(f) => f(arg_types),
+            None => Ok(self.return_type.clone()),
         }
     }
-}
 
-impl FunctionSignature {
     /// Check if the function signature matches the given argument types and return type.
     fn matches(&self, arg_types: &[Field], return_type: &Field) -> bool {
-        if !(self.return_type.data_type() == return_type.data_type()
-            && self.return_type.metadata() == return_type.metadata())
-        {
+        if !type_matches(&self.return_type, return_type) {
             return false;
         }
         if arg_types.len() < self.arg_types.len() {
             return false;
         }
         for (target, ty) in self.arg_types.iter().zip(arg_types) {
-            if !(target.data_type() == ty.data_type() && target.metadata() == ty.metadata()) {
+            if !type_matches(target, ty) {
                 return false;
             }
         }
@@ -111,6 +118,33 @@ impl FunctionSignature {
     }
 }
 
+/// Check if the type of `field` matches the declared type `target`.
+///
+/// `any` is declared as `Null` with extension type `arrowudf.any`, which matches any type,
+/// `anyarray` matches any list, and `map<K, any>` matches any map with keys of type `K`.
+fn type_matches(target: &Field, field: &Field) -> bool {
+    let is_any = |f: &Field| {
+        f.metadata().get("ARROW:extension:name").map(|s| s.as_str()) == Some("arrowudf.any")
+    };
+    match (target.data_type(), field.data_type()) {
+        _ if is_any(target) => true,
+        (DataType::List(item), DataType::List(_)) if is_any(item.as_ref()) => true,
+        (DataType::Map(target_entries, _), DataType::Map(entries, _)) => {
+            match (target_entries.data_type(), entries.data_type()) {
+                (DataType::Struct(target_kv), DataType::Struct(kv))
+                    if target_kv.len() == 2 && kv.len() == 2 && is_any(&target_kv[1]) =>
+                {
+                    type_matches(&target_kv[0], &kv[0])
+                }
+                _ => target_entries == entries,
+            }
+        }
+        (target_type, data_type) => {
+            target_type == data_type && target.metadata() == field.metadata()
+        }
+    }
+}
+
 /// A collection of distributed `#[function]` signatures.
 #[doc(hidden)]
 #[linkme::distributed_slice]
@@ -149,3 +183,227 @@ impl FunctionRegistry {
         self.signatures.values().flatten()
     }
 }
+
+/// Serialize all functions in the global registry to a JSON array.
+///
+/// Each function is an object with the following keys:
+///
+/// - `name`: The name of the function.
+/// - `kind`: `"scalar"`, `"async_scalar"`, `"table"` or `"window"`.
+/// - `arg_types`: The argument types, not including the variadic arguments.
+/// - `variadic`: Whether the function is variadic.
+/// - `return_type`: The return type.
+/// - `volatile`: Whether the function is volatile.
+/// - `export_name`: The symbol exported through FFI, or `null` for window functions and async
+///   functions, which are not exported.
+///
+/// Types are rendered as the name of the arrow data type, e.g. `"Int32"`, except for nested types:
+///
+/// - a list is `{"list": <item type>}`,
+/// - a struct is `{"struct": [{"name": <field name>, "type": <field type>}, ...]}`,
+/// - a map is `{"map": {"key": <key type>, "value": <value type>}}`,
+/// - a type with extension metadata is `{"extension": <extension name>, "storage": <type>}`.
+///
+/// The functions are sorted by name.
+///
+/// # Example
+///
+/// ```
+/// use arrow_udf::{function, sig::catalog_json};
+///
+/// #[function("add(int, int) -> int")]
+/// fn add(lhs: i32, rhs: i32) -> i32 {
+///    lhs + rhs
+/// }
+///
+/// let catalog: serde_json::Value = serde_json::from_str(&catalog_json()).unwrap();
+/// let add = catalog.as_array().unwrap().iter().find(|f| f["name"] == "add").unwrap();
+/// assert_eq!(add["arg_types"], serde_json::json!(["Int32", "Int32"]));
+/// ```
+pub fn catalog_json() -> String {
+    let mut functions = REGISTRY.iter().map(signature_json).collect::<Vec<_>>();
+    functions.sort_by_cached_key(|f| {
+        (
+            f["name"].as_str().unwrap_or_default().to_string(),
+            f.to_string(),
+        )
+    });
+    Value::Array(functions).to_string()
+}
+
+/// Write the catalog of all functions in the global registry to a file.
+///
+/// The content is the same as [`catalog_json`]. The file is not written if its content is
+/// unchanged, so that build scripts watching it with `cargo:rerun-if-changed` are not rerun.
+///
+/// This allows a host linking many UDF crates to generate code from their functions at build
+/// time, e.g. `CREATE FUNCTION` statements. See the [example] for details.
+///
+/// [example]: https://github.com/risingwavelabs/arrow-udf/blob/main/arrow-udf/examples/ddl.rs
+pub fn write_catalog(path: impl AsRef<Path>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let catalog = catalog_json();
+    if std::fs::read_to_string(path).is_ok_and(|content| content == catalog) {
+        return Ok(());
+    }
+    std::fs::write(path, catalog)
+}
+
+/// Render a function signature as a JSON object.
+fn signature_json(sig: &FunctionSignature) -> Value {
+    json!({
+        "name": sig.name,
+        "kind": sig.function.kind_name(),
+        "arg_types": sig.arg_types.iter().map(|f| type_json(f)).collect::<Vec<_>>(),
+        "variadic": sig.variadic,
+        "return_type": type_json(&sig.return_type),
+        "volatile": sig.volatile,
+        "export_name": sig.export_name,
+    })
+}
+
+/// Render the type of a field as a JSON value.
+fn type_json(field: &Field) -> Value {
+    let ty = match field.data_type() {
+        DataType::List(item) | DataType::LargeList(item) => json!({ "list": type_json(item) }),
+        DataType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|f| json!({ "name": f.name(), "type": type_json(f) }))
+                .collect::<Vec<_>>();
+            json!({ "struct": fields })
+        }
+        DataType::Map(entries, _) => match entries.data_type() {
+            DataType::Struct(kv) if kv.len() == 2 => {
+                json!({ "map": { "key": type_json(&kv[0]), "value": type_json(&kv[1]) } })
+            }
+            t => Value::String(t.to_string()),
+        },
+        t => Value::String(t.to_string()),
+    };
+    match field.metadata().get("ARROW:extension:name") {
+        Some(name) => json!({ "extension": name, "storage": ty }),
+        None => ty,
+    }
+}
+
+/// A function signature parsed from its normalized form by [`parse_signature`].
+///
+/// Unlike [`FunctionSignature`], it does not contain the function itself, and the types are kept
+/// as normalized type names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSignature {
+    /// The name of the function.
+    pub name: String,
+
+    /// The normalized argument types, e.g. `int32`, `string[]` or `struct KeyValue`.
+    ///
+    /// The trailing `...` of variadic functions is not included.
+    pub arg_types: Vec<String>,
+
+    /// Whether the function is variadic.
+    pub variadic: bool,
+
+    /// The normalized return type.
+    pub return_type: String,
+
+    /// Whether it is a table function.
+    pub is_table_function: bool,
+}
+
+/// Parse a normalized function signature.
+///
+/// The symbol `arrowudf_<base64>` of each function exported by `#[function]` encodes its
+/// normalized signature in base64, with `$` and `_` instead of `+` and `/` and without padding.
+/// A normalized signature has the following grammar, without any whitespace except in struct types:
+///
+/// ```text
+/// signature := name "(" args ")" ("->" | "->>") type
+/// args      := "" | "..." | type ("," type)* ["," "..."]
+/// ```
+///
+/// `->>` is used for table functions. Types are normalized type names, e.g. `int32`, `string[]`,
+/// `struct KeyValue`, `map<string,int32>` or `decimal128(10,2)`.
+///
+/// # Example
+///
+/// ```
+/// use arrow_udf::sig::parse_signature;
+///
+/// let sig = parse_signature("concat_ws(string,...)->string").unwrap();
+/// assert_eq!(sig.name, "concat_ws");
+/// assert_eq!(sig.arg_types, ["string"]);
+/// assert!(sig.variadic);
+/// assert_eq!(sig.return_type, "string");
+/// assert!(!sig.is_table_function);
+/// ```
+pub fn parse_signature(s: &str) -> Result<ParsedSignature, crate::Error> {
+    let invalid = || crate::Error::ParseError(format!("invalid function signature: {s:?}"));
+    let (name, rest) = s.split_once('(').ok_or_else(invalid)?;
+    // skip the parentheses of types like `decimal128(p,s)`
+    let mut depth = 0;
+    let end = rest
+        .char_indices()
+        .find_map(|(i, c)| match c {
+            '(' => {
+                depth += 1;
+                None
+            }
+            ')' if depth == 0 => Some(i),
+            ')' => {
+                depth -= 1;
+                None
+            }
+            _ => None,
+        })
+        .ok_or_else(invalid)?;
+    let (args, rest) = (&rest[..end], &rest[end + 1..]);
+    let (is_table_function, return_type) = if let Some(ret) = rest.strip_prefix("->>") {
+        (true, ret)
+    } else if let Some(ret) = rest.strip_prefix("->") {
+        (false, ret)
+    } else {
+        return Err(invalid());
+    };
+    if name.is_empty() || return_type.is_empty() {
+        return Err(invalid());
+    }
+    let mut arg_types = match args {
+        "" => vec![],
+        _ => split_types(args),
+    };
+    let variadic = arg_types.last() == Some(&"...");
+    if variadic {
+        arg_types.pop();
+    }
+    if arg_types.iter().any(|t| t.is_empty() || *t == "...") {
+        return Err(invalid());
+    }
+    Ok(ParsedSignature {
+        name: name.to_string(),
+        arg_types: arg_types.into_iter().map(|t| t.to_string()).collect(),
+        variadic,
+        return_type: return_type.to_string(),
+        is_table_function,
+    })
+}
+
+/// Splits a comma-separated list of types, ignoring the commas inside `<>` and `()`.
+fn split_types(s: &str) -> Vec<&str> {
+    let mut types = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                types.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    types.push(&s[start..]);
+    types
+}
@@ -14,18 +14,33 @@
 
 //! Data types for user-defined functions.
 
+use std::ops::{Add, Sub};
+use std::sync::Arc;
+
 use arrow_array::builder::StructBuilder;
-use arrow_schema::Fields;
+use arrow_array::{make_array, Array, ArrayRef, ListArray};
+use arrow_buffer::{NullBuffer, OffsetBuffer};
+use arrow_data::transform::MutableArrayData;
+use arrow_data::ArrayData;
+use arrow_schema::{DataType, Field, Fields};
 pub use arrow_udf_macros::StructType;
 
 // re-export common types
+#[doc(no_inline)]
+pub use arrow_buffer::i256;
 pub use chrono;
 #[doc(no_inline)]
-pub use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+pub use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+pub use half;
+#[doc(no_inline)]
+pub use half::f16;
 pub use rust_decimal;
 #[doc(no_inline)]
 pub use rust_decimal::Decimal;
 pub use serde_json;
+pub use uuid;
+#[doc(no_inline)]
+pub use uuid::Uuid;
 
 /// Interval type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -35,6 +50,298 @@ pub struct Interval {
     pub nanos: i64,
 }
 
+impl Interval {
+    /// Creates an interval from nanoseconds in `i128`.
+    ///
+    /// Returns `None` if the nanoseconds overflow `i64`. Functions can return this as an error
+    /// with `Result<Interval, E>`.
+    pub fn checked_from_nanos(months: i32, days: i32, nanos: i128) -> Option<Self> {
+        Some(Self {
+            months,
+            days,
+            nanos: nanos.try_into().ok()?,
+        })
+    }
+
+    /// Creates an interval from nanoseconds in `i128`, saturating at the bounds of `i64`.
+    pub fn saturating_from_nanos(months: i32, days: i32, nanos: i128) -> Self {
+        Self {
+            months,
+            days,
+            nanos: nanos.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+        }
+    }
+
+    /// Adds two intervals field by field. Returns `None` if any field overflows.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Some(Self {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            nanos: self.nanos.checked_add(other.nanos)?,
+        })
+    }
+
+    /// Subtracts two intervals field by field. Returns `None` if any field overflows.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(Self {
+            months: self.months.checked_sub(other.months)?,
+            days: self.days.checked_sub(other.days)?,
+            nanos: self.nanos.checked_sub(other.nanos)?,
+        })
+    }
+
+    /// Creates an interval of nanoseconds from a `chrono::Duration`.
+    ///
+    /// Returns `None` if the duration overflows `i64` nanoseconds, i.e. it is longer than about
+    /// 292 years.
+    pub fn checked_from_duration(duration: chrono::Duration) -> Option<Self> {
+        Some(Self {
+            months: 0,
+            days: 0,
+            nanos: duration.num_nanoseconds()?,
+        })
+    }
+
+    /// Returns the nanoseconds part as a `chrono::Duration`.
+    ///
+    /// Months and days are not included, since their length depends on the date they are added to.
+    pub fn nanos_duration(&self) -> chrono::Duration {
+        chrono::Duration::nanoseconds(self.nanos)
+    }
+
+    /// Carries whole days of nanoseconds into days, like `justify_hours` in PostgreSQL.
+    ///
+    /// A day is counted as 24 hours. Months are not changed, since their number of days varies.
+    /// The days and nanoseconds of the result have the same sign, e.g. `1 day -1 hour` becomes
+    /// `23 hours`. Returns `None` if the days overflow.
+    pub fn normalize(self) -> Option<Self> {
+        const NANOS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000_000;
+        let mut days = self.days.checked_add((self.nanos / NANOS_PER_DAY) as i32)?;
+        let mut nanos = self.nanos % NANOS_PER_DAY;
+        if days > 0 && nanos < 0 {
+            days -= 1;
+            nanos += NANOS_PER_DAY;
+        } else if days < 0 && nanos > 0 {
+            days += 1;
+            nanos -= NANOS_PER_DAY;
+        }
+        Some(Self {
+            months: self.months,
+            days,
+            nanos,
+        })
+    }
+}
+
+/// Adds two intervals field by field.
+///
+/// # Panics
+///
+/// Panics if any field overflows. Use [`Interval::checked_add`] to handle overflow.
+impl Add for Interval {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other).expect("interval overflow")
+    }
+}
+
+/// Subtracts two intervals field by field.
+///
+/// # Panics
+///
+/// Panics if any field overflows. Use [`Interval::checked_sub`] to handle overflow.
+impl Sub for Interval {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(other).expect("interval overflow")
+    }
+}
+
+/// The variadic arguments of a row, i.e. the trailing columns after the fixed arguments.
+///
+/// This is passed to functions whose signature ends with `...`.
+/// If there are no trailing columns, the row is empty.
+#[derive(Debug, Clone, Copy)]
+pub struct VariadicRow<'a> {
+    columns: &'a [ArrayRef],
+    row: usize,
+}
+
+impl<'a> VariadicRow<'a> {
+    #[doc(hidden)]
+    pub fn new(columns: &'a [ArrayRef], row: usize) -> Self {
+        Self { columns, row }
+    }
+
+    /// Returns the number of variadic arguments.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns true if there is no variadic argument.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Returns the index of the current row.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the column of the `i`-th variadic argument.
+    pub fn column(&self, i: usize) -> &'a ArrayRef {
+        &self.columns[i]
+    }
+
+    /// Returns the columns of all variadic arguments.
+    pub fn columns(&self) -> &'a [ArrayRef] {
+        self.columns
+    }
+
+    /// Returns true if the `i`-th variadic argument is null in the current row.
+    pub fn is_null(&self, i: usize) -> bool {
+        self.columns[i].is_null(self.row)
+    }
+}
+
+/// A value of an `any` argument, whose type is only known at runtime.
+///
+/// The value is a row of the argument array. It can be returned as an element of `anyarray`.
+#[derive(Debug, Clone, Copy)]
+pub struct AnyValue<'a> {
+    array: &'a ArrayRef,
+    /// The index of the argument among all `any` arguments.
+    source: usize,
+    row: usize,
+}
+
+impl<'a> AnyValue<'a> {
+    #[doc(hidden)]
+    pub fn new(array: &'a ArrayRef, source: usize, row: usize) -> Self {
+        Self { array, source, row }
+    }
+
+    /// Returns the array of the argument.
+    pub fn array(&self) -> &'a ArrayRef {
+        self.array
+    }
+
+    /// Returns the index of the current row.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the data type of the value.
+    pub fn data_type(&self) -> &'a DataType {
+        self.array.data_type()
+    }
+}
+
+/// A builder for `anyarray` values, whose element type is the data type of the `any` arguments.
+///
+/// The elements are copied from the arrays of the `any` arguments, which must have the same type.
+#[doc(hidden)]
+pub struct AnyListBuilder<'a> {
+    item_type: DataType,
+    values: MutableArrayData<'a>,
+    offsets: Vec<i32>,
+    nulls: Vec<bool>,
+}
+
+impl<'a> AnyListBuilder<'a> {
+    /// Creates a builder from the data of all `any` arguments in order.
+    pub fn new(sources: &'a [ArrayData], capacity: usize) -> Result<Self, crate::Error> {
+        let item_type = any_type(sources)?;
+        let mut offsets = Vec::with_capacity(capacity + 1);
+        offsets.push(0);
+        Ok(Self {
+            item_type,
+            values: MutableArrayData::new(sources.iter().collect(), true, capacity),
+            offsets,
+            nulls: Vec::with_capacity(capacity),
+        })
+    }
+
+    /// Appends a list of values, where `None` is a null element.
+    pub fn append_value<'v, T: Into<Option<AnyValue<'v>>>>(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+    ) {
+        let mut len = *self.offsets.last().unwrap();
+        for value in values {
+            match value.into() {
+                Some(v) => self.values.extend(v.source, v.row, v.row + 1),
+                None => self.values.extend_nulls(1),
+            }
+            len += 1;
+        }
+        self.offsets.push(len);
+        self.nulls.push(true);
+    }
+
+    /// Appends a null list.
+    pub fn append_null(&mut self) {
+        self.offsets.push(*self.offsets.last().unwrap());
+        self.nulls.push(false);
+    }
+
+    /// Builds the list array.
+    pub fn finish(self) -> ListArray {
+        ListArray::new(
+            Arc::new(Field::new("item", self.item_type, true)),
+            OffsetBuffer::new(self.offsets.into()),
+            make_array(self.values.freeze()),
+            Some(NullBuffer::from(self.nulls)),
+        )
+    }
+}
+
+/// A builder for `any` values, whose type is the data type of the `any` arguments.
+///
+/// The values are copied from the arrays of the `any` arguments, which must have the same type.
+#[doc(hidden)]
+pub struct AnyBuilder<'a> {
+    values: MutableArrayData<'a>,
+}
+
+impl<'a> AnyBuilder<'a> {
+    /// Creates a builder from the data of all `any` arguments in order.
+    pub fn new(sources: &'a [ArrayData], capacity: usize) -> Result<Self, crate::Error> {
+        any_type(sources)?;
+        Ok(Self {
+            values: MutableArrayData::new(sources.iter().collect(), true, capacity),
+        })
+    }
+
+    /// Appends a value, where `None` is a null value.
+    pub fn append_option(&mut self, value: Option<AnyValue<'_>>) {
+        match value {
+            Some(v) => self.values.extend(v.source, v.row, v.row + 1),
+            None => self.values.extend_nulls(1),
+        }
+    }
+
+    /// Builds the array.
+    pub fn finish(self) -> ArrayRef {
+        make_array(self.values.freeze())
+    }
+}
+
+/// Returns the data type of the `any` arguments, which must be the same for all of them.
+fn any_type(sources: &[ArrayData]) -> Result<DataType, crate::Error> {
+    let ty = sources[0].data_type();
+    if let Some(source) = sources.iter().find(|s| s.data_type() != ty) {
+        return Err(crate::Error::InvalidArgumentError(format!(
+            "expect the same type for all `any` arguments, got {} and {}",
+            ty,
+            source.data_type()
+        )));
+    }
+    Ok(ty.clone())
+}
+
 /// A trait for user-defined struct types.
 ///
 /// This trait can be automatically derived with [`#[derive(StructType)]`](derive@StructType).
@@ -13,17 +13,25 @@
 // limitations under the License.
 
 use std::iter::Sum;
-use std::ops::{Add, Neg};
+use std::ops::{Add, Neg, Range};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use arrow_array::builder::{
+    Decimal128Builder, FixedSizeBinaryBuilder, Int32Builder, MapBuilder, StringBuilder,
+};
 use arrow_array::cast::AsArray;
 use arrow_array::temporal_conversions::time_to_time64us;
-use arrow_array::types::{Date32Type, Int32Type};
+use arrow_array::types::{
+    Date32Type, Decimal128Type, Int32Type, Int64Type, IntervalMonthDayNanoType,
+};
 use arrow_array::*;
 use arrow_cast::pretty::pretty_format_batches;
-use arrow_schema::{DataType, Field, Schema, TimeUnit};
-use arrow_udf::function;
+use arrow_data::transform::MutableArrayData;
+use arrow_schema::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use arrow_udf::session::SessionContext;
 use arrow_udf::types::*;
+use arrow_udf::{function, window, FunctionKind};
 use expect_test::{expect, Expect};
 
 // test no return value
@@ -41,6 +49,7 @@ fn zero() -> i32 {
 #[function("neg(int16) -> int16")]
 #[function("neg(int32) -> int32")]
 #[function("neg(int64) -> int64")]
+#[function("neg(float16) -> float16")]
 #[function("neg(float32) -> float32")]
 #[function("neg(float64) -> float64")]
 #[function("neg(decimal) -> decimal")]
@@ -57,11 +66,51 @@ fn gcd(mut a: i32, mut b: i32) -> i32 {
     a
 }
 
+#[function("sub(int, int) -> int", output_name = "result")]
+fn sub(x: i32, y: i32) -> i32 {
+    x - y
+}
+
 #[function("add(decimal, decimal) -> decimal")]
+#[function("add(decimal128(10,2), decimal128(10,2)) -> decimal128(11,2)")]
+#[function(
+    "add_rounded(decimal128(10,2), decimal128(10,2)) -> decimal128(11,2)",
+    round_decimal
+)]
+#[function("add_narrow(decimal128(10,2), decimal128(10,2)) -> decimal128(10,2)")]
+#[function("add(decimal256(60,2), decimal256(60,2)) -> decimal256(61,2)")]
 fn add<T: Add<Output = T>>(x: T, y: T) -> T {
     x + y
 }
 
+#[function("decimal_or_hundred(decimal128(10,2)) -> decimal128(4,2)")]
+fn decimal_or_hundred(x: Option<Decimal>) -> Decimal {
+    x.unwrap_or(Decimal::new(10000, 2))
+}
+
+#[function("repeat_decimal(int) -> decimal128(4,2)[]")]
+fn repeat_decimal(x: i32) -> impl Iterator<Item = Decimal> {
+    std::iter::repeat(Decimal::from(x)).take(2)
+}
+
+#[function("set_uuid_version(bytea(16)) -> fixedsizebinary(16)")]
+fn set_uuid_version(uuid: &[u8; 16]) -> [u8; 16] {
+    let mut uuid = *uuid;
+    uuid[6] = (uuid[6] & 0x0f) | 0x40;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+    uuid
+}
+
+#[function("xor(boolean, boolean) -> boolean")]
+fn xor(a: bool, b: bool) -> bool {
+    a ^ b
+}
+
+#[function("not(boolean) -> boolean")]
+fn not(a: bool) -> bool {
+    !a
+}
+
 #[function("identity(boolean) -> boolean")]
 #[function("identity(int8) -> int8")]
 #[function("identity(int16) -> int16")]
@@ -71,12 +120,14 @@ fn add<T: Add<Output = T>>(x: T, y: T) -> T {
 #[function("identity(uint16) -> uint16")]
 #[function("identity(uint32) -> uint32")]
 #[function("identity(uint64) -> uint64")]
+#[function("identity(float16) -> float16")]
 #[function("identity(float32) -> float32")]
 #[function("identity(float64) -> float64")]
 #[function("identity(decimal) -> decimal")]
 #[function("identity(date) -> date")]
 #[function("identity(time) -> time")]
 #[function("identity(timestamp) -> timestamp")]
+#[function("identity(timestamp(ns)) -> timestamp(ns)")]
 // #[function("identity(timestamptz) -> timestamptz")]
 #[function("identity(interval) -> interval")]
 #[function("identity(json) -> json")]
@@ -84,20 +135,174 @@ fn add<T: Add<Output = T>>(x: T, y: T) -> T {
 #[function("identity(binary) -> binary")]
 #[function("identity(largestring) -> largestring")]
 #[function("identity(largebinary) -> largebinary")]
+#[function("identity(uuid) -> uuid")]
+#[function("identity(json_text) -> json_text")]
 fn identity<T>(x: T) -> T {
     x
 }
 
+#[function("round(decimal, int = 0) -> decimal")]
+fn round(x: Decimal, scale: i32) -> Decimal {
+    x.round_dp(scale.max(0) as u32)
+}
+
+#[function("array_fill(any, int32) -> anyarray")]
+fn array_fill(x: AnyValue<'_>, n: i32) -> impl Iterator<Item = AnyValue<'_>> {
+    std::iter::repeat(x).take(n.max(0) as usize)
+}
+
+#[function("passthrough(any) -> any")]
+fn passthrough<T>(x: T) -> T {
+    x
+}
+
+#[function("coalesce(any, any) -> any")]
+fn coalesce<'a>(x: Option<AnyValue<'a>>, y: Option<AnyValue<'a>>) -> Option<AnyValue<'a>> {
+    x.or(y)
+}
+
+#[function("add_nanos(interval, int64) -> interval")]
+fn add_nanos(v: Interval, nanos: i64) -> Result<Interval, &'static str> {
+    Interval::checked_from_nanos(v.months, v.days, v.nanos as i128 + nanos as i128)
+        .ok_or("interval out of range")
+}
+
+#[function("saturating_add_nanos(interval, int64) -> interval")]
+fn saturating_add_nanos(v: Interval, nanos: i64) -> Interval {
+    Interval::saturating_from_nanos(v.months, v.days, v.nanos as i128 + nanos as i128)
+}
+
+#[function("interval_sub(interval, interval) -> interval")]
+fn interval_sub(x: Interval, y: Interval) -> Result<Interval, &'static str> {
+    x.checked_sub(y)
+        .and_then(Interval::normalize)
+        .ok_or("interval out of range")
+}
+
 #[function("option_add(int, int) -> int")]
 fn option_add(x: i32, y: Option<i32>) -> i32 {
     x + y.unwrap_or(0)
 }
 
+#[function("coalesce(int, int) -> int", called_on_null_input)]
+fn coalesce(x: Option<i32>, y: Option<i32>) -> Option<i32> {
+    x.or(y)
+}
+
+#[function("strict_add(int, int) -> int", strict)]
+fn strict_add(x: i32, y: Option<i32>) -> i32 {
+    x + y.expect("strict function should never see null")
+}
+
+#[function("is_null(int) -> boolean")]
+fn is_null(x: Option<i32>) -> bool {
+    x.is_none()
+}
+
+#[function("checked_neg(int) -> int", nullable = false)]
+fn checked_neg(x: i32) -> Option<i32> {
+    x.checked_neg()
+}
+
+#[function("async_double(int) -> int")]
+async fn async_double(x: i32) -> Option<i32> {
+    std::future::ready(x.checked_mul(2)).await
+}
+
+#[function("current_timezone() -> string")]
+fn current_timezone(session: &SessionContext) -> Option<String> {
+    session.timezone.clone()
+}
+
+#[function("qualify(string) -> setof string")]
+fn qualify<'a>(name: &'a str, session: &'a SessionContext) -> impl Iterator<Item = String> + 'a {
+    session
+        .search_path
+        .iter()
+        .map(move |schema| format!("{schema}.{name}"))
+}
+
+static CONTEXT_INITS: AtomicUsize = AtomicUsize::new(0);
+
+struct Context {
+    greeting: String,
+}
+
+fn build_context() -> Context {
+    CONTEXT_INITS.fetch_add(1, Ordering::Relaxed);
+    Context {
+        greeting: "Hello".to_string(),
+    }
+}
+
+#[function("greet(string) -> string", init = "build_context")]
+fn greet(name: &str, ctx: &Context) -> String {
+    format!("{}, {name}!", ctx.greeting)
+}
+
+#[function("greet_all(string) -> setof string", init = "build_greetings")]
+fn greet_all<'a>(names: &'a str, ctx: &'a Context) -> impl Iterator<Item = String> + 'a {
+    names
+        .split(',')
+        .map(move |name| format!("{}, {name}!", ctx.greeting))
+}
+
+fn build_greetings() -> Context {
+    Context {
+        greeting: "Hi".to_string(),
+    }
+}
+
+#[function(
+    "meters_to_feet(float64) -> float64",
+    arg_metadata = "0:unit=meter",
+    arg_metadata = "0:precision=high"
+)]
+fn meters_to_feet(x: f64) -> f64 {
+    x * 3.28084
+}
+
+#[function(
+    "scale_by(float64, int = 2) -> float64",
+    arg_metadata = "1:unit=factor"
+)]
+fn scale_by(x: f64, factor: i32) -> f64 {
+    x * factor as f64
+}
+
+static CHAR_LENGTH_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[function("char_length(string) -> int")]
+fn char_length(s: &str) -> i32 {
+    CHAR_LENGTH_CALLS.fetch_add(1, Ordering::Relaxed);
+    s.chars().count() as i32
+}
+
 #[function("div(int, int) -> int")]
 fn div(x: i32, y: i32) -> Result<i32, &'static str> {
     x.checked_div(y).ok_or("division by zero")
 }
 
+#[function("checked_div(int, int) -> int", error_column = "__udf_error")]
+fn checked_div(x: i32, y: i32) -> Result<i32, &'static str> {
+    div(x, y)
+}
+
+#[function("st_point(float64, float64) -> geometry")]
+fn st_point(x: f64, y: f64) -> Vec<u8> {
+    // WKB point in little endian
+    let mut wkb = vec![1];
+    wkb.extend_from_slice(&1u32.to_le_bytes());
+    wkb.extend_from_slice(&x.to_le_bytes());
+    wkb.extend_from_slice(&y.to_le_bytes());
+    wkb
+}
+
+#[function("st_x(geometry) -> float64")]
+fn st_x(wkb: &[u8]) -> Option<f64> {
+    Some(f64::from_le_bytes(wkb.get(5..13)?.try_into().unwrap()))
+}
+
 #[function("to_json(boolean) -> json")]
 #[function("to_json(int*) -> json")]
 #[function("to_json(uint*) -> json")]
@@ -115,6 +320,31 @@ fn datetime(date: NaiveDate, time: NaiveTime) -> NaiveDateTime {
     NaiveDateTime::new(date, time)
 }
 
+#[function("epoch_seconds(timestamp) -> int64", utc)]
+fn epoch_seconds(t: DateTime<Utc>) -> i64 {
+    t.timestamp()
+}
+
+#[function("add_hours(timestamp, int) -> timestamp", utc)]
+fn add_hours(t: DateTime<Utc>, hours: i32) -> DateTime<Utc> {
+    t + chrono::Duration::hours(hours as i64)
+}
+
+#[function("add_nanos(timestamp(ns), int64) -> timestamp(ns)", utc)]
+fn add_timestamp_nanos(t: DateTime<Utc>, nanos: i64) -> DateTime<Utc> {
+    t + chrono::Duration::nanoseconds(nanos)
+}
+
+#[function("to_nanos(timestamp) -> timestamp(ns)")]
+fn timestamp_to_nanos(t: NaiveDateTime) -> NaiveDateTime {
+    t
+}
+
+#[function("to_nanos_list(timestamp) -> timestamp(ns)[]")]
+fn timestamp_to_nanos_list(t: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> {
+    std::iter::once(t)
+}
+
 #[function("length(string) -> int")]
 #[function("length(binary) -> int")]
 #[function("length(largestring) -> int")]
@@ -123,6 +353,49 @@ fn length(s: impl AsRef<[u8]>) -> i32 {
     s.as_ref().len() as i32
 }
 
+#[function("concat_ws(string, ...) -> string")]
+fn concat_ws(sep: &str, rest: VariadicRow<'_>) -> String {
+    let i = rest.row();
+    (rest.columns().iter())
+        .filter(|column| column.is_valid(i))
+        .map(|column| column.as_string::<i32>().value(i))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[function("greatest(...) -> int64", type_infer = "greatest_type")]
+fn greatest(args: VariadicRow<'_>) -> Option<i64> {
+    let i = args.row();
+    (args.columns().iter())
+        .filter(|column| column.is_valid(i))
+        .map(|column| {
+            let column = arrow_cast::cast(&column.slice(i, 1), &DataType::Int64).unwrap();
+            column.as_primitive::<Int64Type>().value(0)
+        })
+        .max()
+}
+
+/// Returns the widest integer type of all arguments.
+fn greatest_type(args: &[Field]) -> Result<Field, arrow_schema::ArrowError> {
+    let mut ty = DataType::Null;
+    for arg in args {
+        ty = match (&ty, arg.data_type()) {
+            (_, DataType::Null) => ty,
+            (DataType::Null, t)
+            | (DataType::Int8, t @ (DataType::Int16 | DataType::Int32 | DataType::Int64))
+            | (DataType::Int16, t @ (DataType::Int32 | DataType::Int64))
+            | (DataType::Int32, t @ DataType::Int64) => t.clone(),
+            (_, DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64) => ty,
+            (_, t) => {
+                return Err(arrow_schema::ArrowError::InvalidArgumentError(format!(
+                    "greatest does not support {t}"
+                )))
+            }
+        };
+    }
+    Ok(Field::new("greatest", ty, true))
+}
+
 #[function("substring(string, int) -> string")]
 fn substring_string(s: &str, start: i32) -> &str {
     s.char_indices()
@@ -158,6 +431,18 @@ fn to_string4(x: i32, output: &mut impl std::fmt::Write) -> Option<()> {
     Some(())
 }
 
+#[function("to_json_array(int[]) -> json")]
+fn to_json_array(v: &[i32], output: &mut impl std::fmt::Write) {
+    output.write_char('[').unwrap();
+    for (i, x) in v.iter().enumerate() {
+        if i > 0 {
+            output.write_char(',').unwrap();
+        }
+        write!(output, "{x}").unwrap();
+    }
+    output.write_char(']').unwrap();
+}
+
 #[function("bytes1(int) -> binary")]
 fn bytes1(x: i32) -> Vec<u8> {
     vec![0; x as usize]
@@ -250,6 +535,38 @@ fn key_values(kv: &str) -> impl Iterator<Item = KeyValue<'_>> {
     })
 }
 
+#[function("map_sum(map<string,int>) -> int")]
+fn map_sum<'a>(map: impl Iterator<Item = (&'a str, Option<i32>)>) -> i32 {
+    map.filter_map(|(_, v)| v).sum()
+}
+
+#[function("map_decimal_count(map<string,decimal128(10,2)>) -> int")]
+fn map_decimal_count<'a>(map: impl Iterator<Item = (&'a str, Option<Decimal>)>) -> i32 {
+    map.filter_map(|(_, v)| v).count() as i32
+}
+
+#[function("map_first_bytes(map<string,fixedsizebinary(4)>) -> int")]
+fn map_first_bytes<'a>(map: impl Iterator<Item = (&'a str, Option<&'a [u8; 4]>)>) -> i32 {
+    map.filter_map(|(_, v)| v).map(|v| v[0] as i32).sum()
+}
+
+#[function("map_uuids(map<string,uuid>) -> int")]
+fn map_uuids<'a>(map: impl Iterator<Item = (&'a str, Option<Uuid>)>) -> i32 {
+    map.filter(|(_, v)| v.is_some()).count() as i32
+}
+
+#[function("to_map(string) -> map<string,string>", keys_sorted)]
+fn to_map(kv: &str) -> impl Iterator<Item = (&str, Option<&str>)> {
+    let mut entries = kv
+        .split(',')
+        .filter_map(|kv| kv.split_once('='))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|(k, v)| (k, (!v.is_empty()).then_some(v)))
+}
+
 #[derive(StructType)]
 struct StructOfAll {
     // FIXME: panic on 'StructBuilder and field_builders are of unequal lengths.'
@@ -315,11 +632,168 @@ fn struct_of_all() -> StructOfAll {
     }
 }
 
-#[function("range(int) -> setof int")]
+#[derive(StructType)]
+struct NameScores<'a> {
+    name: &'a str,
+    scores: Option<Vec<i32>>,
+}
+
+#[function("name_scores(string) -> struct NameScores")]
+fn name_scores(s: &str) -> NameScores<'_> {
+    match s.split_once(':') {
+        Some((name, scores)) => NameScores {
+            name,
+            scores: Some(scores.split(',').map(|s| s.parse().unwrap()).collect()),
+        },
+        None => NameScores {
+            name: s,
+            scores: None,
+        },
+    }
+}
+
+#[function("key_value_list(string) -> struct KeyValue[]")]
+fn key_value_list(kv: &str) -> Option<Vec<KeyValue<'_>>> {
+    kv.split(',')
+        .map(|kv| {
+            kv.split_once('=')
+                .map(|(key, value)| KeyValue { key, value })
+        })
+        .collect()
+}
+
+#[function("range(int) -> setof int", rows_per_input = 10)]
 fn range(x: i32) -> impl Iterator<Item = i32> {
     0..x
 }
 
+/// Yields the integer part and the hundredths of a decimal.
+#[function("split_decimal(decimal128(10,2)) -> setof int")]
+fn split_decimal(x: Decimal) -> impl Iterator<Item = i32> {
+    let hundredths = x.mantissa() as i32;
+    [hundredths / 100, hundredths % 100].into_iter()
+}
+
+#[function("range_values(int) -> setof int", no_row_index)]
+fn range_values(x: i32) -> impl Iterator<Item = i32> {
+    0..x
+}
+
+#[function("pair() -> setof int")]
+fn pair() -> impl Iterator<Item = i32> {
+    0..2
+}
+
+#[function("split_pair(string) -> setof string", max_rows_per_input = 2)]
+fn split_pair(s: &str) -> impl Iterator<Item = &str> {
+    s.split(',')
+}
+
+#[function("batch_range(int) ->> int", batches)]
+fn batch_range(input: &RecordBatch) -> impl Iterator<Item = RecordBatch> + '_ {
+    let n = input.column(0).as_primitive::<Int32Type>();
+    n.iter().enumerate().filter_map(|(i, n)| {
+        let n = n?;
+        let rows = Int32Array::from(vec![i as i32; n as usize]);
+        let values = Int32Array::from_iter_values(0..n);
+        let batch = RecordBatch::try_from_iter([
+            ("i", Arc::new(rows) as ArrayRef),
+            ("v", Arc::new(values) as ArrayRef),
+        ]);
+        Some(batch.unwrap())
+    })
+}
+
+#[function("checked_batch_range(int) ->> int", batches)]
+fn checked_batch_range(input: &RecordBatch) -> impl Iterator<Item = RecordBatch> + '_ {
+    batch_range(input).inspect(|batch| assert!(batch.num_rows() < 3, "too many rows"))
+}
+
+#[function("bad_batches(int) ->> int", batches)]
+fn bad_batches(input: &RecordBatch) -> Result<Vec<RecordBatch>, String> {
+    let batch = RecordBatch::try_from_iter([
+        ("row", Arc::new(Int32Array::from(vec![0])) as ArrayRef),
+        ("v", Arc::new(StringArray::from(vec!["0"])) as ArrayRef),
+    ]);
+    Ok(vec![batch.map_err(|e| e.to_string())?; input.num_rows()])
+}
+
+#[function("minus_mean(float64) -> float64", columnar)]
+fn minus_mean(x: &Float64Array) -> ArrayRef {
+    let (sum, count) = x
+        .iter()
+        .flatten()
+        .fold((0.0, 0), |(s, c), v| (s + v, c + 1));
+    let mean = sum / count as f64;
+    Arc::new(
+        x.iter()
+            .map(|v| v.map(|v| v - mean))
+            .collect::<Float64Array>(),
+    )
+}
+
+#[function("sum_columns(int32, ...) -> int32", columnar)]
+fn sum_columns(first: &Int32Array, rest: &[ArrayRef]) -> Result<ArrayRef, String> {
+    let mut sums: Vec<Option<i32>> = first.iter().collect();
+    for column in rest {
+        let column = column.as_primitive::<Int32Type>();
+        for (sum, v) in sums.iter_mut().zip(column.iter()) {
+            *sum = match (*sum, v) {
+                (Some(a), Some(b)) => Some(a.checked_add(b).ok_or("integer overflow")?),
+                _ => None,
+            };
+        }
+    }
+    Ok(Arc::new(Int32Array::from(sums)))
+}
+
+#[function("map_get(map<varchar, any>, varchar) -> any", columnar)]
+fn map_get(map: &MapArray, key: &StringArray) -> ArrayRef {
+    let keys = map.keys().as_string::<i32>();
+    let values = map.values().to_data();
+    let offsets = map.value_offsets();
+    let mut output = MutableArrayData::new(vec![&values], true, map.len());
+    for (i, key) in key.iter().enumerate() {
+        let found = key.filter(|_| map.is_valid(i)).and_then(|key| {
+            (offsets[i] as usize..offsets[i + 1] as usize).find(|&j| keys.value(j) == key)
+        });
+        match found {
+            Some(j) => output.extend(0, j, j + 1),
+            None => output.extend_nulls(1),
+        }
+    }
+    make_array(output.freeze())
+}
+
+#[function("bad_columnar(int32) -> int32", columnar)]
+fn bad_columnar(_: &Int32Array) -> ArrayRef {
+    Arc::new(Int32Array::from(vec![1]))
+}
+
+#[window("row_number() -> int64")]
+fn row_number(row: usize, _frame: Range<usize>) -> i64 {
+    row as i64 + 1
+}
+
+#[window("lag(int) -> int")]
+fn lag(values: &Int32Array, row: usize, _frame: Range<usize>) -> Option<i32> {
+    let prev = row.checked_sub(1)?;
+    values.is_valid(prev).then(|| values.value(prev))
+}
+
+#[window("frame_sum(int) -> int64")]
+fn frame_sum(values: &Int32Array, _row: usize, frame: Range<usize>) -> i64 {
+    frame
+        .filter(|i| values.is_valid(*i))
+        .map(|i| values.value(i) as i64)
+        .sum()
+}
+
+#[window("frame_count(decimal128(10,2)) -> int64")]
+fn frame_count(values: &Decimal128Array, _row: usize, frame: Range<usize>) -> i64 {
+    frame.filter(|i| values.is_valid(*i)).count() as i64
+}
+
 #[function("json_array_elements(json) ->> json")]
 fn json_array_elements(
     x: serde_json::Value,
@@ -374,133 +848,1528 @@ fn test_neg() {
 }
 
 #[test]
-fn test_div() {
-    let schema = Schema::new(vec![
-        Field::new("x", DataType::Int32, true),
-        Field::new("y", DataType::Int32, true),
-    ]);
-    let arg0 = Int32Array::from(vec![Some(1), Some(-1), None]);
-    let arg1 = Int32Array::from(vec![Some(0), Some(-1), None]);
-    let input =
-        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+fn test_neg_float16() {
+    let schema = Schema::new(vec![Field::new("float16", DataType::Float16, true)]);
+    let arg0 = Float16Array::from(vec![Some(f16::from_f32(1.5)), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let output = div_int32_int32_int32_eval(&input).unwrap();
+    let output = neg_float16_float16_eval(&input).unwrap();
     check(
         &[output],
         expect![[r#"
-        +-----+------------------+
-        | div | error            |
-        +-----+------------------+
-        |     | division by zero |
-        | 1   |                  |
-        |     |                  |
-        +-----+------------------+"#]],
+        +------+
+        | neg  |
+        +------+
+        | -1.5 |
+        |      |
+        +------+"#]],
     );
 }
 
 #[test]
-fn test_key_value() {
-    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
-    let arg0 = StringArray::from(vec!["a=b", "??"]);
-    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+fn test_boolean_bitwise() {
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Boolean, true),
+        Field::new("b", DataType::Boolean, true),
+    ]);
+    let arg0 = BooleanArray::from(vec![None, Some(true), Some(true), Some(false), Some(false)]);
+    let arg1 = BooleanArray::from(vec![Some(true), Some(true), Some(false), Some(true), None]);
+    // sliced arrays have non-zero offsets in their bitmaps
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0.slice(1, 4)), Arc::new(arg1.slice(1, 4))],
+    )
+    .unwrap();
+
+    let output = xor_boolean_boolean_boolean_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------+
+        | xor   |
+        +-------+
+        | false |
+        | true  |
+        | true  |
+        |       |
+        +-------+"#]],
+    );
 
-    let output = key_value_string_struct_KeyValue_eval(&input).unwrap();
+    let output = not_boolean_boolean_eval(&input).unwrap();
     check(
         &[output],
         expect![[r#"
-        +--------------------+
-        | key_value          |
-        +--------------------+
-        | {key: a, value: b} |
-        |                    |
-        +--------------------+"#]],
+        +-------+
+        | not   |
+        +-------+
+        | false |
+        | false |
+        | true  |
+        | true  |
+        +-------+"#]],
     );
 }
 
 #[test]
-fn test_key_values() {
-    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
-    let arg0 = StringArray::from(vec!["a=b,c=d"]);
+fn test_run_end_encoded_input() {
+    let run_ends = Int32Array::from(vec![2, 3, 5]);
+    let values = Int32Array::from(vec![Some(5), None, Some(7)]);
+    let arg0 = RunArray::try_new(&run_ends, &values).unwrap();
+    let arg1 = Int32Array::from(vec![1, 2, 3, 4]);
+    let schema = Schema::new(vec![
+        Field::new("x", arg0.data_type().clone(), true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    // the sliced array starts in the middle of the first run
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0.slice(1, 4)), Arc::new(arg1)],
+    )
+    .unwrap();
+
+    let output = sub_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | result |
+        +--------+
+        | 4      |
+        |        |
+        | 4      |
+        | 3      |
+        +--------+"#]],
+    );
+
+    // table functions
+    let run_ends = Int32Array::from(vec![2, 3]);
+    let values = Int32Array::from(vec![1, 2]);
+    let arg0 = RunArray::try_new(&run_ends, &values).unwrap();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let output = key_values_string_struct_KeyValue_eval(&input)
+    let output = range_int32_int32_eval(&input)
         .unwrap()
         .next()
+        .unwrap()
         .unwrap();
     check(
         &[output],
         expect![[r#"
-        +-----+--------------------+
-        | row | key_values         |
-        +-----+--------------------+
-        | 0   | {key: a, value: b} |
-        | 0   | {key: c, value: d} |
-        +-----+--------------------+"#]],
+        +-----+-------+
+        | row | range |
+        +-----+-------+
+        | 0   | 0     |
+        | 1   | 0     |
+        | 2   | 0     |
+        | 2   | 1     |
+        +-----+-------+"#]],
     );
 }
 
 #[test]
-fn test_struct_of_all() {
-    let schema = Schema::new(vec![Field::new("int32", DataType::Int32, true)]);
-    let arg0 = Int32Array::from(vec![1]);
-    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+fn test_arg_metadata() {
+    let field = |metadata: &[(&str, &str)]| {
+        Field::new("x", DataType::Float64, true).with_metadata(
+            metadata
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    };
+    let input = |field: Field| {
+        let schema = Schema::new(vec![field]);
+        let arg0 = Float64Array::from(vec![Some(10.0), None]);
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap()
+    };
+
+    let output = meters_to_feet_float64_float64_eval(&input(field(&[
+        ("unit", "meter"),
+        ("precision", "high"),
+        ("other", "ignored"),
+    ])))
+    .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------+
+        | meters_to_feet |
+        +----------------+
+        | 32.8084        |
+        |                |
+        +----------------+"#]],
+    );
 
-    let output = struct_of_all_struct_StructOfAll_eval(&input).unwrap();
+    let err = meters_to_feet_float64_float64_eval(&input(field(&[
+        ("unit", "foot"),
+        ("precision", "high"),
+    ])))
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"Invalid argument error: expect metadata "unit" = "meter" for the 0-th argument, got Some("foot")"#
+    );
+
+    let err = meters_to_feet_float64_float64_eval(&input(field(&[("unit", "meter")]))).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"Invalid argument error: expect metadata "precision" = "high" for the 0-th argument, got None"#
+    );
+
+    // the metadata of omitted arguments is not checked
+    let output = scale_by_float64_float64_eval(&input(field(&[]))).unwrap();
     check(
         &[output],
         expect![[r#"
-        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+
-        | struct_of_all                                                                                                                                                                                                                                                                               |
-        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+
-        | {b: , a: 0, c: 1, d: 2, e: 3, aa: 4, cc: 5, dd: 6, ee: 7, f: 4.0, g: 5.0, h: 0.006, i: 2022-04-08, j: 12:34:56.789012, k: 2022-04-08T12:34:56.789012, l: 0 years 7 mons 8 days 0 hours 0 mins 0.000000009 secs, m: {"key":"value"}, n: string, o: 0a0b0c, p: [a, b], q: {key: a, value: b}} |
-        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+"#]],
+        +----------+
+        | scale_by |
+        +----------+
+        | 20.0     |
+        |          |
+        +----------+"#]],
     );
 }
 
 #[test]
-fn test_split() {
-    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
-    let arg0 = StringArray::from(vec!["a,b"]);
+fn test_dictionary_input() {
+    let schema = Schema::new(vec![Field::new(
+        "s",
+        DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+        true,
+    )]);
+    let arg0: DictionaryArray<Int32Type> = (0..1000)
+        .map(|i| ["a", "bb", "ccc"].get(i % 4).copied())
+        .collect();
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let output = split_string_stringarray_eval(&input).unwrap();
+    let output = char_length_string_int32_eval(&input).unwrap();
+    // the function is only called for each distinct value
+    assert_eq!(CHAR_LENGTH_CALLS.load(Ordering::Relaxed), 3);
+    // the output has the return type of the signature
+    assert_eq!(
+        output.schema(),
+        Arc::new(Schema::new(vec![Field::new(
+            "char_length",
+            DataType::Int32,
+            true
+        )]))
+    );
     check(
-        &[output],
+        &[output.slice(0, 5)],
         expect![[r#"
-        +--------+
-        | split  |
-        +--------+
-        | [a, b] |
-        +--------+"#]],
+        +-------------+
+        | char_length |
+        +-------------+
+        | 1           |
+        | 2           |
+        | 3           |
+        |             |
+        | 1           |
+        +-------------+"#]],
     );
 }
 
 #[test]
-fn test_option_add() {
+fn test_variadic() {
     let schema = Schema::new(vec![
-        Field::new("x", DataType::Int32, true),
-        Field::new("y", DataType::Int32, true),
+        Field::new("sep", DataType::Utf8, true),
+        Field::new("a", DataType::Utf8, true),
+        Field::new("b", DataType::Utf8, true),
     ]);
-    let arg0 = Int32Array::from(vec![Some(1), Some(1), None, None]);
-    let arg1 = Int32Array::from(vec![Some(1), None, Some(1), None]);
-    let input =
-        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
-
-    let output = option_add_int32_int32_int32_eval(&input).unwrap();
+    let arg0 = StringArray::from(vec![Some(","), Some("-"), None]);
+    let arg1 = StringArray::from(vec![Some("a"), None, Some("a")]);
+    let arg2 = StringArray::from(vec![Some("b"), Some("b"), Some("b")]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0), Arc::new(arg1), Arc::new(arg2)],
+    )
+    .unwrap();
+
+    let output = concat_ws_string_variadic_string_eval(&input).unwrap();
     check(
         &[output],
         expect![[r#"
-        +------------+
-        | option_add |
-        +------------+
-        | 2          |
-        | 1          |
+        +-----------+
+        | concat_ws |
+        +-----------+
+        | a,b       |
+        | b         |
+        |           |
+        +-----------+"#]],
+    );
+}
+
+#[test]
+fn test_variadic_empty() {
+    // no trailing columns: the function is called with an empty `VariadicRow`
+    let schema = Schema::new(vec![Field::new("sep", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some(","), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = concat_ws_string_variadic_string_eval(&input).unwrap();
+    assert_eq!(
+        output.column(0).as_string::<i32>(),
+        &StringArray::from(vec![Some(""), None])
+    );
+}
+
+#[test]
+fn test_init_context() {
+    let schema = Schema::new(vec![Field::new("name", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("Alice"), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = greet_string_string_eval(&input).unwrap();
+    greet_string_string_eval(&input).unwrap();
+    assert_eq!(CONTEXT_INITS.load(Ordering::Relaxed), 1);
+    check(
+        &[output],
+        expect![[r#"
+        +---------------+
+        | greet         |
+        +---------------+
+        | Hello, Alice! |
+        |               |
+        +---------------+"#]],
+    );
+}
+
+#[test]
+fn test_init_context_table_function() {
+    let schema = Schema::new(vec![Field::new("names", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("Alice,Bob"), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let outputs: Vec<_> = greet_all_string_string_eval(&input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+------------+
+        | row | greet_all  |
+        +-----+------------+
+        | 0   | Hi, Alice! |
+        | 0   | Hi, Bob!   |
+        +-----+------------+"#]],
+    );
+}
+
+#[test]
+fn test_div() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(-1), None]);
+    let arg1 = Int32Array::from(vec![Some(0), Some(-1), None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = div_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+------------------+
+        | div | error            |
+        +-----+------------------+
+        |     | division by zero |
+        | 1   |                  |
+        |     |                  |
+        +-----+------------------+"#]],
+    );
+}
+
+#[test]
+fn test_array_fill() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("n", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let arg1 = Int32Array::from(vec![Some(2), Some(1), Some(0)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = array_fill_any_int32_anyarray_eval(&input).unwrap();
+    assert_eq!(
+        output.schema().field(0).data_type(),
+        &DataType::new_list(DataType::Int32, true)
+    );
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | array_fill |
+        +------------+
+        | [1, 1]     |
+        |            |
+        | []         |
+        +------------+"#]],
+    );
+
+    // the element type follows the type of the input
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Utf8, true),
+        Field::new("n", DataType::Int32, true),
+    ]);
+    let arg0 = StringArray::from(vec!["a", "b"]);
+    let arg1 = Int32Array::from(vec![1, 3]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = array_fill_any_int32_anyarray_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | array_fill |
+        +------------+
+        | [a]        |
+        | [b, b, b]  |
+        +------------+"#]],
+    );
+}
+
+#[test]
+fn test_any_return() {
+    // primitive types take the SIMD path
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = passthrough_any_any_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0).data_type(), &DataType::Int32);
+    check(
+        &[output],
+        expect![[r#"
+        +-------------+
+        | passthrough |
+        +-------------+
+        | 1           |
+        |             |
+        | 3           |
+        +-------------+"#]],
+    );
+
+    // other types are copied row by row
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("a"), None, Some("c")]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = passthrough_any_any_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0).data_type(), &DataType::Utf8);
+    check(
+        &[output],
+        expect![[r#"
+        +-------------+
+        | passthrough |
+        +-------------+
+        | a           |
+        |             |
+        | c           |
+        +-------------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Utf8, true),
+        Field::new("y", DataType::Utf8, true),
+    ]);
+    let arg0 = StringArray::from(vec![Some("a"), None, None]);
+    let arg1 = StringArray::from(vec![Some("b"), Some("c"), None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = coalesce_any_any_any_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | coalesce |
+        +----------+
+        | a        |
+        | c        |
+        |          |
+        +----------+"#]],
+    );
+
+    // all `any` arguments must have the same type
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Utf8, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = StringArray::from(vec!["a"]);
+    let arg1 = Int32Array::from(vec![1]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let err = coalesce_any_any_any_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid argument error: expect the same type for all `any` arguments, got Utf8 and Int32"
+    );
+}
+
+#[test]
+fn test_interval_overflow() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Interval(IntervalUnit::MonthDayNano), true),
+        Field::new("y", DataType::Int64, true),
+    ]);
+    let arg0 = IntervalMonthDayNanoArray::from(vec![
+        IntervalMonthDayNanoType::make_value(1, 2, i64::MAX),
+        IntervalMonthDayNanoType::make_value(1, 2, i64::MAX - 1),
+        IntervalMonthDayNanoType::make_value(1, 2, i64::MIN),
+    ]);
+    let arg1 = Int64Array::from(vec![1, 1, -1]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let parts = |array: &ArrayRef| {
+        let array = array.as_primitive::<IntervalMonthDayNanoType>();
+        (0..array.len())
+            .map(|i| {
+                array
+                    .is_valid(i)
+                    .then(|| IntervalMonthDayNanoType::to_parts(array.value(i)))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // `i64::MAX` nanoseconds round-trip without overflow
+    let output = identity_interval_interval_eval(&input.project(&[0]).unwrap()).unwrap();
+    assert_eq!(
+        parts(output.column(0)),
+        [
+            Some((1, 2, i64::MAX)),
+            Some((1, 2, i64::MAX - 1)),
+            Some((1, 2, i64::MIN))
+        ]
+    );
+
+    let output = add_nanos_interval_int64_interval_eval(&input).unwrap();
+    assert_eq!(
+        parts(output.column(0)),
+        [None, Some((1, 2, i64::MAX)), None]
+    );
+    let errors = output.column(1).as_string::<i32>();
+    assert_eq!(
+        errors.iter().collect::<Vec<_>>(),
+        [
+            Some("interval out of range"),
+            None,
+            Some("interval out of range")
+        ]
+    );
+
+    let output = saturating_add_nanos_interval_int64_interval_eval(&input).unwrap();
+    assert_eq!(
+        parts(output.column(0)),
+        [
+            Some((1, 2, i64::MAX)),
+            Some((1, 2, i64::MAX)),
+            Some((1, 2, i64::MIN))
+        ]
+    );
+}
+
+#[test]
+fn test_interval_arithmetic() {
+    const HOUR: i64 = 60 * 60 * 1_000_000_000;
+    let interval = |months, days, nanos| Interval {
+        months,
+        days,
+        nanos,
+    };
+
+    assert_eq!(interval(1, 2, 3) + interval(-2, 1, -5), interval(-1, 3, -2));
+    assert_eq!(interval(1, 2, 3) - interval(2, -1, 5), interval(-1, 3, -2));
+    assert_eq!(
+        interval(0, 0, i64::MAX).checked_add(interval(0, 0, 1)),
+        None
+    );
+    assert_eq!(
+        interval(i32::MIN, 0, 0).checked_sub(interval(1, 0, 0)),
+        None
+    );
+
+    assert_eq!(
+        Interval::checked_from_duration(chrono::Duration::hours(-25)),
+        Some(interval(0, 0, -25 * HOUR))
+    );
+    assert_eq!(
+        Interval::checked_from_duration(chrono::Duration::days(1 << 20)),
+        None
+    );
+    assert_eq!(
+        interval(1, 2, -3 * HOUR).nanos_duration(),
+        chrono::Duration::hours(-3)
+    );
+
+    // nanoseconds are carried into days, and the signs of days and nanoseconds agree
+    assert_eq!(
+        interval(1, 0, 49 * HOUR).normalize(),
+        Some(interval(1, 2, HOUR))
+    );
+    assert_eq!(
+        interval(1, 0, -49 * HOUR).normalize(),
+        Some(interval(1, -2, -HOUR))
+    );
+    assert_eq!(
+        interval(0, 1, -HOUR).normalize(),
+        Some(interval(0, 0, 23 * HOUR))
+    );
+    assert_eq!(
+        interval(0, -1, HOUR).normalize(),
+        Some(interval(0, 0, -23 * HOUR))
+    );
+    assert_eq!(interval(0, i32::MAX, 24 * HOUR).normalize(), None);
+    assert_eq!(interval(0, i32::MIN, -24 * HOUR).normalize(), None);
+}
+
+#[test]
+#[should_panic(expected = "interval overflow")]
+fn test_interval_add_overflow() {
+    let _ = Interval {
+        months: i32::MAX,
+        days: 0,
+        nanos: 0,
+    } + Interval {
+        months: 1,
+        days: 0,
+        nanos: 0,
+    };
+}
+
+#[test]
+fn test_interval_sub() {
+    const HOUR: i64 = 60 * 60 * 1_000_000_000;
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Interval(IntervalUnit::MonthDayNano), true),
+        Field::new("y", DataType::Interval(IntervalUnit::MonthDayNano), true),
+    ]);
+    let arg0 = IntervalMonthDayNanoArray::from(vec![
+        IntervalMonthDayNanoType::make_value(1, 1, 0),
+        IntervalMonthDayNanoType::make_value(0, 0, -HOUR),
+        IntervalMonthDayNanoType::make_value(0, i32::MIN, 0),
+    ]);
+    let arg1 = IntervalMonthDayNanoArray::from(vec![
+        IntervalMonthDayNanoType::make_value(2, 0, HOUR),
+        IntervalMonthDayNanoType::make_value(0, 0, 24 * HOUR),
+        IntervalMonthDayNanoType::make_value(0, 1, 0),
+    ]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = interval_sub_interval_interval_interval_eval(&input).unwrap();
+    let array = output.column(0).as_primitive::<IntervalMonthDayNanoType>();
+    let parts = (0..array.len())
+        .map(|i| {
+            array
+                .is_valid(i)
+                .then(|| IntervalMonthDayNanoType::to_parts(array.value(i)))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parts,
+        [Some((-1, 0, 23 * HOUR)), Some((0, -1, -HOUR)), None]
+    );
+    let errors = output.column(1).as_string::<i32>();
+    assert_eq!(
+        errors.iter().collect::<Vec<_>>(),
+        [None, None, Some("interval out of range")]
+    );
+}
+
+/// assert the errors of generated functions can be sent across threads
+#[test]
+fn test_error_send_sync() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<arrow_udf::Error>();
+
+    let err = arrow_udf::Error::ExternalError(anyhow::anyhow!("oops").into());
+    let err = std::thread::spawn(move || err).join().unwrap();
+    assert_eq!(err.to_string(), "External error: oops");
+}
+
+#[test]
+fn test_error_column() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(1)]);
+    let arg1 = Int32Array::from(vec![Some(1), Some(0)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = checked_div_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------------+------------------+
+        | checked_div | __udf_error      |
+        +-------------+------------------+
+        | 1           |                  |
+        |             | division by zero |
+        +-------------+------------------+"#]],
+    );
+}
+
+#[test]
+fn test_output_name() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(5), None]);
+    let arg1 = Int32Array::from(vec![Some(3), Some(1)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = sub_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | result |
+        +--------+
+        | 2      |
+        |        |
+        +--------+"#]],
+    );
+}
+
+#[test]
+fn test_scalar_cursor() {
+    use arrow_udf::ffi::*;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]));
+    let batches = (0..10)
+        .map(|i| {
+            let arg0 = Int32Array::from(vec![i * 10, i * 100]);
+            let arg1 = Int32Array::from(vec![i, i]);
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = CSlice {
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+    let outputs = unsafe {
+        scalar_cursor_open(sub_int32_int32_int32_eval, &mut out);
+        let cursor = out.ptr as *mut ScalarCursor;
+        // push the first 4 batches one by one, and the rest at once
+        let inputs = batches[..4]
+            .iter()
+            .map(std::slice::from_ref)
+            .chain([&batches[4..]]);
+        for input in inputs {
+            let buf = encode_ipc(input);
+            assert_eq!(
+                scalar_cursor_push(cursor, buf.as_ptr(), buf.len(), &mut out),
+                0
+            );
+        }
+        assert_eq!(scalar_cursor_pull(cursor, &mut out), 0);
+        let outputs = decode_ipc(std::slice::from_raw_parts(out.ptr, out.len));
+        dealloc(out.ptr as *mut u8, out.len, 1);
+
+        // no more outputs
+        assert_eq!(scalar_cursor_pull(cursor, &mut out), 0);
+        assert!(out.ptr.is_null());
+        scalar_cursor_close(cursor);
+        outputs
+    };
+
+    assert_eq!(outputs.len(), 10);
+    for (i, output) in (0..10).zip(outputs) {
+        assert_eq!(
+            output.column(0).as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![i * 9, i * 99])
+        );
+    }
+}
+
+#[test]
+fn test_table_try_next() {
+    use arrow_udf::ffi::*;
+
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1, 2, 3, 1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let buf = encode_ipc(&[input]);
+
+    let mut out = CSlice {
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+    let (outputs, error) = unsafe {
+        assert_eq!(
+            table_wrapper(
+                checked_batch_range_int32_int32_eval,
+                buf.as_ptr(),
+                buf.len(),
+                &mut out
+            ),
+            0
+        );
+        let iter = out.ptr as *mut RecordBatchIter;
+        let mut outputs = vec![];
+        while record_batch_iterator_try_next(iter, &mut out) == 0 {
+            assert!(!out.ptr.is_null(), "the stream should not end normally");
+            outputs.extend(decode_ipc(std::slice::from_raw_parts(out.ptr, out.len)));
+            dealloc(out.ptr as *mut u8, out.len, 1);
+        }
+        let error = std::str::from_utf8(std::slice::from_raw_parts(out.ptr, out.len))
+            .unwrap()
+            .to_string();
+        dealloc(out.ptr as *mut u8, out.len, 1);
+        // the iterator ends after the error
+        assert_eq!(record_batch_iterator_try_next(iter, &mut out), 0);
+        assert!(out.ptr.is_null());
+        record_batch_iterator_drop(iter);
+        (outputs, error)
+    };
+
+    // the batches before the failure are delivered
+    check(
+        &outputs,
+        expect![[r#"
+        +---+---+
+        | i | v |
+        +---+---+
+        | 0 | 0 |
+        | 1 | 0 |
+        | 1 | 1 |
+        +---+---+"#]],
+    );
+    assert_eq!(
+        error,
+        "External error: table function panicked after 2 batches: too many rows"
+    );
+}
+
+#[test]
+fn test_table_session() {
+    use arrow_udf::ffi::*;
+
+    // two batches sharing the same dictionary
+    let values: ArrayRef = Arc::new(StringArray::from(vec!["a=b", "c=d,e=f"]));
+    let batches = [vec![0, 1], vec![1, 1]].map(|keys| {
+        let array = DictionaryArray::new(Int32Array::from(keys), values.clone());
+        let schema = Schema::new(vec![Field::new("x", array.data_type().clone(), true)]);
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(array)]).unwrap()
+    });
+    // split the stream into the preamble and the messages of each batch
+    let mut buf = vec![];
+    let mut writer =
+        arrow_ipc::writer::StreamWriter::try_new(&mut buf, &batches[0].schema()).unwrap();
+    let mut offsets = vec![writer.get_ref().len()];
+    for batch in &batches {
+        writer.write(batch).unwrap();
+        offsets.push(writer.get_ref().len());
+    }
+    drop(writer);
+    let preamble = &buf[..offsets[0]];
+    let inputs = [&buf[offsets[0]..offsets[1]], &buf[offsets[1]..offsets[2]]];
+    // the dictionary is only sent with the first batch
+    assert!(!inputs[1].windows(3).any(|w| w == b"c=d"));
+
+    let mut out = CSlice {
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+    let outputs = unsafe {
+        assert_eq!(
+            table_session_open(
+                key_values_string_struct_KeyValue_eval,
+                preamble.as_ptr(),
+                preamble.len(),
+                &mut out
+            ),
+            0
+        );
+        let session = out.ptr as *mut TableSession;
+        let mut outputs = vec![];
+        for input in inputs {
+            assert_eq!(
+                table_session_call(session, input.as_ptr(), input.len(), &mut out),
+                0
+            );
+            let iter = out.ptr as *mut RecordBatchIter;
+            record_batch_iterator_next(iter, &mut out);
+            outputs.extend(decode_ipc(std::slice::from_raw_parts(out.ptr, out.len)));
+            dealloc(out.ptr as *mut u8, out.len, 1);
+            record_batch_iterator_drop(iter);
+        }
+        table_session_close(session);
+
+        // the dictionary is missing in a new session
+        table_session_open(
+            key_values_string_struct_KeyValue_eval,
+            preamble.as_ptr(),
+            preamble.len(),
+            &mut out,
+        );
+        let session = out.ptr as *mut TableSession;
+        assert_eq!(
+            table_session_call(session, inputs[1].as_ptr(), inputs[1].len(), &mut out),
+            -1
+        );
+        dealloc(out.ptr as *mut u8, out.len, 1);
+        table_session_close(session);
+        outputs
+    };
+
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+--------------------+
+        | row | key_values         |
+        +-----+--------------------+
+        | 0   | {key: a, value: b} |
+        | 1   | {key: c, value: d} |
+        | 1   | {key: e, value: f} |
+        | 0   | {key: c, value: d} |
+        | 0   | {key: e, value: f} |
+        | 1   | {key: c, value: d} |
+        | 1   | {key: e, value: f} |
+        +-----+--------------------+"#]],
+    );
+}
+
+#[test]
+fn test_div_without_error() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(4), None, Some(1), Some(6)]);
+    let arg1 = Int32Array::from(vec![Some(2), Some(1), Some(0), Some(3)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    // the error column is all null if no error occurs
+    let output = div_int32_int32_int32_eval(&input.slice(0, 2)).unwrap();
+    let errors = output.column(1).as_string::<i32>();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors.null_count(), 2);
+
+    // errors after some rows are aligned with the rows
+    let output = div_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+------------------+
+        | div | error            |
+        +-----+------------------+
+        | 2   |                  |
+        |     |                  |
+        |     | division by zero |
+        | 2   |                  |
+        +-----+------------------+"#]],
+    );
+}
+
+#[test]
+fn test_key_value() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a=b", "??"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = key_value_string_struct_KeyValue_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------------------+
+        | key_value          |
+        +--------------------+
+        | {key: a, value: b} |
+        |                    |
+        +--------------------+"#]],
+    );
+}
+
+#[test]
+fn test_key_values() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a=b,c=d"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = key_values_string_struct_KeyValue_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+--------------------+
+        | row | key_values         |
+        +-----+--------------------+
+        | 0   | {key: a, value: b} |
+        | 0   | {key: c, value: d} |
+        +-----+--------------------+"#]],
+    );
+}
+
+#[test]
+fn test_map_sum() {
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+    builder.keys().append_value("a");
+    builder.values().append_value(1);
+    builder.keys().append_value("b");
+    builder.values().append_null();
+    builder.keys().append_value("c");
+    builder.values().append_value(3);
+    builder.append(true).unwrap();
+    builder.append(false).unwrap();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = map_sum_map_string_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------+
+        | map_sum |
+        +---------+
+        | 4       |
+        |         |
+        | 0       |
+        +---------+"#]],
+    );
+}
+
+#[test]
+fn test_map_fixed_size_binary() {
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), FixedSizeBinaryBuilder::new(4));
+    builder.keys().append_value("a");
+    builder.values().append_value([1, 0, 0, 0]).unwrap();
+    builder.keys().append_value("b");
+    builder.values().append_value([2, 0, 0, 0]).unwrap();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = map_first_bytes_map_string_fixedsizebinary_4_int32_eval(&input).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![3]));
+
+    // the width of the values must match the signature
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), FixedSizeBinaryBuilder::new(8));
+    builder.keys().append_value("a");
+    builder.values().append_value([0; 8]).unwrap();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = map_first_bytes_map_string_fixedsizebinary_4_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect width 4 for the values of the 0-th argument, got 8"
+    );
+
+    // uuid values must have 16 bytes
+    let err = map_uuids_map_string_uuid_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect width 16 for the values of the 0-th argument, got 8"
+    );
+
+    // the types of keys and values must match the signature
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+    builder.keys().append_value("a");
+    builder.values().append_value(1);
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = map_first_bytes_map_string_fixedsizebinary_4_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect FixedSizeBinaryArray values for the 0-th argument"
+    );
+
+    let mut builder = MapBuilder::new(None, Int32Builder::new(), FixedSizeBinaryBuilder::new(4));
+    builder.keys().append_value(1);
+    builder.values().append_value([0; 4]).unwrap();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = map_first_bytes_map_string_fixedsizebinary_4_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect StringArray keys for the 0-th argument"
+    );
+}
+
+#[test]
+fn test_map_decimal128() {
+    let values = Decimal128Builder::new()
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), values);
+    builder.keys().append_value("a");
+    builder.values().append_value(150);
+    builder.keys().append_value("b");
+    builder.values().append_null();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = map_decimal_count_map_string_decimal128_10_2_int32_eval(&input).unwrap();
+    assert_eq!(&**output.column(0), &Int32Array::from(vec![1]));
+
+    // the scale of the values must match the signature
+    let values = Decimal128Builder::new()
+        .with_precision_and_scale(10, 3)
+        .unwrap();
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), values);
+    builder.keys().append_value("a");
+    builder.values().append_value(1500);
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let schema = Schema::new(vec![Field::new("x", arg0.data_type().clone(), true)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = map_decimal_count_map_string_decimal128_10_2_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect scale 2 for the values of the 0-th argument, got 3"
+    );
+}
+
+#[test]
+fn test_map_get() {
+    let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+    builder.keys().append_value("a");
+    builder.values().append_value(1);
+    builder.keys().append_value("b");
+    builder.values().append_null();
+    builder.append(true).unwrap();
+    builder.append(false).unwrap();
+    builder.keys().append_value("a");
+    builder.values().append_value(3);
+    builder.append(true).unwrap();
+    builder.append(true).unwrap();
+    let arg0 = builder.finish();
+    let arg1 = StringArray::from(vec![Some("a"), Some("a"), None, Some("c")]);
+    let schema = Schema::new(vec![
+        Field::new("x", arg0.data_type().clone(), true),
+        Field::new("y", DataType::Utf8, true),
+    ]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    // missing keys and null maps return null
+    let output = map_get_map_string_any_string_any_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0).data_type(), &DataType::Int32);
+    check(
+        &[output],
+        expect![[r#"
+        +---------+
+        | map_get |
+        +---------+
+        | 1       |
+        |         |
+        |         |
+        |         |
+        +---------+"#]],
+    );
+
+    // the value type is inferred from the map type
+    #[cfg(feature = "global_registry")]
+    {
+        let args = input
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .collect::<Vec<_>>();
+        let int32 = Field::new("", DataType::Int32, true);
+        let sig = arrow_udf::sig::REGISTRY
+            .get("map_get", &args, &int32)
+            .unwrap();
+        let field = sig.infer_return_type(&args).unwrap();
+        assert_eq!(field.data_type(), &DataType::Int32);
+        let err = sig
+            .infer_return_type(&[
+                Field::new("x", DataType::Utf8, true),
+                Field::new("y", DataType::Utf8, true),
+            ])
+            .unwrap_err();
+        assert!(err.to_string().contains("expect map"), "{err}");
+    }
+}
+
+#[test]
+fn test_to_map() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("b=2,a=1,c="), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = to_map_string_map_string_string_eval(&input).unwrap();
+    assert!(matches!(
+        output.schema().field(0).data_type(),
+        DataType::Map(_, true)
+    ));
+    check(
+        &[output],
+        expect![[r#"
+        +-------------------+
+        | to_map            |
+        +-------------------+
+        | {a: 1, b: 2, c: } |
+        |                   |
+        +-------------------+"#]],
+    );
+}
+
+#[test]
+fn test_no_arg_table_function() {
+    // a batch with no columns but 3 rows
+    let input = RecordBatch::try_new_with_options(
+        Arc::new(Schema::empty()),
+        vec![],
+        &RecordBatchOptions::default().with_row_count(Some(3)),
+    )
+    .unwrap();
+
+    let output = pair_int32_eval(&input).unwrap().next().unwrap().unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+------+
+        | row | pair |
+        +-----+------+
+        | 0   | 0    |
+        | 0   | 1    |
+        | 1   | 0    |
+        | 1   | 1    |
+        | 2   | 0    |
+        | 2   | 1    |
+        +-----+------+"#]],
+    );
+}
+
+#[test]
+fn test_struct_of_all() {
+    let schema = Schema::new(vec![Field::new("int32", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = struct_of_all_struct_StructOfAll_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+
+        | struct_of_all                                                                                                                                                                                                                                                                               |
+        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+
+        | {b: , a: 0, c: 1, d: 2, e: 3, aa: 4, cc: 5, dd: 6, ee: 7, f: 4.0, g: 5.0, h: 0.006, i: 2022-04-08, j: 12:34:56.789012, k: 2022-04-08T12:34:56.789012, l: 0 years 7 mons 8 days 0 hours 0 mins 0.000000009 secs, m: {"key":"value"}, n: string, o: 0a0b0c, p: [a, b], q: {key: a, value: b}} |
+        +---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+"#]],
+    );
+}
+
+#[test]
+fn test_struct_of_list() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("alice:1,2"), Some("bob"), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = name_scores_string_struct_NameScores_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------------------------------+
+        | name_scores                   |
+        +-------------------------------+
+        | {name: alice, scores: [1, 2]} |
+        | {name: bob, scores: }         |
+        |                               |
+        +-------------------------------+"#]],
+    );
+}
+
+#[test]
+fn test_list_of_struct() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("a=b,c=d"), Some("??"), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = key_value_list_string_struct_KeyValuearray_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------------------------------------+
+        | key_value_list                           |
+        +------------------------------------------+
+        | [{key: a, value: b}, {key: c, value: d}] |
+        |                                          |
+        |                                          |
+        +------------------------------------------+"#]],
+    );
+}
+
+#[test]
+fn test_view_arrays() {
+    let schema = Schema::new(vec![
+        Field::new("s", DataType::Utf8View, true),
+        Field::new("start", DataType::Int32, true),
+    ]);
+    let arg0 = StringViewArray::from(vec![
+        Some("hello"),
+        None,
+        Some("a string longer than 12 bytes"),
+    ]);
+    let arg1 = Int32Array::from(vec![1, 1, 2]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = substring_string_int32_string_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0).data_type(), &DataType::Utf8);
+    check(
+        &[output],
+        expect![[r#"
+        +-----------------------------+
+        | substring                   |
+        +-----------------------------+
+        | ello                        |
+        |                             |
+        | string longer than 12 bytes |
+        +-----------------------------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        Field::new("s", DataType::BinaryView, true),
+        Field::new("start", DataType::Int32, true),
+    ]);
+    let arg0 = BinaryViewArray::from(vec![Some(&b"abc"[..]), None]);
+    let arg1 = Int32Array::from(vec![1, 1]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = substring_binary_int32_binary_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0).data_type(), &DataType::Binary);
+    check(
+        &[output],
+        expect![[r#"
+        +-----------+
+        | substring |
+        +-----------+
+        | 6263      |
+        |           |
+        +-----------+"#]],
+    );
+
+    // table functions
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8View, true)]);
+    let arg0 = StringViewArray::from(vec!["a=b,c=d"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = key_values_string_struct_KeyValue_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+--------------------+
+        | row | key_values         |
+        +-----+--------------------+
+        | 0   | {key: a, value: b} |
+        | 0   | {key: c, value: d} |
+        +-----+--------------------+"#]],
+    );
+
+    // other types are still rejected
+    let schema = Schema::new(vec![Field::new("x", DataType::LargeUtf8, true)]);
+    let arg0 = LargeStringArray::from(vec!["a,b"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = split_string_stringarray_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect StringArray or StringViewArray for the 0-th argument"
+    );
+}
+
+#[test]
+fn test_split() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a,b"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = split_string_stringarray_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | split  |
+        +--------+
+        | [a, b] |
+        +--------+"#]],
+    );
+}
+
+#[test]
+fn test_option_add() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(1), None, None]);
+    let arg1 = Int32Array::from(vec![Some(1), None, Some(1), None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = option_add_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | option_add |
+        +------------+
+        | 2          |
+        | 1          |
+        |            |
+        |            |
+        +------------+"#]],
+    );
+}
+
+#[test]
+fn test_null_handling() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(1), None, None]);
+    let arg1 = Int32Array::from(vec![Some(2), None, Some(2), None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output1 = coalesce_int32_int32_int32_eval(&input).unwrap();
+    let output2 = strict_add_int32_int32_int32_eval(&input).unwrap();
+    check(
+        &[output1],
+        expect![[r#"
+        +----------+
+        | coalesce |
+        +----------+
+        | 1        |
+        | 1        |
+        | 2        |
+        |          |
+        +----------+"#]],
+    );
+    check(
+        &[output2],
+        expect![[r#"
+        +------------+
+        | strict_add |
+        +------------+
+        | 3          |
+        |            |
         |            |
         |            |
         +------------+"#]],
     );
 }
 
+#[test]
+fn test_output_nullable() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // never returns null
+    let output = is_null_int32_boolean_eval(&input).unwrap();
+    assert!(!output.schema().field(0).is_nullable());
+    assert_eq!(output.column(0).nulls(), None);
+    assert_eq!(
+        output.column(0).as_boolean(),
+        &BooleanArray::from(vec![false, true])
+    );
+
+    // returns null on null input
+    let output = option_add_int32_int32_int32_eval(&input.project(&[0, 0]).unwrap()).unwrap();
+    assert!(output.schema().field(0).is_nullable());
+
+    // declared non-nullable but returns null
+    let err = checked_neg_int32_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid argument error: Column 'checked_neg' is declared as non-nullable but contains null values"
+    );
+    let input = input.slice(0, 1);
+    let output = checked_neg_int32_int32_eval(&input).unwrap();
+    assert!(!output.schema().field(0).is_nullable());
+    assert_eq!(output.column(0).as_primitive::<Int32Type>().value(0), -1);
+}
+
+#[test]
+fn test_session_context() {
+    let session = SessionContext {
+        timezone: Some("Asia/Shanghai".into()),
+        locale: None,
+        search_path: vec!["public".into(), "udf".into()],
+    };
+    assert_eq!(
+        SessionContext::from_metadata(&session.to_metadata()),
+        session
+    );
+
+    let input = RecordBatch::try_new_with_options(
+        Arc::new(Schema::empty()),
+        vec![],
+        &RecordBatchOptions::default().with_row_count(Some(1)),
+    )
+    .unwrap();
+    // no session variables by default
+    let output = current_timezone_string_eval(&input).unwrap();
+    assert!(output.column(0).is_null(0));
+
+    let output = current_timezone_string_eval(&session.attach(input).unwrap()).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------------+
+        | current_timezone |
+        +------------------+
+        | Asia/Shanghai    |
+        +------------------+"#]],
+    );
+
+    let schema = Schema::new(vec![Field::new("name", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["f"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let outputs: Vec<_> = qualify_string_string_eval(&session.attach(input).unwrap())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+----------+
+        | row | qualify  |
+        +-----+----------+
+        | 0   | public.f |
+        | 0   | udf.f    |
+        +-----+----------+"#]],
+    );
+}
+
 #[test]
 fn test_array_sum() {
     let schema = Schema::new(vec![Field::new(
@@ -558,6 +2427,104 @@ fn test_temporal() {
     );
 }
 
+#[test]
+fn test_timestamp_utc() {
+    let schema = Schema::new(vec![
+        Field::new("t", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        Field::new("hours", DataType::Int32, true),
+    ]);
+    let arg0 = TimestampMicrosecondArray::from(vec![Some(1_000_000), None]);
+    let arg1 = Int32Array::from(vec![Some(2), Some(1)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = epoch_seconds_timestamp_int64_eval(&input.project(&[0]).unwrap()).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------------+
+        | epoch_seconds |
+        +---------------+
+        | 1             |
+        |               |
+        +---------------+"#]],
+    );
+
+    let output = add_hours_timestamp_int32_timestamp_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------------------+
+        | add_hours           |
+        +---------------------+
+        | 1970-01-01T02:00:01 |
+        |                     |
+        +---------------------+"#]],
+    );
+}
+
+#[test]
+fn test_timestamp_nanos() {
+    let schema = Schema::new(vec![
+        Field::new("t", DataType::Timestamp(TimeUnit::Nanosecond, None), true),
+        Field::new("nanos", DataType::Int64, true),
+    ]);
+    let arg0 = TimestampNanosecondArray::from(vec![Some(1_000_000_001), Some(-1), None]);
+    let arg1 = Int64Array::from(vec![Some(1), Some(-1), Some(1)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    // sub-microsecond precision survives the round trip
+    let output = identity_timestamp_ns_timestamp_ns_eval(&input.project(&[0]).unwrap()).unwrap();
+    assert_eq!(output.column(0), input.column(0));
+
+    let output = add_nanos_timestamp_ns_int64_timestamp_ns_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------------------------------+-------+
+        | add_nanos                     | error |
+        +-------------------------------+-------+
+        | 1970-01-01T00:00:01.000000002 |       |
+        | 1969-12-31T23:59:59.999999998 |       |
+        |                               |       |
+        +-------------------------------+-------+"#]],
+    );
+
+    // timestamps out of the range of nanoseconds are row errors
+    let schema = Schema::new(vec![Field::new(
+        "t",
+        DataType::Timestamp(TimeUnit::Microsecond, None),
+        true,
+    )]);
+    let arg0 = TimestampMicrosecondArray::from(vec![100_000_000_000_000_000, 1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let output = to_nanos_timestamp_timestamp_ns_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------------+----------------------------------------+
+        | to_nanos                   | error                                  |
+        +----------------------------+----------------------------------------+
+        |                            | timestamp out of range for nanoseconds |
+        | 1970-01-01T00:00:00.000001 |                                        |
+        +----------------------------+----------------------------------------+"#]],
+    );
+
+    // so are the elements of returned lists
+    let output = to_nanos_list_timestamp_timestamp_nsarray_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------------------------+----------------------------------------+
+        | to_nanos_list                | error                                  |
+        +------------------------------+----------------------------------------+
+        |                              | timestamp out of range for nanoseconds |
+        | [1970-01-01T00:00:00.000001] |                                        |
+        +------------------------------+----------------------------------------+"#]],
+    );
+}
+
 #[test]
 fn test_decimal_add() {
     let schema = Schema::new(vec![decimal_field("a"), decimal_field("b")]);
@@ -566,16 +2533,401 @@ fn test_decimal_add() {
     let input =
         RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
 
-    let output = add_decimal_decimal_decimal_eval(&input).unwrap();
-    assert_eq!(output.schema().field(0), &decimal_field("add"));
+    let output = add_decimal_decimal_decimal_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0), &decimal_field("add"));
+    check(
+        &[output],
+        expect![[r#"
+        +--------+
+        | add    |
+        +--------+
+        | 0.0003 |
+        +--------+"#]],
+    );
+}
+
+#[test]
+fn test_decimal128() {
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(10, 2), true),
+        Field::new("b", DataType::Decimal128(10, 2), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![Some(12345), None, Some(-1)])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![Some(1), Some(1), Some(99999)])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = add_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap();
+    assert_eq!(
+        output.schema().field(0).data_type(),
+        &DataType::Decimal128(11, 2)
+    );
+    check(
+        &[output],
+        expect![[r#"
+        +--------+-------+
+        | add    | error |
+        +--------+-------+
+        | 123.46 |       |
+        |        |       |
+        | 999.98 |       |
+        +--------+-------+"#]],
+    );
+
+    // arguments with a different scale are rescaled
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(10, 3), true),
+        Field::new("b", DataType::Decimal128(10, 1), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![Some(12340), None, Some(-1000)])
+        .with_precision_and_scale(10, 3)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![1, 1, 1])
+        .with_precision_and_scale(10, 1)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = add_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------+-------+
+        | add   | error |
+        +-------+-------+
+        | 12.44 |       |
+        |       |       |
+        | -0.90 |       |
+        +-------+-------+"#]],
+    );
+
+    // losing digits is an error without `round_decimal`
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(10, 3), true),
+        Field::new("b", DataType::Decimal128(10, 2), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![1, 5, -1235])
+        .with_precision_and_scale(10, 3)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![1, 1, 1])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let err = add_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: cannot rescale the 0-th argument from scale 3 to 2 without rounding"
+    );
+
+    // values are rounded half away from zero with `round_decimal`
+    let output = add_rounded_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------------+-------+
+        | add_rounded | error |
+        +-------------+-------+
+        | 0.01        |       |
+        | 0.02        |       |
+        | -1.23       |       |
+        +-------------+-------+"#]],
+    );
+
+    // rescaled values must fit in the precision
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(20, 0), true),
+        Field::new("b", DataType::Decimal128(10, 2), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![1_000_000_000])
+        .with_precision_and_scale(20, 0)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![1])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let err = add_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap_err();
+    assert!(err.to_string().contains("precision 10"), "{err}");
+
+    // returned values must fit in the precision
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(10, 2), true),
+        Field::new("b", DataType::Decimal128(10, 2), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![9_999_999_999, 1])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![1, 1])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = add_narrow_decimal128_10_2_decimal128_10_2_decimal128_10_2_eval(&input).unwrap();
+    let array = output.column(0).as_primitive::<Decimal128Type>();
+    assert!(array.is_null(0));
+    assert_eq!(array.value(1), 2);
+    array.validate_decimal_precision(10).unwrap();
+    let error = output.column(1).as_string::<i32>();
+    assert!(
+        error.value(0).contains("precision 10"),
+        "{}",
+        error.value(0)
+    );
+    assert!(error.is_null(1));
+
+    // the output is nullable even if the function never returns null
+    let schema = Schema::new(vec![Field::new("x", DataType::Decimal128(10, 2), true)]);
+    let arg0 = Decimal128Array::from(vec![Some(1), None])
+        .with_precision_and_scale(10, 2)
+        .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let output = decimal_or_hundred_decimal128_10_2_decimal128_4_2_eval(&input).unwrap();
+    let array = output.column(0).as_primitive::<Decimal128Type>();
+    assert!(output.schema().field(0).is_nullable());
+    assert_eq!(array.value(0), 1);
+    assert!(array.is_null(1));
+    let error = output.column(1).as_string::<i32>();
+    assert!(error.is_null(0));
+    assert!(error.value(1).contains("precision 4"), "{}", error.value(1));
+
+    // elements of returned lists must fit in the precision
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1, 100]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let output = repeat_decimal_int32_decimal128_4_2array_eval(&input).unwrap();
+    let list = output.column(0).as_list::<i32>();
+    assert_eq!(
+        list.value(0).as_primitive::<Decimal128Type>().values(),
+        &[100, 100]
+    );
+    assert!(list.is_null(1));
+    let error = output.column(1).as_string::<i32>();
+    assert!(error.is_null(0));
+    assert!(error.value(1).contains("precision 4"), "{}", error.value(1));
+
+    // arguments must fit in `rust_decimal::Decimal`
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal128(38, 2), true),
+        Field::new("b", DataType::Decimal128(38, 2), true),
+    ]);
+    let arg0 = Decimal128Array::from(vec![10_i128.pow(30), 1])
+        .with_precision_and_scale(38, 2)
+        .unwrap();
+    let arg1 = Decimal128Array::from(vec![1, 1])
+        .with_precision_and_scale(38, 2)
+        .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let output = add_decimal128_10_2_decimal128_10_2_decimal128_11_2_eval(&input).unwrap();
+    let array = output.column(0).as_primitive::<Decimal128Type>();
+    assert!(array.is_null(0));
+    assert_eq!(array.value(1), 2);
+    let error = output.column(1).as_string::<i32>();
+    assert!(
+        error.value(0).contains("cannot convert"),
+        "{}",
+        error.value(0)
+    );
+    assert!(error.is_null(1));
+}
+
+#[test]
+fn test_fixed_size_binary() {
+    let schema = Schema::new(vec![Field::new(
+        "uuid",
+        DataType::FixedSizeBinary(16),
+        true,
+    )]);
+    let bytes: Vec<u8> = (0..16).collect();
+    let arg0 = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+        [Some(bytes.as_slice()), None].into_iter(),
+        16,
+    )
+    .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = set_uuid_version_fixedsizebinary_16_fixedsizebinary_16_eval(&input).unwrap();
+    assert_eq!(
+        output.schema().field(0).data_type(),
+        &DataType::FixedSizeBinary(16)
+    );
+    check(
+        &[output],
+        expect![[r#"
+        +----------------------------------+
+        | set_uuid_version                 |
+        +----------------------------------+
+        | 000102030405460788090a0b0c0d0e0f |
+        |                                  |
+        +----------------------------------+"#]],
+    );
+
+    // the width must match the signature
+    let schema = Schema::new(vec![Field::new("uuid", DataType::FixedSizeBinary(8), true)]);
+    let arg0 = FixedSizeBinaryArray::try_from_iter([[0u8; 8]].into_iter()).unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = set_uuid_version_fixedsizebinary_16_fixedsizebinary_16_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect width 16 for the 0-th argument, got 8"
+    );
+}
+
+#[test]
+fn test_uuid() {
+    let field = Field::new("uuid", DataType::FixedSizeBinary(16), true)
+        .with_metadata([("ARROW:extension:name".into(), "arrow.uuid".into())].into());
+    let uuid = Uuid::new_v4();
+    let arg0 = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+        [Some(uuid.as_bytes().as_slice()), None].into_iter(),
+        16,
+    )
+    .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(Schema::new(vec![field])), vec![Arc::new(arg0)]).unwrap();
+
+    let output = identity_uuid_uuid_eval(&input).unwrap();
+    let field = output.schema().field(0).clone();
+    assert_eq!(field.data_type(), &DataType::FixedSizeBinary(16));
+    assert_eq!(
+        field
+            .metadata()
+            .get("ARROW:extension:name")
+            .map(|s| s.as_str()),
+        Some("arrow.uuid")
+    );
+    let array = output.column(0).as_fixed_size_binary();
+    assert_eq!(Uuid::from_slice(array.value(0)).unwrap(), uuid);
+    assert!(array.is_null(1));
+
+    // uuid values must have 16 bytes
+    let schema = Schema::new(vec![Field::new("uuid", DataType::FixedSizeBinary(8), true)]);
+    let arg0 = FixedSizeBinaryArray::try_from_iter([[0u8; 8]].into_iter()).unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = identity_uuid_uuid_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect width 16 for the 0-th argument, got 8"
+    );
+}
+
+#[test]
+fn test_decimal256() {
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Decimal256(60, 2), true),
+        Field::new("b", DataType::Decimal256(60, 2), true),
+    ]);
+    // 2^130 exceeds the range of 128-bit integers
+    let large = i256::from_i128(1 << 100) * i256::from_i128(1 << 30);
+    let arg0 = Decimal256Array::from(vec![Some(large), None, Some(i256::from_i128(-1))])
+        .with_precision_and_scale(60, 2)
+        .unwrap();
+    let arg1 = Decimal256Array::from(vec![
+        Some(i256::from_i128(1)),
+        Some(i256::from_i128(1)),
+        Some(i256::from_i128(99999)),
+    ])
+    .with_precision_and_scale(60, 2)
+    .unwrap();
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = add_decimal256_60_2_decimal256_60_2_decimal256_61_2_eval(&input).unwrap();
+    assert_eq!(
+        output.schema().field(0).data_type(),
+        &DataType::Decimal256(61, 2)
+    );
+    check(
+        &[output],
+        expect![[r#"
+        +-------------------------------------------+
+        | add                                       |
+        +-------------------------------------------+
+        | 13611294676837538538534984297270728458.25 |
+        |                                           |
+        | 999.98                                    |
+        +-------------------------------------------+"#]],
+    );
+}
+
+#[test]
+fn test_default_argument() {
+    let schema = Schema::new(vec![decimal_field("x")]);
+    let arg0 = StringArray::from(vec![Some("1.2345"), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = round_decimal_decimal_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------+
+        | round |
+        +-------+
+        | 1     |
+        |       |
+        +-------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        decimal_field("x"),
+        Field::new("scale", DataType::Int32, true),
+    ]);
+    let arg0 = StringArray::from(vec![Some("1.2345"), Some("1.2345")]);
+    let arg1 = Int32Array::from(vec![Some(2), None]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = round_decimal_int32_decimal_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-------+
+        | round |
+        +-------+
+        | 1.23  |
+        |       |
+        +-------+"#]],
+    );
+}
+
+#[test]
+fn test_geometry() {
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Float64, true),
+        Field::new("y", DataType::Float64, true),
+    ]);
+    let arg0 = Float64Array::from(vec![Some(1.5), None]);
+    let arg1 = Float64Array::from(vec![Some(2.0), Some(3.0)]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+
+    let output = st_point_float64_float64_geometry_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0), &geometry_field("st_point"));
+    check(
+        &[output.clone()],
+        expect![[r#"
+        +--------------------------------------------+
+        | st_point                                   |
+        +--------------------------------------------+
+        | 0101000000000000000000f83f0000000000000040 |
+        |                                            |
+        +--------------------------------------------+"#]],
+    );
+
+    let output = st_x_geometry_float64_eval(&output).unwrap();
     check(
         &[output],
         expect![[r#"
-        +--------+
-        | add    |
-        +--------+
-        | 0.0003 |
-        +--------+"#]],
+        +------+
+        | st_x |
+        +------+
+        | 1.5  |
+        |      |
+        +------+"#]],
     );
 }
 
@@ -586,7 +2938,10 @@ fn test_json() {
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
     let output = to_json_int32_json_eval(&input).unwrap();
-    assert_eq!(output.schema().field(0), &json_field("to_json"));
+    assert_eq!(
+        output.schema().field(0),
+        &json_field("to_json").with_nullable(false)
+    );
     check(
         &[output],
         expect![[r#"
@@ -599,13 +2954,76 @@ fn test_json() {
     );
 }
 
+#[function("json_is_array(json_text) -> boolean")]
+fn json_is_array(s: &str) -> bool {
+    s.trim_start().starts_with('[')
+}
+
+#[test]
+fn test_json_text() {
+    let schema = Schema::new(vec![json_field("x")]);
+    let arg0 = StringArray::from(vec![Some(" [1, 2]"), Some(r#"{"a": [1]}"#), None]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // the text is passed through as is, without being reformatted
+    let output = identity_json_text_json_text_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0), &json_field("identity"));
+    assert_eq!(&**output.column(0), &**input.column(0));
+
+    let output = json_is_array_json_text_boolean_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +---------------+
+        | json_is_array |
+        +---------------+
+        | true          |
+        | false         |
+        |               |
+        +---------------+"#]],
+    );
+}
+
+#[test]
+fn test_json_writer() {
+    let schema = Schema::new(vec![Field::new(
+        "x",
+        DataType::new_list(DataType::Int32, true),
+        true,
+    )]);
+    let arg0 = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), Some(2), Some(3)]),
+        Some(vec![]),
+        None,
+    ]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = to_json_array_int32array_json_eval(&input).unwrap();
+    assert_eq!(output.schema().field(0), &json_field("to_json_array"));
+    check(
+        &[output],
+        expect![[r#"
+        +---------------+
+        | to_json_array |
+        +---------------+
+        | [1,2,3]       |
+        | []            |
+        |               |
+        +---------------+"#]],
+    );
+}
+
 #[test]
 fn test_range() {
     let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
     let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
     let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
 
-    let output = range_int32_int32_eval(&input).unwrap().next().unwrap();
+    let output = range_int32_int32_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
     check(
         &[output],
         expect![[r#"
@@ -627,6 +3045,7 @@ fn test_range() {
     let mut i = 0;
     for output in range_int32_int32_eval(&input).unwrap() {
         let array = output
+            .unwrap()
             .column(1)
             .as_any()
             .downcast_ref::<Int32Array>()
@@ -638,6 +3057,272 @@ fn test_range() {
     }
 }
 
+#[test]
+fn test_table_function_decimal() {
+    // arguments with a different scale are rescaled
+    let schema = Schema::new(vec![Field::new("x", DataType::Decimal128(10, 1), true)]);
+    let arg0 = Decimal128Array::from(vec![Some(123), None])
+        .with_precision_and_scale(10, 1)
+        .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = split_decimal_decimal128_10_2_int32_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+---------------+-------+
+        | row | split_decimal | error |
+        +-----+---------------+-------+
+        | 0   | 12            |       |
+        | 0   | 30            |       |
+        +-----+---------------+-------+"#]],
+    );
+}
+
+#[test]
+fn test_no_row_index() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    // the default mode emits the `row` column
+    let output = range_int32_int32_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(output.schema().field(0).name(), "row");
+    assert_eq!(output.num_columns(), 2);
+
+    let output = range_values_int32_int32_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------------+
+        | range_values |
+        +--------------+
+        | 0            |
+        | 0            |
+        | 1            |
+        | 2            |
+        +--------------+"#]],
+    );
+
+    // for large set, the output is split into multiple batches
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1000000]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let outputs: Vec<_> = range_values_int32_int32_eval(&input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert!(outputs.len() > 1);
+    assert!(outputs.iter().all(|b| b.num_columns() == 1));
+    let num_rows: usize = outputs.iter().map(|b| b.num_rows()).sum();
+    assert_eq!(num_rows, 1000000);
+}
+
+#[test]
+fn test_batch_range() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let outputs: Vec<_> = batch_range_int32_int32_eval(&input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+-------------+
+        | row | batch_range |
+        +-----+-------------+
+        | 0   | 0           |
+        | 2   | 0           |
+        | 2   | 1           |
+        | 2   | 2           |
+        +-----+-------------+"#]],
+    );
+}
+
+#[test]
+fn test_batches_schema_mismatch() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![1]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let err = bad_batches_int32_int32_eval(&input)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Schema error: table function `bad_batches` yielded a batch that does not match the declared schema"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_columnar() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Float64, true)]);
+    let arg0 = Float64Array::from(vec![Some(1.0), None, Some(2.0), Some(6.0)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = minus_mean_float64_float64_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+
+        | minus_mean |
+        +------------+
+        | -2.0       |
+        |            |
+        | -1.0       |
+        | 3.0        |
+        +------------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+        Field::new("z", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![Some(1), Some(2), None]);
+    let arg1 = Int32Array::from(vec![10, 20, 30]);
+    let arg2 = Int32Array::from(vec![100, 200, 300]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0), Arc::new(arg1), Arc::new(arg2)],
+    )
+    .unwrap();
+
+    let output = sum_columns_int32_variadic_int32_eval(&input).unwrap();
+    // errors are returned for the whole batch instead of an error column
+    assert_eq!(output.num_columns(), 1);
+    check(
+        &[output],
+        expect![[r#"
+        +-------------+
+        | sum_columns |
+        +-------------+
+        | 111         |
+        | 222         |
+        |             |
+        +-------------+"#]],
+    );
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Int32, true),
+        Field::new("y", DataType::Int32, true),
+    ]);
+    let arg0 = Int32Array::from(vec![i32::MAX, 0]);
+    let arg1 = Int32Array::from(vec![1, 0]);
+    let input =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0), Arc::new(arg1)]).unwrap();
+    let err = sum_columns_int32_variadic_int32_eval(&input).unwrap_err();
+    assert_eq!(err.to_string(), "Compute error: integer overflow");
+
+    let input = input.project(&[0]).unwrap();
+    let err = bad_columnar_int32_int32_eval(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Compute error: function `bad_columnar` returned 1 rows, expected 2"
+    );
+}
+
+#[test]
+fn test_window() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(3), Some(4)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    // ROWS BETWEEN 1 PRECEDING AND CURRENT ROW
+    let frames = [0..1, 0..2, 1..3, 2..4];
+
+    let output1 = row_number_int64_eval(&input, &frames).unwrap();
+    let output2 = lag_int32_int32_eval(&input, &frames).unwrap();
+    let output3 = frame_sum_int32_int64_eval(&input, &frames).unwrap();
+    let output = RecordBatch::try_from_iter([
+        ("row_number", output1.column(0).clone()),
+        ("lag", output2.column(0).clone()),
+        ("frame_sum", output3.column(0).clone()),
+    ])
+    .unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +------------+-----+-----------+
+        | row_number | lag | frame_sum |
+        +------------+-----+-----------+
+        | 1          |     | 1         |
+        | 2          | 1   | 1         |
+        | 3          |     | 3         |
+        | 4          | 3   | 7         |
+        +------------+-----+-----------+"#]],
+    );
+
+    let err = row_number_int64_eval(&input, &frames[..1]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid argument error: expect 4 frames, got 1"
+    );
+
+    // decimal arguments are not rescaled for window functions
+    let schema = Schema::new(vec![Field::new("x", DataType::Decimal128(10, 3), true)]);
+    let arg0 = Decimal128Array::from(vec![Some(1), None, Some(3), Some(4)])
+        .with_precision_and_scale(10, 3)
+        .unwrap();
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let err = frame_count_decimal128_10_2_int64_eval(&input, &frames).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cast error: expect scale 2 for the 0-th argument, got 3"
+    );
+}
+
+#[test]
+fn test_async_function() {
+    let schema = Schema::new(vec![Field::new("x", DataType::Int32, true)]);
+    let arg0 = Int32Array::from(vec![Some(1), None, Some(i32::MAX)]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let output = block_on(async_double_int32_int32_eval(&input)).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +--------------+
+        | async_double |
+        +--------------+
+        | 2            |
+        |              |
+        |              |
+        +--------------+"#]],
+    );
+}
+
+#[test]
+fn test_function_kind() {
+    let kinds = [
+        FunctionKind::Scalar(neg_int32_int32_eval),
+        FunctionKind::AsyncScalar(async_double_int32_int32_eval),
+        FunctionKind::Table(range_int32_int32_eval),
+        FunctionKind::Window(row_number_int64_eval),
+    ];
+    let names = kinds.iter().map(|k| k.kind_name()).collect::<Vec<_>>();
+    assert_eq!(names, ["scalar", "async_scalar", "table", "window"]);
+    assert!(kinds[1].is_async_scalar());
+    assert!(kinds[2].is_table());
+}
+
 #[test]
 fn test_json_array_elements() {
     let schema = Schema::new(vec![json_field("d")]);
@@ -647,6 +3332,7 @@ fn test_json_array_elements() {
     let output = json_array_elements_json_json_eval(&input)
         .unwrap()
         .next()
+        .unwrap()
         .unwrap();
     check(
         &[output],
@@ -662,18 +3348,367 @@ fn test_json_array_elements() {
     );
 }
 
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_rows_per_input() {
+    use arrow_udf::sig::REGISTRY;
+
+    let int32 = Field::new("", DataType::Int32, true);
+    let sig = REGISTRY.get("range", &[int32.clone()], &int32).unwrap();
+    assert_eq!(sig.rows_per_input, 10);
+    let sig = REGISTRY
+        .get("gcd", &[int32.clone(), int32.clone()], &int32)
+        .unwrap();
+    assert_eq!(sig.rows_per_input, 1);
+}
+
+#[test]
+fn test_max_rows_per_input() {
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec![Some("a,b"), None, Some("c"), Some("")]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+
+    let outputs: Vec<_> = split_pair_string_string_eval(&input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    check(
+        &outputs,
+        expect![[r#"
+        +-----+------------+
+        | row | split_pair |
+        +-----+------------+
+        | 0   | a          |
+        | 0   | b          |
+        | 2   | c          |
+        | 3   |            |
+        +-----+------------+"#]],
+    );
+
+    // each batch has at most 1024 rows
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a,b"; 1000]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let num_rows: Vec<_> = split_pair_string_string_eval(&input)
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .collect();
+    assert_eq!(num_rows, [1024, 976]);
+}
+
+#[test]
+fn test_max_rows_per_input_exceeded() {
+    let schema = Schema::new(vec![Field::new("s", DataType::Utf8, true)]);
+    let arg0 = StringArray::from(vec!["a", "a,b,c"]);
+    let input = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arg0)]).unwrap();
+    let mut outputs = split_pair_string_string_eval(&input).unwrap();
+    // the rows before the error are yielded first
+    let output = outputs.next().unwrap().unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +-----+------------+
+        | row | split_pair |
+        +-----+------------+
+        | 0   | a          |
+        | 1   | a          |
+        | 1   | b          |
+        +-----+------------+"#]],
+    );
+    let err = outputs.next().unwrap().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Compute error: table function `split_pair` produced more than 2 rows for input row 1"
+    );
+    // the iterator ends after the error
+    assert!(outputs.next().is_none());
+}
+
+#[test]
+fn test_greatest() {
+    let schema = Schema::new(vec![
+        Field::new("a", DataType::Int8, true),
+        Field::new("b", DataType::Int32, true),
+        Field::new("c", DataType::Int16, true),
+    ]);
+    let arg0 = Int8Array::from(vec![Some(1), None, None]);
+    let arg1 = Int32Array::from(vec![Some(100), Some(-1), None]);
+    let arg2 = Int16Array::from(vec![Some(10), Some(-10), None]);
+    let input = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![Arc::new(arg0), Arc::new(arg1), Arc::new(arg2)],
+    )
+    .unwrap();
+
+    let output = greatest_variadic_int64_eval(&input).unwrap();
+    check(
+        &[output],
+        expect![[r#"
+        +----------+
+        | greatest |
+        +----------+
+        | 100      |
+        | -1       |
+        |          |
+        +----------+"#]],
+    );
+}
+
+#[test]
+fn test_export_name() {
+    extern "C" {
+        #[link_name = "arrowudf_Z2NkKGludDMyLGludDMyKS0$aW50MzI"]
+        fn gcd_symbol(ptr: *const u8, len: usize, out: *mut arrow_udf::ffi::CSlice) -> i32;
+    }
+    assert_eq!(
+        GCD_INT32_INT32_INT32_EXPORT_NAME,
+        "arrowudf_Z2NkKGludDMyLGludDMyKS0$aW50MzI"
+    );
+    // the constant is the symbol of the FFI function
+    assert_eq!(gcd_symbol as usize, gcd_int32_int32_int32_ffi as usize);
+}
+
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_export_name_in_registry() {
+    use arrow_udf::sig::REGISTRY;
+
+    let int32 = Field::new("", DataType::Int32, true);
+    let sig = REGISTRY
+        .get("gcd", &[int32.clone(), int32.clone()], &int32)
+        .unwrap();
+    assert_eq!(
+        sig.export_name.as_deref(),
+        Some(GCD_INT32_INT32_INT32_EXPORT_NAME)
+    );
+}
+
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_type_infer() {
+    use arrow_udf::sig::REGISTRY;
+
+    let field = |ty| Field::new("", ty, true);
+    let int64 = field(DataType::Int64);
+    let args = [field(DataType::Int8), field(DataType::Int16)];
+    let sig = REGISTRY.get("greatest", &args, &int64).unwrap();
+    assert_eq!(
+        sig.infer_return_type(&args).unwrap().data_type(),
+        &DataType::Int16
+    );
+    // the inference function sees all arguments of the call
+    let args = [
+        field(DataType::Int8),
+        field(DataType::Int16),
+        field(DataType::Int32),
+    ];
+    assert_eq!(
+        sig.infer_return_type(&args).unwrap().data_type(),
+        &DataType::Int32
+    );
+    assert_eq!(
+        sig.infer_return_type(&[field(DataType::Int8)])
+            .unwrap()
+            .data_type(),
+        &DataType::Int8
+    );
+    assert!(sig.infer_return_type(&[field(DataType::Utf8)]).is_err());
+
+    // a function is registered for each number of arguments
+    let decimal = decimal_field("");
+    assert!(REGISTRY
+        .get("round", &[decimal.clone()], &decimal)
+        .is_some());
+    assert!(REGISTRY
+        .get(
+            "round",
+            &[decimal.clone(), field(DataType::Int32)],
+            &decimal
+        )
+        .is_some());
+
+    // `any` matches all types and `anyarray` matches all lists
+    let sig = REGISTRY.get(
+        "array_fill",
+        &[field(DataType::Utf8), field(DataType::Int32)],
+        &field(DataType::new_list(DataType::Utf8, true)),
+    );
+    assert!(sig.is_some());
+
+    // functions without type inference return the declared type
+    let int32 = field(DataType::Int32);
+    let sig = REGISTRY
+        .get("gcd", &[int32.clone(), int32.clone()], &int32)
+        .unwrap();
+    assert_eq!(
+        sig.infer_return_type(&[int32.clone(), int32.clone()])
+            .unwrap(),
+        int32
+    );
+    assert!(sig.infer_return_type(&[int32.clone()]).is_err());
+}
+
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_catalog_json() {
+    let catalog: serde_json::Value = serde_json::from_str(&arrow_udf::sig::catalog_json()).unwrap();
+    let functions = catalog.as_array().unwrap();
+    let names = functions
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(names.windows(2).all(|w| w[0] <= w[1]), "not sorted");
+
+    let function = functions
+        .iter()
+        .find(|f| f["name"] == "key_value_list")
+        .unwrap();
+    assert_eq!(
+        function,
+        &serde_json::json!({
+            "name": "key_value_list",
+            "kind": "scalar",
+            "arg_types": ["Utf8"],
+            "variadic": false,
+            "return_type": {
+                "list": {
+                    "struct": [
+                        { "name": "key", "type": "Utf8" },
+                        { "name": "value", "type": "Utf8" },
+                    ]
+                }
+            },
+            "volatile": false,
+            "export_name": KEY_VALUE_LIST_STRING_STRUCT_KEYVALUEARRAY_EXPORT_NAME,
+        })
+    );
+    let function = functions.iter().find(|f| f["name"] == "concat_ws").unwrap();
+    assert_eq!(function["variadic"], true);
+    assert_eq!(function["arg_types"], serde_json::json!(["Utf8"]));
+
+    // async functions are not exported
+    let function = functions
+        .iter()
+        .find(|f| f["name"] == "async_double")
+        .unwrap();
+    assert_eq!(function["kind"], "async_scalar");
+    assert_eq!(function["export_name"], serde_json::Value::Null);
+}
+
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_write_catalog() {
+    let path = std::env::temp_dir().join(format!("arrow-udf-catalog-{}.json", std::process::id()));
+    std::fs::write(&path, "stale").unwrap();
+    arrow_udf::sig::write_catalog(&path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        arrow_udf::sig::catalog_json()
+    );
+    // writing the same catalog again is a no-op
+    arrow_udf::sig::write_catalog(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "global_registry")]
+fn test_parse_signature() {
+    use arrow_udf::sig::{parse_signature, ParsedSignature};
+
+    assert_eq!(
+        parse_signature("key_values(map<string,int32>,struct KeyValue[])->>struct KeyValue")
+            .unwrap(),
+        ParsedSignature {
+            name: "key_values".into(),
+            arg_types: vec!["map<string,int32>".into(), "struct KeyValue[]".into()],
+            variadic: false,
+            return_type: "struct KeyValue".into(),
+            is_table_function: true,
+        }
+    );
+    let sig = parse_signature("add(decimal128(10,2),decimal128(10,2))->decimal128(11,2)").unwrap();
+    assert_eq!(sig.arg_types, ["decimal128(10,2)", "decimal128(10,2)"]);
+    assert_eq!(sig.return_type, "decimal128(11,2)");
+    let sig = parse_signature("pair()->int32").unwrap();
+    assert!(sig.arg_types.is_empty());
+    assert!(!sig.variadic);
+    let sig = parse_signature("concat(...)->string").unwrap();
+    assert!(sig.arg_types.is_empty());
+    assert!(sig.variadic);
+
+    for s in [
+        "",
+        "f",
+        "(int32)->int32",
+        "f(int32)",
+        "f(int32)->",
+        "f(...,int32)->int32",
+        "f(,)->int32",
+    ] {
+        assert!(parse_signature(s).is_err(), "{s:?} should be invalid");
+    }
+}
+
 /// Compare the actual output with the expected output.
 #[track_caller]
 fn check(actual: &[RecordBatch], expect: Expect) {
     expect.assert_eq(&pretty_format_batches(actual).unwrap().to_string());
 }
 
+/// Poll a future to completion on the current thread.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    struct ThreadWaker(std::thread::Thread);
+
+    impl std::task::Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(output) => return output,
+            std::task::Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// Encode record batches to an IPC file buffer.
+fn encode_ipc(batches: &[RecordBatch]) -> Vec<u8> {
+    let mut buf = vec![];
+    let mut writer =
+        arrow_ipc::writer::FileWriter::try_new(&mut buf, &batches[0].schema()).unwrap();
+    for batch in batches {
+        writer.write(batch).unwrap();
+    }
+    writer.finish().unwrap();
+    drop(writer);
+    buf
+}
+
+/// Decode record batches from an IPC file buffer.
+fn decode_ipc(buf: &[u8]) -> Vec<RecordBatch> {
+    arrow_ipc::reader::FileReader::try_new(std::io::Cursor::new(buf), None)
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect()
+}
+
 /// Returns a field with JSON type.
 fn json_field(name: &str) -> Field {
     Field::new(name, DataType::Utf8, true)
         .with_metadata([("ARROW:extension:name".into(), "arrowudf.json".into())].into())
 }
 
+/// Returns a field with geometry type.
+fn geometry_field(name: &str) -> Field {
+    Field::new(name, DataType::Binary, true)
+        .with_metadata([("ARROW:extension:name".into(), "geoarrow.wkb".into())].into())
+}
+
 /// Returns a field with decimal type.
 fn decimal_field(name: &str) -> Field {
     Field::new(name, DataType::Utf8, true)